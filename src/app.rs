@@ -1,6 +1,7 @@
 use anyhow::Result;
 use crate::component::ConnectionMsg;
-use crate::component::{Component, DBListMsg, DashboardMsg, RootComponent, RootMsg, TableMsg};
+use crate::connection::Connection;
+use crate::component::{Component, DBListMsg, DashboardMsg, PendingTable, RootComponent, RootMsg, TableMsg};
 use crate::terminal::Terminal;
 use crate::update::Command;
 use crate::update::MapMsg;
@@ -10,17 +11,18 @@ use crossterm::event::{self, Event, KeyCode};
 use ratatui::prelude::Backend;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[allow(dead_code)]
 pub enum AppMsg {
     Quit,
-    Root(RootMsg),
+    // Boxed: RootMsg carries whole Connections and would bloat every AppMsg
+    Root(Box<RootMsg>),
 }
 
 impl From<RootMsg> for AppMsg {
     fn from(msg: RootMsg) -> Self {
-        AppMsg::Root(msg)
+        AppMsg::Root(Box::new(msg))
     }
 }
 
@@ -46,12 +48,22 @@ impl From<ConnectionMsg> for AppMsg {
     }
 }
 
+/// Where to start instead of the connection list (`--connection`, `--table`)
+pub struct Launch {
+    pub connection: Connection,
+    pub table: Option<PendingTable>,
+}
+
+/// How often the app wakes up without input, to animate the loading spinner
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 pub struct App<B: Backend> {
     term: Terminal<B>,
     root: RootComponent,
     rx: Receiver<AppMsg>,
     tx: Sender<AppMsg>,
     should_quit: bool,
+    last_tick: Instant,
 }
 
 impl<B: Backend> App<B> {
@@ -59,10 +71,11 @@ impl<B: Backend> App<B> {
         let (tx, rx) = std::sync::mpsc::channel();
         Ok(Self {
             term,
-            root: RootComponent::new()?,
+            root: RootComponent::new(),
             rx,
             tx,
             should_quit: false,
+            last_tick: Instant::now(),
         })
     }
 
@@ -71,10 +84,28 @@ impl<B: Backend> App<B> {
             self.handle_async_messages();
             self.draw()?;
             self.handle_event()?;
+            self.tick();
         }
         Ok(())
     }
 
+    /// Go straight to the dashboard of `launch.connection`, opening
+    /// `launch.table` once the tree has loaded
+    fn launch(&mut self, launch: Launch) {
+        self.handle_update(Update::msg(AppMsg::from(RootMsg::ConnectionSelected(launch.connection))));
+        if let Some(table) = launch.table {
+            self.handle_update(Update::msg(AppMsg::from(DashboardMsg::OpenOnLoad(table))));
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.last_tick.elapsed() < TICK_RATE {
+            return;
+        }
+        self.last_tick = Instant::now();
+        self.handle_update(Update::msg(AppMsg::from(RootMsg::Tick)));
+    }
+
 
     fn draw(&mut self) -> Result<()> {
         self.term.draw(|f| {
@@ -84,20 +115,31 @@ impl<B: Backend> App<B> {
     }
 
     fn handle_event(&mut self) -> Result<()> {
-        if !event::poll(Duration::from_millis(250))? {
+        // Wait for input until the next tick is due
+        if !event::poll(TICK_RATE.saturating_sub(self.last_tick.elapsed()))? {
             return Ok(());
         }
+        self.on_event(event::read()?)
+    }
 
-        let Event::Key(key) = event::read()? else {
-            return Ok(());
+    fn on_event(&mut self, event: Event) -> Result<()> {
+        let update = match event {
+            Event::Key(key) => {
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    self.should_quit = true;
+                    return Ok(());
+                }
+                self.root.handle_key(key).map_auto()
+            }
+            Event::Mouse(mouse) => self.root.handle_mouse(mouse).map_auto(),
+            // Redraw right away; every view lays out and clamps its scrolling
+            // against the area it's given at draw time
+            Event::Resize(_, _) => {
+                self.term.resize()?;
+                return self.draw();
+            }
+            _ => return Ok(()),
         };
-
-        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            self.should_quit = true;
-            return Ok(());
-        }
-
-        let update = self.root.handle_key(key).map_auto();
         self.handle_update(update);
 
         Ok(())
@@ -117,7 +159,7 @@ impl<B: Backend> App<B> {
                 self.should_quit = true;
                 Update::none()
             }
-            AppMsg::Root(m) => self.root.update(m).map_auto(),
+            AppMsg::Root(m) => self.root.update(*m).map_auto(),
         }
     }
 
@@ -152,7 +194,33 @@ impl<B: Backend> App<B> {
     }
 }
 
-pub fn run_app<B: Backend>(terminal: Terminal<B>) -> Result<()> {
+pub fn run_app<B: Backend>(terminal: Terminal<B>, launch: Option<Launch>) -> Result<()> {
     let mut app = App::new(terminal)?;
+    if let Some(launch) = launch {
+        app.launch(launch);
+    }
     app.run()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::ScreenMode;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn resize_redraws_at_the_new_size() {
+        let terminal = ratatui::Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let mut app = App::new(Terminal::new(terminal, ScreenMode::Alternate)).unwrap();
+        app.draw().unwrap();
+
+        app.term.backend_mut().resize(80, 20);
+        app.on_event(Event::Resize(80, 20)).unwrap();
+
+        // No key press needed: the frame is already repainted over the whole new area
+        let buffer = app.term.backend().buffer();
+        assert_eq!((buffer.area.width, buffer.area.height), (80, 20));
+        let mut grown = buffer.content.iter().enumerate().filter(|(i, _)| i % 80 >= 40 || i / 80 >= 10);
+        assert!(grown.any(|(_, cell)| cell.symbol() != " "));
+    }
+}
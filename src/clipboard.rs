@@ -0,0 +1,20 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+/// The system clipboard, opened on first use. It stays open for the rest of
+/// the run: on X11 and Wayland the copied text is served by whoever owns the
+/// clipboard, so dropping it right after a copy would lose the text.
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Put `text` on the system clipboard
+pub fn copy(text: &str) -> Result<()> {
+    let mut clipboard = CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner());
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new()?);
+    }
+    if let Some(clipboard) = clipboard.as_mut() {
+        clipboard.set_text(text)?;
+    }
+    Ok(())
+}
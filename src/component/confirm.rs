@@ -0,0 +1,103 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use super::{Component, RootMsg};
+use crate::update::Update;
+
+/// A yes/no question asked before a destructive action. `on_confirm` is sent
+/// once the user agrees; nothing happens when they decline.
+pub struct ConfirmRequest {
+    title: String,
+    prompt: String,
+    on_confirm: Box<RootMsg>,
+}
+
+impl ConfirmRequest {
+    pub fn new(title: impl Into<String>, prompt: impl Into<String>, on_confirm: impl Into<RootMsg>) -> Self {
+        Self {
+            title: title.into(),
+            prompt: prompt.into(),
+            on_confirm: Box::new(on_confirm.into()),
+        }
+    }
+}
+
+pub enum ConfirmMsg {
+    /// Send the pending message; handled by the root
+    Accept,
+    Cancel,
+}
+
+/// Modal prompt drawn over the whole screen; it takes every key while open
+#[derive(Default)]
+pub struct ConfirmComponent {
+    pending: Option<ConfirmRequest>,
+}
+
+impl ConfirmComponent {
+    pub fn is_open(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn open(&mut self, request: ConfirmRequest) {
+        self.pending = Some(request);
+    }
+
+    /// Close the prompt, returning the message it was guarding
+    pub fn accept(&mut self) -> Option<RootMsg> {
+        self.pending.take().map(|request| *request.on_confirm)
+    }
+}
+
+impl Component for ConfirmComponent {
+    type Msg = ConfirmMsg;
+
+    fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
+        match msg {
+            ConfirmMsg::Accept => {} // Handled by parent
+            ConfirmMsg::Cancel => self.pending = None,
+        }
+        Update::none()
+    }
+
+    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        use crossterm::event::KeyCode::*;
+
+        match key.code {
+            Char('y') | Enter => ConfirmMsg::Accept.into(),
+            Char('n') | Esc => ConfirmMsg::Cancel.into(),
+            _ => Update::none(),
+        }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) {
+        let Some(request) = &self.pending else {
+            return;
+        };
+        let text = format!("{}\n\ny/Enter: confirm   n/Esc: cancel", request.prompt);
+        let width = area.width.saturating_mul(3) / 5;
+        // Wrapped line count, plus the borders
+        let inner_width = usize::from(width.saturating_sub(2)).max(1);
+        let lines: usize = text.lines().map(|l| l.chars().count().div_ceil(inner_width).max(1)).sum();
+        let height = u16::try_from(lines + 2).unwrap_or(u16::MAX).min(area.height.saturating_mul(4) / 5);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+        let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .title(request.title.as_str())
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red)),
+        );
+        f.render_widget(paragraph, popup);
+    }
+}
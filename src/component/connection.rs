@@ -4,68 +4,494 @@ use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::Span,
-    widgets::{Block, Borders, List, ListItem, ListState},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
 
-use super::Component;
-use crate::{update::Update, db::DBBehavior};
-use crate::{connection::Connection, connection::load_connections, db::DB};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use super::{Component, ConfirmRequest};
+use crate::app::AppMsg;
+use crate::config::Config;
+use crate::logger::error;
+use crate::settings::TreeIcons;
+use crate::status::StatusMessage;
+use crate::keymap::{Action, Keymap};
+use crate::theme::Theme;
+use crate::{update::{Command, Update}, db::DBBehavior};
+use crate::{connection::Connection, connection::load_connections, connection::secret, db::{self, DatabaseType, DB}};
 
 pub enum ConnectionMsg {
-    ConnectionSelected(Connection),
+    /// Boxed: a Connection dwarfs every other variant
+    ConnectionSelected(Box<Connection>),
     MoveUp,
     MoveDown,
     MoveTop,
     MoveBottom,
     MovePageUp,
     MovePageDown,
+    /// Unfold / fold the group under the cursor
+    ExpandGroup,
+    FoldGroup,
+    ToggleGroup,
+    /// Write the example config on first run
+    GenerateConfig,
+    ConfigGenerated { path: PathBuf, items: Vec<Connection> },
+    ConfigGenerateFailed(String),
+    /// Open the editor on a new connection / the selected one
+    AddConnection,
+    EditConnection,
+    FormFieldBy(i32),
+    FormCycleType(i32),
+    FormPush(char),
+    FormPop,
+    /// Validate the editor and write it to the config file
+    SaveForm,
+    CancelForm,
+    /// Ask to remove the selected connection from the config file
+    DeleteConnection,
+    /// Remove the saved connection at this index, once confirmed
+    ConfirmDelete(usize),
+    /// The config file was rewritten; `select` is the index to select
+    ConnectionsSaved { items: Vec<Connection>, saved_count: usize, select: usize },
+    SaveFailed(String),
+    /// Check that the selected connection answers
+    TestConnection,
+    ConnectionTested { name: String, result: Result<(), String> },
+    /// Copy the selected connection's URL (or file, for SQLite and DuckDB),
+    /// including the password when `true`
+    CopyUrl(bool),
+    /// The URL to copy, or why it couldn't be built
+    UrlResolved { name: String, result: Result<String, String> },
+    /// Show a message in the status bar; handled by the root
+    Status(StatusMessage),
+    /// Ask before a destructive action; handled by the root
+    Confirm(ConfirmRequest),
+}
+
+/// Result of the `t` connectivity check
+enum PingStatus {
+    Testing,
+    Ok,
+    Failed(String),
+}
+
+/// Heading of the connections without a `group`, once any connection has one
+const UNGROUPED: &str = "Ungrouped";
+
+/// A line of the connection list
+enum Row<'a> {
+    /// Heading of a group; `first` is the index of its first connection
+    Group { name: &'a str, first: usize, count: usize, collapsed: bool },
+    Connection(usize),
+}
+
+/// Text fields of the connection editor, after the `type` selector
+const FORM_FIELDS: [&str; 8] = ["name", "host", "port", "user", "password", "keyring", "database", "path"];
+
+/// Connection being added or edited. Settings the editor doesn't cover
+/// (ssh, sslmode, ...) are kept from the edited connection.
+struct ConnectionForm {
+    /// Index in the saved connections; `None` adds one
+    index: Option<usize>,
+    base: Connection,
+    r#type: DatabaseType,
+    values: [String; 8],
+    /// 0 is the type selector, then `FORM_FIELDS`
+    selected: usize,
+    error: Option<String>,
+}
+
+impl ConnectionForm {
+    fn new(index: Option<usize>, base: Connection) -> Self {
+        let values = [
+            base.name.clone().unwrap_or_default(),
+            base.host.clone().unwrap_or_default(),
+            base.port.map(|p| p.to_string()).unwrap_or_default(),
+            base.user.clone().unwrap_or_default(),
+            base.password.clone().unwrap_or_default(),
+            base.password_keyring.clone().unwrap_or_default(),
+            base.database.clone().unwrap_or_default(),
+            base.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default(),
+        ];
+        Self { index, r#type: base.r#type, base, values, selected: 0, error: None }
+    }
+
+    /// The edited connection, or why it can't be saved. With a keyring entry
+    /// set, a typed password is returned to be stored there instead of in the file.
+    fn connection(&self) -> Result<(Connection, Option<String>), String> {
+        let value = |i: usize| {
+            let v = self.values[i].trim();
+            (!v.is_empty()).then(|| v.to_string())
+        };
+        let [name, host, port, user, _, keyring, database, path] = [0, 1, 2, 3, 4, 5, 6, 7].map(value);
+        if name.is_none() {
+            return Err("name is required".to_string());
+        }
+        // A `url` (or MySQL `socket`) set in the file stands in for host/path
+        let has_url = self.base.url.is_some();
+        if self.r#type.is_file() && path.is_none() && !has_url {
+            return Err(format!("path is required for {}", self.r#type.as_str()));
+        }
+        let has_socket = self.r#type == DatabaseType::MySql && self.base.socket.is_some();
+        // Postgres falls back to PGHOST, then localhost
+        let host_optional = has_url || has_socket || self.r#type == DatabaseType::Postgres;
+        if !self.r#type.is_file() && host.is_none() && !host_optional {
+            return Err(format!("host is required for {}", self.r#type.as_str()));
+        }
+        let port = match port {
+            Some(p) => Some(p.parse::<u64>().map_err(|_| format!("port must be a number, not {}", p))?),
+            None => None,
+        };
+        let mut conn = self.base.clone();
+        conn.r#type = self.r#type;
+        conn.name = name;
+        conn.host = host;
+        conn.port = port;
+        conn.user = user;
+        // Keep the password exactly as typed
+        let password = (!self.values[4].is_empty()).then(|| self.values[4].clone());
+        conn.database = database;
+        conn.path = path.map(PathBuf::from);
+        conn.password_keyring = keyring;
+        if conn.password_keyring.is_some() {
+            // An empty password leaves the stored one alone
+            conn.password = None;
+            return Ok((conn, password));
+        }
+        conn.password = password;
+        Ok((conn, None))
+    }
 }
 
 pub struct ConnectionComponent {
     items: Vec<Connection>,
     selected: usize,
+    /// Leading `items` that come from `Config::connections_path()`; only
+    /// these can be edited or deleted here
+    saved_count: usize,
+    /// Where the example config would go; only set while no config file exists
+    scaffold_path: Option<PathBuf>,
+    /// Why the config couldn't be read; the list starts empty instead
+    load_error: Option<String>,
+    notice: Option<String>,
+    form: Option<ConnectionForm>,
+    /// Connectivity check results by connection name
+    status: HashMap<String, PingStatus>,
+    /// Folded groups, by heading
+    collapsed: HashSet<String>,
+    icons: TreeIcons,
+    theme: Theme,
+    keymap: Keymap,
 }
 
 impl ConnectionComponent {
-    pub fn new() -> Result<Self> {
-        Ok(Self {
-            items: load_connections()?,
+    /// `load_error` is why the config couldn't be read, shown in place of the list
+    pub fn new(
+        items: Vec<Connection>,
+        load_error: Option<String>,
+        icons: TreeIcons,
+        theme: Theme,
+        keymap: Keymap,
+    ) -> Self {
+        let (saved_count, load_error) = match Config::saved_connections() {
+            Ok(saved) => (saved.len(), load_error),
+            Err(e) => (0, load_error.or_else(|| Some(format!("{:#}", e)))),
+        };
+        let mut component = Self {
+            items,
             selected: 0,
+            saved_count,
+            scaffold_path: Config::connections_path().ok().filter(|path| !path.exists()),
+            load_error,
+            notice: None,
+            form: None,
+            status: HashMap::new(),
+            collapsed: HashSet::new(),
+            icons,
+            theme,
+            keymap,
+        };
+        // Ungrouped connections are listed last, so the first one may not be on top
+        component.move_top();
+        component
+    }
+
+    /// Whether the list is drawn under group headings: only once some
+    /// connection has a `group`
+    fn grouped(&self) -> bool {
+        self.items.iter().any(|c| c.group.is_some())
+    }
+
+    fn group_of(conn: &Connection) -> &str {
+        conn.group.as_deref().unwrap_or(UNGROUPED)
+    }
+
+    /// Lines of the list: the connections in config order, or under group
+    /// headings (groups in order of appearance, then the ungrouped ones)
+    fn rows(&self) -> Vec<Row<'_>> {
+        if !self.grouped() {
+            return (0..self.items.len()).map(Row::Connection).collect();
+        }
+        let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+        let mut ungrouped = Vec::new();
+        for (i, conn) in self.items.iter().enumerate() {
+            match conn.group.as_deref() {
+                None => ungrouped.push(i),
+                Some(name) => match groups.iter_mut().find(|(group, _)| *group == name) {
+                    Some((_, members)) => members.push(i),
+                    None => groups.push((name, vec![i])),
+                },
+            }
+        }
+        if !ungrouped.is_empty() {
+            groups.push((UNGROUPED, ungrouped));
+        }
+        let mut rows = Vec::new();
+        for (name, members) in groups {
+            let collapsed = self.collapsed.contains(name);
+            rows.push(Row::Group { name, first: members[0], count: members.len(), collapsed });
+            if !collapsed {
+                rows.extend(members.into_iter().map(Row::Connection));
+            }
+        }
+        rows
+    }
+
+    /// Where the cursor can rest, in list order. Headings of unfolded groups
+    /// are skipped; a folded group is one stop, held by its first connection.
+    fn stops(&self) -> Vec<usize> {
+        self.rows()
+            .into_iter()
+            .filter_map(|row| match row {
+                Row::Connection(i) => Some(i),
+                Row::Group { first, collapsed: true, .. } => Some(first),
+                Row::Group { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Whether `conn` is folded away under its group's heading
+    fn is_hidden(&self, conn: &Connection) -> bool {
+        self.grouped() && self.collapsed.contains(Self::group_of(conn))
+    }
+
+    /// Fold or unfold the group of the connection under the cursor
+    fn set_group_collapsed(&mut self, collapsed: bool) {
+        let Some(group) = self.items.get(self.selected).filter(|_| self.grouped()).map(Self::group_of) else {
+            return;
+        };
+        let group = group.to_string();
+        if collapsed {
+            // The heading that replaces the group stands for its first connection
+            self.selected = self.items.iter().position(|c| Self::group_of(c) == group).unwrap_or(self.selected);
+            self.collapsed.insert(group);
+        } else {
+            self.collapsed.remove(&group);
+        }
+    }
+
+    fn toggle_group(&mut self) {
+        let hidden = self.items.get(self.selected).is_some_and(|c| self.is_hidden(c));
+        self.set_group_collapsed(!hidden);
+    }
+
+    /// Index of the selected connection among the saved ones, or a notice
+    /// saying why it can't be changed here
+    fn selected_saved(&self) -> Result<usize, String> {
+        match self.selected_connection() {
+            None => Err("no connection selected".to_string()),
+            Some(_) if self.selected < self.saved_count => Ok(self.selected),
+            Some(_) => Err("defined in another config file; edit that file instead".to_string()),
+        }
+    }
+
+    /// Apply `change` to the saved connections in a background task, write
+    /// them back and reload the merged list. `change` returns the index to select.
+    fn write_connections(
+        change: impl FnOnce(&mut Vec<Connection>) -> Result<usize> + Send + 'static,
+    ) -> Command {
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let result = Config::saved_connections().and_then(|mut saved| {
+                let select = change(&mut saved)?;
+                Config::save_connections(&saved)?;
+                Ok(ConnectionMsg::ConnectionsSaved { items: load_connections()?, saved_count: saved.len(), select })
+            });
+            let msg = match result {
+                Ok(msg) => msg.into(),
+                Err(e) => {
+                    error(&format!("Connection: saving config failed: {}", e));
+                    ConnectionMsg::SaveFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn test_connection(&mut self) -> Command {
+        let Some(conn) = self.selected_connection().cloned() else {
+            return Command::none();
+        };
+        let name = conn.name.clone().unwrap_or_default();
+        self.status.insert(name.clone(), PingStatus::Testing);
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let result = DB::ping(&conn).map_err(|e| format!("{:#}", e));
+            let _ = tx.send(ConnectionMsg::ConnectionTested { name, result }.into());
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    /// Build the selected connection's URL in the background: reading the
+    /// password may ask a keyring or run a command
+    fn resolve_url(&self, with_password: bool) -> Command {
+        let Some(conn) = self.selected_connection().cloned() else {
+            return Command::none();
+        };
+        let name = conn.name.clone().unwrap_or_default();
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let result = db::shareable_location(&conn, with_password).map_err(|e| format!("{:#}", e));
+            let _ = tx.send(ConnectionMsg::UrlResolved { name, result }.into());
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn save_form(&mut self) -> Command {
+        let Some(form) = &mut self.form else {
+            return Command::none();
+        };
+        let (conn, secret) = match form.connection() {
+            Ok(edited) => edited,
+            Err(e) => {
+                form.error = Some(e);
+                return Command::none();
+            }
+        };
+        let index = form.index;
+        self.form = None;
+        Self::write_connections(move |saved| {
+            if let (Some(key), Some(password)) = (&conn.password_keyring, &secret) {
+                secret::store(key, password)?;
+            }
+            match index {
+                Some(i) => {
+                    let slot = saved
+                        .get_mut(i)
+                        .ok_or_else(|| anyhow::anyhow!("the config file changed; reopen the editor"))?;
+                    *slot = conn;
+                    Ok(i)
+                }
+                None => {
+                    saved.push(conn);
+                    Ok(saved.len() - 1)
+                }
+            }
         })
     }
+
+    fn draw_form(&self, f: &mut Frame, area: Rect, form: &ConnectionForm) {
+        let width = area.width.saturating_mul(3) / 5;
+        let height = (FORM_FIELDS.len() as u16 + 3).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+        let type_line = if form.selected == 0 {
+            format!("< {} >", form.r#type.as_str())
+        } else {
+            form.r#type.as_str().to_string()
+        };
+        let lines: Vec<Line> = std::iter::once(("type", type_line))
+            .chain(FORM_FIELDS.iter().zip(&form.values).enumerate().map(|(i, (label, value))| {
+                let mut shown = if *label == "password" { "*".repeat(value.chars().count()) } else { value.clone() };
+                if form.selected == i + 1 {
+                    shown.push('_');
+                }
+                (*label, shown)
+            }))
+            .enumerate()
+            .map(|(i, (label, value))| {
+                let style = if i == form.selected {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                Line::styled(format!("{:>9}  {}", label, value), style)
+            })
+            .collect();
+        let title = match form.index {
+            Some(_) => "Edit connection",
+            None => "New connection",
+        };
+        let mut block = Block::default()
+            .title(format!("{}  (↑/↓ field; ←/→ type; Enter: save; Esc: cancel)", title))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border(true));
+        if let Some(e) = &form.error {
+            block = block.title_bottom(Line::styled(format!(" {} ", e), Style::default().fg(Color::Red)));
+        }
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+    fn generate_config() -> impl FnOnce(std::sync::mpsc::Sender<AppMsg>) + Send + 'static {
+        move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let result = Config::write_scaffold()
+                .and_then(|path| load_connections().map(|items| (path, items)));
+            let msg = match result {
+                Ok((path, items)) => ConnectionMsg::ConfigGenerated { path, items }.into(),
+                Err(e) => {
+                    error(&format!("Connection: config scaffold failed: {}", e));
+                    ConnectionMsg::ConfigGenerateFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        }
+    }
+    fn on_config_generated(&mut self, path: PathBuf, items: Vec<Connection>) {
+        self.items = items;
+        self.selected = 0;
+        self.scaffold_path = None;
+        self.load_error = None;
+        // The example connections are listed right away and can be edited here
+        self.notice = Some(format!(
+            "wrote {}; replace its example connections ({}: edit)",
+            path.display(),
+            self.keymap.describe(Action::EditConnection)
+        ));
+    }
+    /// The connection under the cursor; none while it rests on a folded group
     fn selected_connection(&self) -> Option<&Connection> {
-        self.items.get(self.selected)
+        self.items.get(self.selected).filter(|c| !self.is_hidden(c))
+    }
+    /// Move the cursor `delta` stops, staying within the list
+    fn move_by(&mut self, delta: isize) {
+        let stops = self.stops();
+        let Some(last) = stops.len().checked_sub(1) else {
+            return;
+        };
+        let pos = stops.iter().position(|&i| i == self.selected).unwrap_or(0);
+        self.selected = stops[pos.saturating_add_signed(delta).min(last)];
     }
     fn move_up(&mut self) {
-        if !self.items.is_empty() {
-            self.selected = self.selected.saturating_sub(1);
-        }
+        self.move_by(-1);
     }
     fn move_down(&mut self) {
-        if !self.items.is_empty() {
-            self.selected = (self.selected + 1).min(self.items.len() - 1);
-        }
+        self.move_by(1);
     }
     fn move_top(&mut self) {
-        if !self.items.is_empty() {
-            self.selected = 0;
-        }
+        self.move_by(isize::MIN);
     }
     fn move_bottom(&mut self) {
-        if !self.items.is_empty() {
-            self.selected = self.items.len() - 1;
-        }
+        self.move_by(isize::MAX);
     }
     fn move_page_up(&mut self) {
-        if !self.items.is_empty() {
-            self.selected = self.selected.saturating_sub(10);
-        }
+        self.move_by(-10);
     }
     fn move_page_down(&mut self) {
-        if !self.items.is_empty() {
-            self.selected = (self.selected + 10).min(self.items.len() - 1);
-        }
+        self.move_by(10);
     }
 }
 
@@ -80,25 +506,220 @@ impl Component for ConnectionComponent {
             ConnectionMsg::MoveBottom => self.move_bottom().into(),
             ConnectionMsg::MovePageUp => self.move_page_up().into(),
             ConnectionMsg::MovePageDown => self.move_page_down().into(),
+            ConnectionMsg::ExpandGroup => self.set_group_collapsed(false).into(),
+            ConnectionMsg::FoldGroup => self.set_group_collapsed(true).into(),
+            ConnectionMsg::ToggleGroup => self.toggle_group().into(),
             ConnectionMsg::ConnectionSelected(_) => Update::none(), // Handled by parent
+            ConnectionMsg::GenerateConfig => match self.scaffold_path {
+                Some(_) => Command::Spawn(Box::new(Self::generate_config())).into(),
+                None => Update::none(),
+            },
+            ConnectionMsg::ConfigGenerated { path, items } => self.on_config_generated(path, items).into(),
+            ConnectionMsg::ConfigGenerateFailed(e) => {
+                self.notice = Some(e.clone());
+                ConnectionMsg::Status(StatusMessage::error(format!("writing the config failed: {}", e))).into()
+            }
+            ConnectionMsg::AddConnection => {
+                self.form = Some(ConnectionForm::new(None, Connection::new(DatabaseType::Postgres)));
+                Update::none()
+            }
+            ConnectionMsg::EditConnection => {
+                match self.selected_saved() {
+                    Ok(i) => self.form = Some(ConnectionForm::new(Some(i), self.items[i].clone())),
+                    Err(e) => self.notice = Some(e),
+                }
+                Update::none()
+            }
+            ConnectionMsg::FormFieldBy(delta) => {
+                if let Some(form) = &mut self.form {
+                    form.selected = form.selected.saturating_add_signed(delta as isize).min(FORM_FIELDS.len());
+                }
+                Update::none()
+            }
+            ConnectionMsg::FormCycleType(delta) => {
+                if let Some(form) = &mut self.form {
+                    let all = DatabaseType::ALL;
+                    let i = all.iter().position(|t| *t == form.r#type).unwrap_or(0);
+                    form.r#type = all[(i as isize + delta as isize).rem_euclid(all.len() as isize) as usize];
+                }
+                Update::none()
+            }
+            ConnectionMsg::FormPush(c) => {
+                if let Some(form) = self.form.as_mut().filter(|f| f.selected > 0) {
+                    form.values[form.selected - 1].push(c);
+                }
+                Update::none()
+            }
+            ConnectionMsg::FormPop => {
+                if let Some(form) = self.form.as_mut().filter(|f| f.selected > 0) {
+                    form.values[form.selected - 1].pop();
+                }
+                Update::none()
+            }
+            ConnectionMsg::SaveForm => self.save_form().into(),
+            ConnectionMsg::CancelForm => {
+                self.form = None;
+                Update::none()
+            }
+            ConnectionMsg::DeleteConnection => match self.selected_saved() {
+                Ok(i) => {
+                    let name = self.items[i].name.clone().unwrap_or_default();
+                    let prompt = format!("Remove {} from the config file?", name);
+                    let request = ConfirmRequest::new("Delete connection", prompt, ConnectionMsg::ConfirmDelete(i));
+                    ConnectionMsg::Confirm(request).into()
+                }
+                Err(e) => {
+                    self.notice = Some(e);
+                    Update::none()
+                }
+            },
+            ConnectionMsg::ConfirmDelete(i) => Self::write_connections(move |saved| {
+                if i >= saved.len() {
+                    anyhow::bail!("the config file changed; nothing was deleted");
+                }
+                saved.remove(i);
+                Ok(i.min(saved.len().saturating_sub(1)))
+            })
+            .into(),
+            ConnectionMsg::Confirm(_) => Update::none(), // Handled by parent
+            ConnectionMsg::ConnectionsSaved { items, saved_count, select } => {
+                self.items = items;
+                self.saved_count = saved_count;
+                self.selected = select.min(self.items.len().saturating_sub(1));
+                // Show the saved connection even if its group was folded
+                self.set_group_collapsed(false);
+                self.scaffold_path = None;
+                self.load_error = None;
+                // Settings may have changed since the last check
+                self.status.clear();
+                self.notice = Config::connections_path()
+                    .ok()
+                    .map(|p| format!("saved {}", p.display()));
+                Update::none()
+            }
+            ConnectionMsg::SaveFailed(e) => {
+                self.notice = Some(e.clone());
+                ConnectionMsg::Status(StatusMessage::error(format!("saving connections failed: {}", e))).into()
+            }
+            ConnectionMsg::TestConnection => self.test_connection().into(),
+            ConnectionMsg::ConnectionTested { name, result } => {
+                let (status, message) = match result {
+                    Ok(()) => (PingStatus::Ok, StatusMessage::info(format!("{}: connection ok", name))),
+                    Err(e) => {
+                        let message = StatusMessage::error(format!("{}: {}", name, e));
+                        (PingStatus::Failed(e), message)
+                    }
+                };
+                self.status.insert(name, status);
+                ConnectionMsg::Status(message).into()
+            }
+            ConnectionMsg::CopyUrl(with_password) => self.resolve_url(with_password).into(),
+            ConnectionMsg::UrlResolved { name, result } => {
+                let status = match result.and_then(|url| crate::clipboard::copy(&url).map_err(|e| e.to_string())) {
+                    Ok(()) => StatusMessage::info(format!("{}: copied the connection URL", name)),
+                    Err(e) => {
+                        error(&format!("Connection: copying the URL of {} failed: {}", name, e));
+                        StatusMessage::error(format!("{}: copying the URL failed: {}", name, e))
+                    }
+                };
+                ConnectionMsg::Status(status).into()
+            }
+            ConnectionMsg::Status(_) => Update::none(), // Handled by parent
         }
     }
 
     fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
         use crossterm::event::KeyCode::*;
-        match key.code {
-            Enter => match self.selected_connection() {
-                Some(conn) => ConnectionMsg::ConnectionSelected(conn.clone()).into(),
+        if let Some(form) = &self.form {
+            return match key.code {
+                Enter => ConnectionMsg::SaveForm.into(),
+                Esc => ConnectionMsg::CancelForm.into(),
+                Up | BackTab => ConnectionMsg::FormFieldBy(-1).into(),
+                Down | Tab => ConnectionMsg::FormFieldBy(1).into(),
+                Left if form.selected == 0 => ConnectionMsg::FormCycleType(-1).into(),
+                Right | Char(' ') if form.selected == 0 => ConnectionMsg::FormCycleType(1).into(),
+                Backspace => ConnectionMsg::FormPop.into(),
+                Char(c) => ConnectionMsg::FormPush(c).into(),
+                _ => Update::none(),
+            };
+        }
+        let is = |action| self.keymap.is(action, &key);
+        let grouped = self.grouped();
+        if is(Action::Select) {
+            match self.selected_connection() {
+                Some(conn) => ConnectionMsg::ConnectionSelected(Box::new(conn.clone())).into(),
+                // On a folded group
+                None if grouped && !self.items.is_empty() => ConnectionMsg::ExpandGroup.into(),
                 None => Update::none(),
-            },
-            Up | Char('k') => ConnectionMsg::MoveUp.into(),
-            Down | Char('j') => ConnectionMsg::MoveDown.into(),
-            PageUp => ConnectionMsg::MovePageUp.into(),
-            PageDown => ConnectionMsg::MovePageDown.into(),
-            Home => ConnectionMsg::MoveTop.into(),
-            End => ConnectionMsg::MoveBottom.into(),
-            _ => Update::none(),
+            }
+        } else if grouped && is(Action::Expand) {
+            ConnectionMsg::ExpandGroup.into()
+        } else if grouped && is(Action::Fold) {
+            ConnectionMsg::FoldGroup.into()
+        } else if grouped && is(Action::ToggleExpand) {
+            ConnectionMsg::ToggleGroup.into()
+        } else if is(Action::MoveUp) {
+            ConnectionMsg::MoveUp.into()
+        } else if is(Action::MoveDown) {
+            ConnectionMsg::MoveDown.into()
+        } else if is(Action::PageUp) {
+            ConnectionMsg::MovePageUp.into()
+        } else if is(Action::PageDown) {
+            ConnectionMsg::MovePageDown.into()
+        } else if is(Action::GenerateConfig) && self.scaffold_path.is_some() {
+            ConnectionMsg::GenerateConfig.into()
+        } else if is(Action::MoveTop) {
+            ConnectionMsg::MoveTop.into()
+        } else if is(Action::MoveBottom) {
+            ConnectionMsg::MoveBottom.into()
+        } else if is(Action::AddConnection) {
+            ConnectionMsg::AddConnection.into()
+        } else if is(Action::EditConnection) {
+            ConnectionMsg::EditConnection.into()
+        } else if is(Action::DeleteConnection) {
+            ConnectionMsg::DeleteConnection.into()
+        } else if is(Action::TestConnection) {
+            ConnectionMsg::TestConnection.into()
+        } else if is(Action::CopyUrl) {
+            ConnectionMsg::CopyUrl(false).into()
+        } else if is(Action::CopyUrlWithPassword) {
+            ConnectionMsg::CopyUrl(true).into()
+        } else {
+            Update::none()
+        }
+    }
+
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        let mut keys = self.keymap.help(&[
+            (Action::Select, "Connect"),
+            (Action::MoveUp, "Move up"),
+            (Action::MoveDown, "Move down"),
+            (Action::PageUp, "Move up a page"),
+            (Action::PageDown, "Move down a page"),
+            (Action::MoveTop, "First connection"),
+            (Action::MoveBottom, "Last connection"),
+            (Action::AddConnection, "Add a connection"),
+            (Action::EditConnection, "Edit the connection"),
+            (Action::DeleteConnection, "Delete the connection"),
+            (Action::TestConnection, "Test the connection"),
+            (Action::CopyUrl, "Copy the URL, password masked (file path for SQLite/DuckDB)"),
+            (Action::CopyUrlWithPassword, "Copy the URL with the password"),
+        ]);
+        if self.grouped() {
+            keys.extend(self.keymap.help(&[
+                (Action::Expand, "Unfold the group"),
+                (Action::Fold, "Fold the group"),
+                (Action::ToggleExpand, "Fold/unfold the group"),
+            ]));
+        }
+        if self.scaffold_path.is_some() {
+            keys.extend(self.keymap.help(&[(Action::GenerateConfig, "Write an example config")]));
         }
+        keys
+    }
+
+    fn is_typing(&self) -> bool {
+        self.form.is_some()
     }
 
     fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) {
@@ -121,9 +742,12 @@ impl Component for ConnectionComponent {
             .split(outer[1])[1];
 
         let title = Span::styled(
-            "Connections",
+            match &self.notice {
+                Some(notice) => format!("Connections ({})", notice),
+                None => "Connections".to_string(),
+            },
             Style::default()
-                .fg(Color::Yellow)
+                .fg(self.theme.focused_border)
                 .add_modifier(Modifier::BOLD),
         );
         let block = Block::default().title(title).borders(Borders::ALL);
@@ -132,25 +756,72 @@ impl Component for ConnectionComponent {
         let border_rows = 2u16; // top+bottom borders
         let avail = inner.height.saturating_sub(border_rows);
         let visible = usize::try_from(avail).unwrap_or(0).max(1);
-        let total = self.items.len();
+        let rows = self.rows();
+        let selected_row = rows
+            .iter()
+            .position(|row| match row {
+                Row::Connection(i) => *i == self.selected,
+                Row::Group { first, collapsed, .. } => *collapsed && *first == self.selected,
+            })
+            .unwrap_or(0);
+        let total = rows.len();
         let start = if total <= visible {
             0
         } else {
-            self.selected.saturating_add(1).saturating_sub(visible)
+            selected_row.saturating_add(1).saturating_sub(visible)
         };
         let end = (start + visible).min(total);
+        // Connections sit one level below their heading
+        let indent = if self.grouped() { "  " } else { "" };
+
+        if let Some(e) = self.load_error.as_ref().filter(|_| total == 0) {
+            let text = Paragraph::new(format!("Failed to load config: {}", e))
+                .style(Style::default().fg(Color::Red))
+                .wrap(Wrap { trim: false })
+                .block(block);
+            f.render_widget(text, inner);
+            if let Some(form) = &self.form {
+                self.draw_form(f, area, form);
+            }
+            return;
+        }
 
         let items: Vec<ListItem> = if total == 0 {
-            vec![ListItem::new("(no connections found)")]
+            match &self.scaffold_path {
+                Some(path) => vec![
+                    ListItem::new(format!("No config file found at {}", path.display())),
+                    ListItem::new("Press [g] to generate an example config"),
+                ],
+                None => vec![ListItem::new("(no connections found)")],
+            }
         } else {
-            self.items[start..end]
+            rows[start..end]
                 .iter()
-                .map(|c| {
-                    ListItem::new(Span::raw(format!(
-                        "{} ({})",
+                .map(|row| {
+                    let c = match row {
+                        Row::Connection(i) => &self.items[*i],
+                        Row::Group { name, count, collapsed, .. } => {
+                            let expander = self.icons.expander(!collapsed, true);
+                            let heading = format!("{} {} ({})", expander, name, count);
+                            return ListItem::new(Span::styled(heading, Style::default().add_modifier(Modifier::BOLD)));
+                        }
+                    };
+                    let mut spans = vec![Span::raw(format!(
+                        "{}{} ({})",
+                        indent,
                         c.name.clone().unwrap_or("unknown".to_string()),
                         DB::database_url(c).unwrap_or("invalid config".to_string())
-                    )))
+                    ))];
+                    match self.status.get(c.name.as_deref().unwrap_or_default()) {
+                        Some(PingStatus::Testing) => spans.push(Span::styled(" …", Style::default().fg(Color::DarkGray))),
+                        Some(PingStatus::Ok) => spans.push(Span::styled(" ✓", Style::default().fg(Color::Green))),
+                        Some(PingStatus::Failed(e)) => spans.push(Span::styled(
+                            format!(" ✗ {}", e.lines().next().unwrap_or_default()),
+                            Style::default().fg(Color::Red),
+                        )),
+                        None => {}
+                    }
+                    ListItem::new(Line::from(spans))
                 })
                 .collect()
         };
@@ -159,16 +830,20 @@ impl Component for ConnectionComponent {
             .block(block)
             .highlight_style(
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(self.theme.highlight)
                     .add_modifier(Modifier::BOLD),
             )
-            .highlight_symbol("▶ ");
+            .highlight_symbol(self.icons.highlight());
 
         let mut state = ListState::default();
         if total > 0 {
             // Adjust the selection index within the window
-            state.select(Some(self.selected - start));
+            state.select(Some(selected_row - start));
         }
         f.render_stateful_widget(list, inner, &mut state);
+
+        if let Some(form) = &self.form {
+            self.draw_form(f, area, form);
+        }
     }
 }
@@ -1,15 +1,72 @@
-use crossterm::event::KeyEvent;
+use std::cell::Cell;
+
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
 };
 
-use super::{Component, DBListComponent, DBListMsg, TableComponent, TableMsg};
+use super::{
+    finder::build_index, in_area, table::DEFAULT_WRAP_LINES, Component, ConfirmRequest, DBListComponent, DBListMsg,
+    FinderComponent, FinderEntry, FinderMsg, TableComponent, TableInfo, TableKind, TableMsg, ValueSearchComponent,
+    ValueSearchMsg,
+};
 use crate::{
-    update::{MapMsg, Update},
+    update::{Command, MapMsg, Update},
+    config::Config,
     connection::Connection,
+    db::{split_table_path, CliTarget, ServerInfo, DB, DBBehavior},
+    history::DEFAULT_HISTORY_SIZE,
+    keymap::{Action, Keymap},
+    logger::{error, warn},
+    settings::TreeWidth,
+    state::State,
+    status::StatusMessage,
+    theme::Theme,
 };
 
+/// Percentages stay within this range, so neither panel disappears
+const TREE_PERCENT_RANGE: (u16, u16) = (5, 80);
+const TREE_MIN_COLUMNS: u16 = 10;
+
+impl TreeWidth {
+    /// Grow (positive `steps`) or shrink by 5% or 4 columns a step
+    fn resized(self, steps: i16) -> Self {
+        match self {
+            TreeWidth::Percent(p) => {
+                let p = p.saturating_add_signed(steps * 5);
+                TreeWidth::Percent(p.clamp(TREE_PERCENT_RANGE.0, TREE_PERCENT_RANGE.1))
+            }
+            TreeWidth::Columns(n) => TreeWidth::Columns(n.saturating_add_signed(steps * 4).max(TREE_MIN_COLUMNS)),
+        }
+    }
+
+    fn constraint(self) -> Constraint {
+        match self {
+            TreeWidth::Percent(p) => Constraint::Percentage(p),
+            TreeWidth::Columns(n) => Constraint::Length(n),
+        }
+    }
+}
+
+/// Save a change to the UI state in the background
+fn remember(f: impl FnOnce(&mut State) + Send + 'static) -> Command {
+    let task = move |_tx: std::sync::mpsc::Sender<crate::app::AppMsg>| {
+        if let Err(e) = State::update(f) {
+            error(&format!("Dashboard: saving the UI state failed: {}", e));
+        }
+    };
+    Command::Spawn(Box::new(task))
+}
+
+/// Table to open once the tree has loaded
+pub enum PendingTable {
+    /// As given to `--table`: `table`, `database.table` or `database.schema.table`
+    Path(String),
+    /// The table open when the last session ended
+    Last(TableInfo),
+}
+
 /// Messages the Dashboard component can emit
 pub enum DashboardMsg {
     /// Request to leave dashboard back to Connection
@@ -17,30 +74,78 @@ pub enum DashboardMsg {
     /// DBList wants to select a table
     SelectTable {
         database: String,
+        schema: Option<String>,
         table: String,
+        kind: TableKind,
     },
     /// Table wants to go back to DBList focus
     BackToDBList,
+    /// DBList wants the external CLI opened on a table
+    OpenInCli(CliTarget),
     ConnectionSelected(Connection),
+    /// Open the fuzzy table finder over the loaded tree
+    OpenFinder,
+    /// Open the search for a value in every table's text columns
+    OpenValueSearch,
+    /// Widen (positive) or narrow the tree panel by a step and remember it
+    ResizeTree(i16),
+    /// Hide the tree so the table view gets the whole width, or bring it back
+    ToggleZoom,
+    /// Finder (or value search) picked a table: reveal it in the tree and open it
+    JumpToTable(FinderEntry),
+    /// Open this table once the tree has loaded (`--table` on the command
+    /// line, or the last session's table)
+    OpenOnLoad(PendingTable),
+    /// Version and latency of the server, for the connection with this generation
+    ServerInfoLoaded(u64, ServerInfo),
+    /// Show a message in the status bar; handled by the root
+    Status(StatusMessage),
+    /// Ask before a destructive action; handled by the root
+    Confirm(ConfirmRequest),
     DBListMsg(DBListMsg),
     TableMsg(TableMsg),
+    FinderMsg(FinderMsg),
+    ValueSearchMsg(ValueSearchMsg),
 }
 
 impl From<DBListMsg> for DashboardMsg {
     fn from(msg: DBListMsg) -> Self {
         match msg {
-            DBListMsg::SelectTable { database, table } => {
-                DashboardMsg::SelectTable { database, table }
+            DBListMsg::SelectTable { database, schema, table, kind } => {
+                DashboardMsg::SelectTable { database, schema, table, kind }
             }
             DBListMsg::LeaveDashboard => DashboardMsg::Leave,
+            DBListMsg::Status(status) => DashboardMsg::Status(status),
+            DBListMsg::OpenInCli { database, schema, table } => {
+                DashboardMsg::OpenInCli(CliTarget { database, schema, table })
+            }
             m => DashboardMsg::DBListMsg(m),
         }
     }
 }
+impl From<FinderMsg> for DashboardMsg {
+    fn from(msg: FinderMsg) -> Self {
+        match msg {
+            FinderMsg::Confirm(entry) => DashboardMsg::JumpToTable(entry),
+            m => DashboardMsg::FinderMsg(m),
+        }
+    }
+}
+impl From<ValueSearchMsg> for DashboardMsg {
+    fn from(msg: ValueSearchMsg) -> Self {
+        match msg {
+            ValueSearchMsg::Confirm(entry) => DashboardMsg::JumpToTable(entry),
+            m => DashboardMsg::ValueSearchMsg(m),
+        }
+    }
+}
 impl From<TableMsg> for DashboardMsg {
     fn from(msg: TableMsg) -> Self {
         match msg {
             TableMsg::BackToDBList => DashboardMsg::BackToDBList,
+            TableMsg::JumpToTable(entry) => DashboardMsg::JumpToTable(entry),
+            TableMsg::Status(status) => DashboardMsg::Status(status),
+            TableMsg::Confirm(request) => DashboardMsg::Confirm(request),
             m => DashboardMsg::TableMsg(m),
         }
     }
@@ -55,43 +160,179 @@ pub enum DashboardFocus {
 pub struct DashboardComponent {
     dblist: DBListComponent,
     table: TableComponent,
+    finder: FinderComponent,
+    value_search: ValueSearchComponent,
     focus: DashboardFocus,
     connection: Option<Connection>,
+    /// Bumped per selected connection, so a slow `server_info` of the previous one is dropped
+    connection_generation: u64,
+    tree_width: TreeWidth,
+    /// Tree hidden, table view full-screen; focus stays on the table
+    zoomed: bool,
+    /// Table waiting for the tree to load
+    pending_table: Option<PendingTable>,
+    keymap: Keymap,
+    /// Where the panes were last drawn, to route mouse events
+    dblist_area: Cell<Rect>,
+    table_area: Cell<Rect>,
 }
 
 impl DashboardComponent {
-    pub fn new() -> Self {
+    pub fn new(config: &Config, state: &State, theme: Theme, keymap: Keymap) -> Self {
         Self {
-            dblist: DBListComponent::new(),
-            table: TableComponent::new(),
+            dblist: DBListComponent::new(
+                config.tree_enter_action.unwrap_or_default(),
+                config.tree_row_counts.unwrap_or(false),
+                config.tree_icons.unwrap_or_default(),
+                config.tree_natural_sort.unwrap_or(false),
+                theme,
+                keymap.clone(),
+            ),
+            table: TableComponent::new(
+                config.frozen_columns.unwrap_or(1),
+                config.wrap_lines.unwrap_or(DEFAULT_WRAP_LINES),
+                config.history_size.unwrap_or(DEFAULT_HISTORY_SIZE),
+                theme,
+                keymap.clone(),
+            ),
+            finder: FinderComponent::new(theme),
+            value_search: ValueSearchComponent::new(theme),
             focus: DashboardFocus::DBList,
             connection: None,
+            connection_generation: 0,
+            // The width last picked with `<` / `>` wins over the config
+            tree_width: state.tree_width.or(config.tree_width).unwrap_or_default(),
+            zoomed: false,
+            pending_table: None,
+            keymap,
+            dblist_area: Cell::default(),
+            table_area: Cell::default(),
         }
     }
 
-    fn move_to_table(&mut self, database: String, table: String) -> Update<DashboardMsg> {
-        self.table.set_table(database, table);
+    fn move_to_table(
+        &mut self,
+        database: String,
+        schema: Option<String>,
+        table: String,
+        kind: TableKind,
+    ) -> Update<DashboardMsg> {
+        let info = TableInfo { database: database.clone(), schema: schema.clone(), table: table.clone() };
+        let remember = remember(move |state| state.last_table = Some(info));
+        self.table.set_table(database, schema, table, kind);
         if let Some(conn) = &self.connection {
             self.table.set_connection(conn.clone());
         }
         self.focus = DashboardFocus::Table;
         if let Some(conn) = &self.connection {
-            DashboardMsg::TableMsg(TableMsg::LoadRecords(conn.clone())).into()
+            Update::msg_cmd(DashboardMsg::TableMsg(TableMsg::LoadRecords(conn.clone())), remember)
         } else {
-            Update::none()
+            remember.into()
         }
     }
 
     fn move_to_dblist(&mut self) -> Update<DashboardMsg> {
+        // The tree can't take focus while hidden
+        self.zoomed = false;
         self.focus = DashboardFocus::DBList;
         Update::none()
     }
 
+    fn toggle_zoom(&mut self) -> Update<DashboardMsg> {
+        self.zoomed = !self.zoomed;
+        if self.zoomed {
+            self.focus = DashboardFocus::Table;
+        }
+        Update::none()
+    }
+
+    fn open_in_cli(&mut self, target: CliTarget) -> Update<DashboardMsg> {
+        match &self.connection {
+            Some(conn) => {
+                Command::SuspendTerminal(TableComponent::launch_external_cli(conn, Some(target))).into()
+            }
+            None => Update::none(),
+        }
+    }
+
+    fn open_finder(&mut self) -> Update<DashboardMsg> {
+        self.finder.open(build_index(self.dblist.databases()));
+        Update::none()
+    }
+
+    fn open_value_search(&mut self) -> Update<DashboardMsg> {
+        if let Some(conn) = &self.connection {
+            self.value_search.open(conn.clone(), self.dblist.databases());
+        }
+        Update::none()
+    }
+
+    fn jump_to_table(&mut self, entry: FinderEntry) -> Update<DashboardMsg> {
+        self.finder.close();
+        // A running value search carries on; reopening it shows the rest
+        self.value_search.hide();
+        let Some(kind) = self.dblist.reveal_table(&entry.database, entry.schema.as_deref(), &entry.table) else {
+            return Update::none();
+        };
+        self.move_to_table(entry.database, entry.schema, entry.table, kind)
+    }
+
+    /// Open the loaded table matching `path`; a bare table name or
+    /// `database.table` matches in any database or schema
+    fn open_table_path(&mut self, path: &str) -> Update<DashboardMsg> {
+        let (database, schema, table) = split_table_path(path);
+        let entry = build_index(self.dblist.databases()).into_iter().find(|e| {
+            e.table == table
+                && (database.is_none() || database == Some(e.database.as_str()))
+                && (schema.is_none() || schema == e.schema.as_deref())
+        });
+        match entry {
+            Some(entry) => self.jump_to_table(entry),
+            None => Self::no_table(path),
+        }
+    }
+
+    /// Reopen the last session's table, matching every part of its name
+    fn open_last_table(&mut self, info: TableInfo) -> Update<DashboardMsg> {
+        let entry = build_index(self.dblist.databases())
+            .into_iter()
+            .find(|e| e.database == info.database && e.schema == info.schema && e.table == info.table);
+        match entry {
+            Some(entry) => self.jump_to_table(entry),
+            None => Self::no_table(&info.label()),
+        }
+    }
+
+    fn no_table(name: &str) -> Update<DashboardMsg> {
+        // Don't try a dropped table again next session
+        let forget = remember(|state| state.last_table = None);
+        Update::msg_cmd(DashboardMsg::Status(StatusMessage::error(format!("No table {}", name))), forget)
+    }
+
     fn on_connection_selected(&mut self, conn: Connection) -> Update<DashboardMsg> {
         // Store selected connection
         self.connection = Some(conn.clone());
+        self.connection_generation += 1;
+        self.value_search.reset();
+        self.table.set_server_info(None);
+        self.table.forget_properties();
+        let generation = self.connection_generation;
+        let info_conn = conn.clone();
+        let task = move |tx: std::sync::mpsc::Sender<crate::app::AppMsg>| match DB::server_info(&info_conn) {
+            Ok(info) => {
+                let _ = tx.send(DashboardMsg::ServerInfoLoaded(generation, info).into());
+            }
+            // Loading the tree reports an unreachable server already
+            Err(e) => warn(&format!("Dashboard: reading the server version failed: {}", e)),
+        };
+        let name = conn.name.clone();
+        let remember = remember(move |state| {
+            state.last_connection = name;
+            state.last_table = None;
+        });
         // Trigger DBList load immediately
-        self.dblist.update(DBListMsg::Load(conn)).map_auto()
+        let load = self.dblist.update(DBListMsg::Load(Box::new(conn))).map_auto();
+        Update { msg: load.msg, cmd: Command::batch([load.cmd, Command::Spawn(Box::new(task)), remember]) }
     }
 }
 
@@ -100,10 +341,69 @@ impl Component for DashboardComponent {
 
     fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
         match msg {
-            DashboardMsg::SelectTable { database, table } => self.move_to_table(database, table),
+            DashboardMsg::SelectTable { database, schema, table, kind } => {
+                self.move_to_table(database, schema, table, kind)
+            }
             DashboardMsg::BackToDBList => self.move_to_dblist(),
-            DashboardMsg::Leave => DashboardMsg::Leave.into(),
+            DashboardMsg::OpenInCli(target) => self.open_in_cli(target),
+            DashboardMsg::Leave => {
+                // Quitting from the connection list starts there next time
+                let forget = remember(|state| {
+                    state.last_connection = None;
+                    state.last_table = None;
+                });
+                Update::msg_cmd(DashboardMsg::Leave, forget)
+            }
+            DashboardMsg::ServerInfoLoaded(generation, info) if generation == self.connection_generation => {
+                let status = StatusMessage::info(format!("Connected to {}", info));
+                self.table.set_server_info(Some(info));
+                DashboardMsg::Status(status).into()
+            }
+            DashboardMsg::ServerInfoLoaded(..) => Update::none(),
+            DashboardMsg::Status(_) => Update::none(), // Handled by parent
+            DashboardMsg::Confirm(_) => Update::none(), // Handled by parent
             DashboardMsg::ConnectionSelected(conn) => self.on_connection_selected(conn),
+            DashboardMsg::OpenFinder => self.open_finder(),
+            DashboardMsg::OpenValueSearch => self.open_value_search(),
+            DashboardMsg::ResizeTree(steps) => {
+                self.tree_width = self.tree_width.resized(steps);
+                let width = self.tree_width;
+                remember(move |state| state.tree_width = Some(width)).into()
+            }
+            DashboardMsg::ToggleZoom => self.toggle_zoom(),
+            DashboardMsg::JumpToTable(entry) => self.jump_to_table(entry),
+            DashboardMsg::OpenOnLoad(pending) => {
+                self.pending_table = Some(pending);
+                Update::none()
+            }
+            DashboardMsg::FinderMsg(m) => self.finder.update(m).map_auto(),
+            DashboardMsg::ValueSearchMsg(m) => self.value_search.update(m).map_auto(),
+            DashboardMsg::DBListMsg(m @ DBListMsg::Click(_)) => {
+                // Clicking the tree also moves focus there
+                let _ = self.move_to_dblist();
+                self.dblist.update(m).map_auto()
+            }
+            DashboardMsg::DBListMsg(m @ DBListMsg::Loaded(_)) => {
+                let loaded = self.dblist.update(m).map_auto();
+                let opened = match self.pending_table.take() {
+                    Some(PendingTable::Path(path)) => self.open_table_path(&path),
+                    Some(PendingTable::Last(info)) => self.open_last_table(info),
+                    None => return loaded,
+                };
+                Update { msg: opened.msg.or(loaded.msg), cmd: Command::batch([loaded.cmd, opened.cmd]) }
+            }
+            DashboardMsg::DBListMsg(m @ DBListMsg::Refresh) => {
+                let refreshed = self.dblist.update(m).map_auto();
+                let Some(conn) = &self.connection else {
+                    return refreshed;
+                };
+                let properties = self.table.refresh_properties(conn);
+                Update { msg: refreshed.msg, cmd: Command::batch([refreshed.cmd, properties]) }
+            }
+            DashboardMsg::DBListMsg(m @ DBListMsg::LoadFailed(_)) => {
+                self.pending_table = None;
+                self.dblist.update(m).map_auto()
+            }
             DashboardMsg::DBListMsg(m) => self.dblist.update(m).map_auto(),
             DashboardMsg::TableMsg(TableMsg::FocusProperties) => {
                 // Set focus first
@@ -119,6 +419,27 @@ impl Component for DashboardComponent {
     }
 
     fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        // The finder overlay takes all keys while open
+        if self.finder.is_open() {
+            return self.finder.handle_key(key).map_auto();
+        }
+        if self.value_search.is_open() {
+            return self.value_search.handle_key(key).map_auto();
+        }
+        if self.keymap.is(Action::FindTable, &key) {
+            return DashboardMsg::OpenFinder.into();
+        }
+        if !self.is_typing() {
+            if self.keymap.is(Action::ShrinkTree, &key) {
+                return DashboardMsg::ResizeTree(-1).into();
+            } else if self.keymap.is(Action::GrowTree, &key) {
+                return DashboardMsg::ResizeTree(1).into();
+            } else if self.keymap.is(Action::ToggleZoom, &key) {
+                return DashboardMsg::ToggleZoom.into();
+            } else if self.keymap.is(Action::SearchValues, &key) {
+                return DashboardMsg::OpenValueSearch.into();
+            }
+        }
         // Forward key to focused component - let update handle side effects
         match self.focus {
             DashboardFocus::DBList => self.dblist.handle_key(key).map_auto(),
@@ -126,22 +447,103 @@ impl Component for DashboardComponent {
         }
     }
 
-    fn draw(&self, f: &mut Frame, area: Rect, focused: bool) {
-        // Create layout: 15% left (DBList), 85% right (Table)
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(15), Constraint::Percentage(85)])
-            .split(area);
+    fn handle_mouse(&self, event: MouseEvent) -> Update<Self::Msg> {
+        if self.finder.is_open() || self.value_search.is_open() {
+            return Update::none();
+        }
+        // Whichever pane is under the pointer gets the event, focused or not
+        if in_area(self.dblist_area.get(), &event) {
+            self.dblist.handle_mouse(event).map_auto()
+        } else if in_area(self.table_area.get(), &event) {
+            self.table.handle_mouse(event).map_auto()
+        } else {
+            Update::none()
+        }
+    }
 
-        let dblist_area = chunks[0];
-        let table_area = chunks[1];
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        let mut keys = match self.focus {
+            DashboardFocus::DBList => self.dblist.keybindings(),
+            DashboardFocus::Table => self.table.keybindings(),
+        };
+        keys.extend(self.keymap.help(&[
+            (Action::FindTable, "Find a table"),
+            (Action::SearchValues, "Search every table for a value"),
+            (Action::ShrinkTree, "Narrow the tree panel"),
+            (Action::GrowTree, "Widen the tree panel"),
+            (Action::ToggleZoom, "Toggle a full-screen table"),
+        ]));
+        keys
+    }
+
+    fn is_typing(&self) -> bool {
+        self.finder.is_typing()
+            || self.value_search.is_typing()
+            || match self.focus {
+                DashboardFocus::DBList => self.dblist.is_typing(),
+                DashboardFocus::Table => self.table.is_typing(),
+            }
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect, focused: bool) {
+        // Tree on the left (`tree_width`), table view in the rest
+        let (dblist_area, table_area) = if self.zoomed {
+            (Rect::default(), area)
+        } else {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([self.tree_width.constraint(), Constraint::Min(0)])
+                .split(area);
+            (chunks[0], chunks[1])
+        };
+        self.dblist_area.set(dblist_area);
+        self.table_area.set(table_area);
 
         // Draw DBList
-        let dblist_focused = focused && matches!(self.focus, DashboardFocus::DBList);
-        self.dblist.draw(f, dblist_area, dblist_focused);
+        if !self.zoomed {
+            let dblist_focused = focused && matches!(self.focus, DashboardFocus::DBList);
+            self.dblist.draw(f, dblist_area, dblist_focused);
+        }
 
         // Draw Table
         let table_focused = focused && matches!(self.focus, DashboardFocus::Table);
         self.table.draw(f, table_area, table_focused);
+
+        // Finder and value search overlays on top of both panes
+        self.value_search.draw(f, area, focused);
+        self.finder.draw(f, area, focused);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Database, Table};
+
+    fn loaded_with_last(info: TableInfo) -> (DashboardComponent, Update<DashboardMsg>) {
+        let mut dashboard =
+            DashboardComponent::new(&Config::default(), &State::default(), Theme::default(), Keymap::default());
+        let _ = dashboard.update(DashboardMsg::OpenOnLoad(PendingTable::Last(info)));
+        let table =
+            |name: &str| Table { name: name.to_string(), engine: None, schema: None, kind: TableKind::Table }.into();
+        let db = Database::new("db".to_string(), vec![table("v1"), table("v1.2_backup")]);
+        let update = dashboard.update(DashboardMsg::DBListMsg(DBListMsg::Loaded(vec![db])));
+        (dashboard, update)
+    }
+
+    #[test]
+    fn last_table_with_dots_in_its_name_is_reopened() {
+        let info = TableInfo { database: "db".to_string(), schema: None, table: "v1.2_backup".to_string() };
+        let (dashboard, update) = loaded_with_last(info);
+        assert_eq!(dashboard.focus, DashboardFocus::Table);
+        assert!(!matches!(update.msg, Some(DashboardMsg::Status(_))));
+    }
+
+    #[test]
+    fn missing_last_table_is_reported() {
+        let info = TableInfo { database: "db".to_string(), schema: None, table: "gone".to_string() };
+        let (dashboard, update) = loaded_with_last(info);
+        assert_eq!(dashboard.focus, DashboardFocus::DBList);
+        assert!(matches!(update.msg, Some(DashboardMsg::Status(_))));
     }
 }
@@ -1,18 +1,27 @@
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::Span,
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
 };
 
-use super::Component;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use super::{in_area, Component};
 use crate::app::AppMsg;
 use crate::db::DBBehavior;
+use crate::keymap::{Action, Keymap};
 use crate::logger::{error, info};
+use crate::settings::{TreeEnterAction, TreeIcons};
+use crate::status::StatusMessage;
+use crate::theme::Theme;
 use crate::update::{Command, Update};
-use crate::{connection::Connection, db};
+use crate::{connection::Connection, db, db::RowCount};
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Database {
@@ -95,8 +104,80 @@ pub struct Table {
     pub name: String,
     pub engine: Option<String>,
     pub schema: Option<String>,
+    pub kind: TableKind,
+}
+
+/// Whether a tree entry is a table or a view; views open read-only
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableKind {
+    Table,
+    View,
+}
+
+impl TableKind {
+    /// Kind from a catalog's table type: `BASE TABLE` / `VIEW` in
+    /// information_schema, `table` / `view` in sqlite_master
+    pub fn from_table_type(table_type: &str) -> Self {
+        if table_type.to_uppercase().contains("VIEW") {
+            TableKind::View
+        } else {
+            TableKind::Table
+        }
+    }
+}
+
+/// Order names the way people read them: runs of digits compare by value,
+/// so `item2` sorts before `item10`. Everything else compares as-is.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(ca), Some(cb)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+            let (na, ra) = a.split_at(digits(a));
+            let (nb, rb) = b.split_at(digits(b));
+            let (ta, tb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+            // More significant digits is the bigger number; ties fall back to
+            // the digits themselves, then to fewer leading zeros first
+            let order = ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb)).then_with(|| na.len().cmp(&nb.len()));
+            if order != Ordering::Equal {
+                return order;
+            }
+            (a, b) = (ra, rb);
+        } else {
+            if ca != cb {
+                return ca.cmp(&cb);
+            }
+            (a, b) = (&a[ca.len_utf8()..], &b[cb.len_utf8()..]);
+        }
+    }
+}
+
+/// Sort databases, schemas and tables with `natural_cmp`
+fn sort_naturally(dbs: &mut [Database]) {
+    let child_name = |child: &Child| match child {
+        Child::Table(t) => t.name.clone(),
+        Child::Schema(s) => s.name.clone(),
+    };
+    dbs.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+    for db in dbs {
+        db.children.sort_by(|a, b| natural_cmp(&child_name(a), &child_name(b)));
+        for child in &mut db.children {
+            if let Child::Schema(s) = child {
+                s.tables.sort_by(|a, b| natural_cmp(&a.name, &b.name));
+            }
+        }
+    }
 }
 
+/// (database, schema, table) of a table in the tree
+pub type TableKey = (String, Option<String>, String);
+
+/// Row counts shown next to tables; `None` when the count failed
+pub type RowCounts = HashMap<TableKey, Option<RowCount>>;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum NodePath {
     Database(usize),
@@ -121,6 +202,91 @@ impl Databases {
         }
     }
 
+    pub fn data(&self) -> &[Database] {
+        &self.data
+    }
+
+    /// Select a table by name, expanding its database (and schema) and
+    /// clearing the filter so it is visible. Returns the table's kind, or None
+    /// if it isn't in the tree.
+    pub fn reveal_table(&mut self, database: &str, schema: Option<&str>, table: &str) -> Option<TableKind> {
+        let path = self.table_path(database, schema, table)?;
+        let (NodePath::TableInDb(db_idx, _) | NodePath::TableInSchema(db_idx, _, _)) = path else {
+            return None;
+        };
+        self.data[db_idx].expand();
+        if let NodePath::TableInSchema(_, child_idx, _) = path {
+            if let Child::Schema(s) = &mut self.data[db_idx].children[child_idx] {
+                s.expand();
+            }
+        }
+        self.filter.clear();
+        self.selected = Some(path);
+        match self.get_selected()? {
+            SelectedRef::Table { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+
+    fn table_path(&self, database: &str, schema: Option<&str>, table: &str) -> Option<NodePath> {
+        let db_idx = self.data.iter().position(|d| d.name == database)?;
+        self.data[db_idx]
+            .children
+            .iter()
+            .enumerate()
+            .find_map(|(child_idx, child)| match (child, schema) {
+                (Child::Table(t), None) if t.name == table => Some(NodePath::TableInDb(db_idx, child_idx)),
+                (Child::Schema(s), Some(schema)) if s.name == schema => s
+                    .tables
+                    .iter()
+                    .position(|t| t.name == table)
+                    .map(|table_idx| NodePath::TableInSchema(db_idx, child_idx, table_idx)),
+                _ => None,
+            })
+    }
+
+    /// Swap in a fresh load, keeping the filter, which databases and schemas
+    /// are expanded, and the selected node where they still exist
+    pub fn refresh(&mut self, mut databases: Vec<Database>) {
+        for db in &mut databases {
+            let Some(old) = self.data.iter().find(|d| d.name == db.name) else {
+                continue;
+            };
+            db.is_expanded = old.is_expanded;
+            for child in &mut db.children {
+                if let Child::Schema(s) = child {
+                    s.is_expanded = old
+                        .children
+                        .iter()
+                        .any(|c| matches!(c, Child::Schema(o) if o.name == s.name && o.is_expanded));
+                }
+            }
+        }
+        let selected = match self.get_selected() {
+            Some(SelectedRef::Database(database)) => (database.to_string(), None, None),
+            Some(SelectedRef::Schema { database, schema }) => (database.to_string(), Some(schema.to_string()), None),
+            Some(SelectedRef::Table { database, schema, table, .. }) => {
+                (database.to_string(), schema.map(str::to_string), Some(table.to_string()))
+            }
+            None => (String::new(), None, None),
+        };
+        self.data = databases;
+        let db_idx = self.data.iter().position(|d| d.name == selected.0);
+        self.selected = match (db_idx, selected) {
+            (Some(db_idx), (_, None, None)) => Some(NodePath::Database(db_idx)),
+            (Some(db_idx), (_, Some(schema), None)) => self.data[db_idx]
+                .children
+                .iter()
+                .position(|c| matches!(c, Child::Schema(s) if s.name == schema))
+                .map(|child_idx| NodePath::Schema(db_idx, child_idx)),
+            (Some(_), (database, schema, Some(table))) => self.table_path(&database, schema.as_deref(), &table),
+            (None, _) => None,
+        };
+        if self.selected.is_none() {
+            self.select_first();
+        }
+    }
+
     // Filter methods
     pub fn filter(&self) -> &str {
         &self.filter
@@ -196,6 +362,21 @@ impl Databases {
         self.selected = None;
     }
 
+    /// Select the `index`th row as drawn. Returns false, keeping the
+    /// selection, when there is no such row.
+    pub fn select_visible(&mut self, index: usize) -> bool {
+        let previous = self.selected;
+        self.select_first();
+        for _ in 0..index {
+            self.selected = self.find_next_filtered();
+        }
+        if self.selected.is_none() {
+            self.selected = previous;
+            return false;
+        }
+        true
+    }
+
     pub fn select_next(&mut self) {
         if let Some(next) = self.find_next_filtered() {
             self.selected = Some(next);
@@ -392,6 +573,7 @@ impl Databases {
                         database: &self.data[db_idx].name,
                         schema: None,
                         table: &t.name,
+                        kind: t.kind,
                     })
                 } else {
                     None
@@ -403,6 +585,7 @@ impl Databases {
                         database: &self.data[db_idx].name,
                         schema: Some(&s.name),
                         table: &s.tables[table_idx].name,
+                        kind: s.tables[table_idx].kind,
                     })
                 } else {
                     None
@@ -411,6 +594,57 @@ impl Databases {
         }
     }
 
+    /// (database, table) of the first table inside the selected database/schema node
+    pub fn first_table_under_selected(&self) -> Option<(String, Option<String>, String, TableKind)> {
+        let (db_idx, child_idx) = match self.selected? {
+            NodePath::Database(db_idx) => (db_idx, None),
+            NodePath::Schema(db_idx, child_idx) => (db_idx, Some(child_idx)),
+            _ => return None,
+        };
+        let db = &self.data[db_idx];
+        fn first_in(child: &Child) -> Option<(Option<String>, &Table)> {
+            match child {
+                Child::Table(t) => Some((None, t)),
+                Child::Schema(s) => s.tables.first().map(|t| (Some(s.name.clone()), t)),
+            }
+        }
+        let (schema, table) = match child_idx {
+            Some(child_idx) => first_in(&db.children[child_idx]),
+            None => db.children.iter().find_map(first_in),
+        }?;
+        Some((db.name.clone(), schema, table.name.clone(), table.kind))
+    }
+
+    /// Expand every database and schema; the selection stays visible
+    pub fn expand_all(&mut self) {
+        for db in &mut self.data {
+            db.expand();
+            for child in &mut db.children {
+                if let Child::Schema(s) = child {
+                    s.expand();
+                }
+            }
+        }
+    }
+
+    /// Fold every database and schema, moving the selection up to its database
+    pub fn collapse_all(&mut self) {
+        for db in &mut self.data {
+            db.fold();
+            for child in &mut db.children {
+                if let Child::Schema(s) = child {
+                    s.fold();
+                }
+            }
+        }
+        if let Some(
+            NodePath::Schema(db_idx, _) | NodePath::TableInDb(db_idx, _) | NodePath::TableInSchema(db_idx, _, _),
+        ) = self.selected
+        {
+            self.selected = Some(NodePath::Database(db_idx));
+        }
+    }
+
     pub fn toggle_expand_selected(&mut self) {
         match self.selected {
             Some(NodePath::Database(db_idx)) => {
@@ -476,7 +710,26 @@ impl Databases {
     }
 
     /// Build list items with filter applied, return (items, selected_index)
-    pub fn build_list_items(&self) -> (Vec<(String, usize)>, Option<usize>) {
+    /// Tree rows with their depth, and the index of the selected one. With
+    /// `row_counts`, tables show their count, or "…" while it is pending.
+    pub fn build_list_items(
+        &self,
+        row_counts: Option<&RowCounts>,
+        icons: TreeIcons,
+    ) -> (Vec<(String, usize)>, Option<usize>) {
+        let table_label = |indent: &str, database: &str, schema: Option<&str>, t: &Table| {
+            let icon = icons.table(t.kind);
+            // Views aren't counted: a COUNT(*) runs the whole view query
+            let Some(counts) = row_counts.filter(|_| t.kind == TableKind::Table) else {
+                return format!("{}{} {}", indent, icon, t.name);
+            };
+            let key = (database.to_string(), schema.map(str::to_string), t.name.clone());
+            match counts.get(&key) {
+                Some(Some(count)) => format!("{}{} {}  {}", indent, icon, t.name, count),
+                Some(None) => format!("{}{} {}", indent, icon, t.name),
+                None => format!("{}{} {}  …", indent, icon, t.name),
+            }
+        };
         let mut items = Vec::new();
         let mut selected_index = None;
         let mut index = 0;
@@ -489,14 +742,8 @@ impl Databases {
                 continue;
             }
 
-            let prefix = if db.is_expanded {
-                "▼ 📁"
-            } else if db.has_children() {
-                "▶ 📁"
-            } else {
-                "  📁"
-            };
-            items.push((format!("{} {}", prefix, db.name), 0));
+            let expander = icons.expander(db.is_expanded, db.has_children());
+            items.push((format!("{} {} {}", expander, icons.database(), db.name), 0));
             if self.selected == Some(NodePath::Database(db_idx)) {
                 selected_index = Some(index);
             }
@@ -509,7 +756,7 @@ impl Databases {
                             if !self.filter.is_empty() && !t.name.to_lowercase().contains(&filter_lower) {
                                 continue;
                             }
-                            items.push((format!("    📄 {}", t.name), 1));
+                            items.push((table_label("    ", &db.name, None, t), 1));
                             if self.selected == Some(NodePath::TableInDb(db_idx, child_idx)) {
                                 selected_index = Some(index);
                             }
@@ -521,14 +768,8 @@ impl Databases {
                                 continue;
                             }
 
-                            let prefix = if s.is_expanded {
-                                "▼ 📂"
-                            } else if s.has_children() {
-                                "▶ 📂"
-                            } else {
-                                "  📂"
-                            };
-                            items.push((format!("  {} {}", prefix, s.name), 1));
+                            let expander = icons.expander(s.is_expanded, s.has_children());
+                            items.push((format!("  {} {} {}", expander, icons.schema(), s.name), 1));
                             if self.selected == Some(NodePath::Schema(db_idx, child_idx)) {
                                 selected_index = Some(index);
                             }
@@ -539,7 +780,7 @@ impl Databases {
                                     if !self.filter.is_empty() && !t.name.to_lowercase().contains(&filter_lower) {
                                         continue;
                                     }
-                                    items.push((format!("      📄 {}", t.name), 2));
+                                    items.push((table_label("      ", &db.name, Some(&s.name), t), 2));
                                     if self.selected == Some(NodePath::TableInSchema(db_idx, child_idx, table_idx)) {
                                         selected_index = Some(index);
                                     }
@@ -580,7 +821,7 @@ impl Databases {
 pub enum SelectedRef<'a> {
     Database(&'a str),
     Schema { database: &'a str, schema: &'a str },
-    Table { database: &'a str, schema: Option<&'a str>, table: &'a str },
+    Table { database: &'a str, schema: Option<&'a str>, table: &'a str, kind: TableKind },
 }
 
 pub enum DBListMsg {
@@ -591,15 +832,28 @@ pub enum DBListMsg {
     MoveBottom,
     Expand,
     Fold,
-    SelectTable { database: String, table: String },
+    SelectTable { database: String, schema: Option<String>, table: String, kind: TableKind },
+    /// Launch the external CLI focused on the selected table
+    OpenInCli { database: String, schema: Option<String>, table: String },
     ToggleExpand,
+    ExpandAll,
+    CollapseAll,
     Filter,
-    Load(Connection),
+    /// Boxed: a Connection dwarfs every other variant
+    Load(Box<Connection>),
+    /// Reload the tree for the current connection, keeping its expanded nodes
+    Refresh,
+    /// Show a message in the status bar; handled by the root
+    Status(StatusMessage),
     Loaded(Vec<Database>),
     LoadFailed(String),
+    /// Counts for the tables of one database, fetched after it is expanded
+    RowCounts(RowCounts),
     FilterPush(char),
     FilterPop,
     FilterConfirm,
+    /// A row of the tree was clicked, counted from the top of the whole list
+    Click(usize),
 }
 
 pub enum Focus {
@@ -607,19 +861,184 @@ pub enum Focus {
     Filter,
 }
 
+impl TreeIcons {
+    /// Expanded/folded marker of a database or schema node
+    pub(super) fn expander(self, expanded: bool, has_children: bool) -> &'static str {
+        match (self, expanded, has_children) {
+            (TreeIcons::Unicode, true, _) => "▼",
+            (TreeIcons::Unicode, false, true) => "▶",
+            (TreeIcons::Ascii, true, _) => "v",
+            (TreeIcons::Ascii, false, true) => ">",
+            (_, false, false) => " ",
+        }
+    }
+
+    /// Marker in front of the selected row
+    pub(super) fn highlight(self) -> &'static str {
+        match self {
+            TreeIcons::Unicode => "▶ ",
+            TreeIcons::Ascii => "> ",
+        }
+    }
+
+    fn database(self) -> &'static str {
+        match self {
+            TreeIcons::Unicode => "📁",
+            TreeIcons::Ascii => "[D]",
+        }
+    }
+
+    fn schema(self) -> &'static str {
+        match self {
+            TreeIcons::Unicode => "📂",
+            TreeIcons::Ascii => "[S]",
+        }
+    }
+
+    fn table(self, kind: TableKind) -> &'static str {
+        match (self, kind) {
+            (TreeIcons::Unicode, TableKind::Table) => "📄",
+            (TreeIcons::Unicode, TableKind::View) => "🔎",
+            (TreeIcons::Ascii, TableKind::Table) => "[T]",
+            (TreeIcons::Ascii, TableKind::View) => "[V]",
+        }
+    }
+}
+
 pub struct DBListComponent {
     databases: Databases,
     focus: Focus,
+    enter_action: TreeEnterAction,
+    /// Connection the tree was loaded from, for `Refresh`
+    connection: Option<Connection>,
+    refreshing: bool,
+    /// Shown in the title: a refresh in progress or its failure
+    status: Option<String>,
+    /// Why the last load failed; shown in place of an empty tree until a load succeeds
+    load_error: Option<String>,
+    /// Show row counts next to tables (`tree_row_counts` in config)
+    show_row_counts: bool,
+    icons: TreeIcons,
+    /// Order names with digits compared by value (`tree_natural_sort` in config)
+    natural_sort: bool,
+    row_counts: RowCounts,
+    /// Databases whose counts have been requested since the last load
+    counts_requested: HashSet<String>,
+    theme: Theme,
+    keymap: Keymap,
+    /// Row and time of the last click, to spot a double click
+    last_click: Option<(usize, Instant)>,
+    /// Where the tree was last drawn and how far it was scrolled, to map clicks to rows
+    tree_area: Cell<Rect>,
+    tree_offset: Cell<usize>,
 }
 
+/// Longest gap between the two clicks of a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
 impl DBListComponent {
-    pub fn new() -> Self {
+    pub fn new(
+        enter_action: TreeEnterAction,
+        show_row_counts: bool,
+        icons: TreeIcons,
+        natural_sort: bool,
+        theme: Theme,
+        keymap: Keymap,
+    ) -> Self {
         Self {
             databases: Databases::default(),
             focus: Focus::Tree,
+            enter_action,
+            connection: None,
+            refreshing: false,
+            status: None,
+            load_error: None,
+            show_row_counts,
+            icons,
+            natural_sort,
+            row_counts: HashMap::new(),
+            counts_requested: HashSet::new(),
+            theme,
+            keymap,
+            last_click: None,
+            tree_area: Cell::default(),
+            tree_offset: Cell::default(),
         }
     }
 
+    /// Keys while the tree itself has focus
+    fn handle_tree_key(&self, key: KeyEvent) -> Update<DBListMsg> {
+        let is = |action| self.keymap.is(action, &key);
+        let msg = if is(Action::MoveUp) {
+            DBListMsg::MoveUp
+        } else if is(Action::MoveDown) {
+            DBListMsg::MoveDown
+        } else if is(Action::MoveTop) {
+            DBListMsg::MoveTop
+        } else if is(Action::MoveBottom) {
+            DBListMsg::MoveBottom
+        } else if is(Action::Expand) {
+            match self.databases.get_selected() {
+                Some(SelectedRef::Table { database, schema, table, kind }) => DBListMsg::SelectTable {
+                    database: database.to_string(),
+                    schema: schema.map(str::to_string),
+                    table: table.to_string(),
+                    kind,
+                },
+                Some(SelectedRef::Database(_)) | Some(SelectedRef::Schema { .. }) => DBListMsg::Expand,
+                None => return Update::none(),
+            }
+        } else if is(Action::Fold) {
+            DBListMsg::Fold
+        } else if is(Action::ExpandAll) {
+            DBListMsg::ExpandAll
+        } else if is(Action::CollapseAll) {
+            DBListMsg::CollapseAll
+        } else if is(Action::OpenCli) {
+            match self.databases.get_selected() {
+                Some(SelectedRef::Table { database, schema, table, .. }) => DBListMsg::OpenInCli {
+                    database: database.to_string(),
+                    schema: schema.map(str::to_string),
+                    table: table.to_string(),
+                },
+                _ => return Update::none(),
+            }
+        } else if is(Action::Filter) {
+            DBListMsg::Filter
+        } else if is(Action::Refresh) {
+            DBListMsg::Refresh
+        } else if is(Action::Back) {
+            DBListMsg::LeaveDashboard
+        } else if is(Action::ToggleExpand) || is(Action::Select) {
+            match self.databases.get_selected() {
+                Some(SelectedRef::Table { database, schema, table, kind }) => DBListMsg::SelectTable {
+                    database: database.to_string(),
+                    schema: schema.map(str::to_string),
+                    table: table.to_string(),
+                    kind,
+                },
+                Some(SelectedRef::Database(_)) | Some(SelectedRef::Schema { .. }) => {
+                    match (is(Action::ToggleExpand), self.enter_action) {
+                        (true, _) | (_, TreeEnterAction::Toggle) => DBListMsg::ToggleExpand,
+                        (false, TreeEnterAction::SelectFirstTable) => {
+                            match self.databases.first_table_under_selected() {
+                                Some((database, schema, table, kind)) => {
+                                    DBListMsg::SelectTable { database, schema, table, kind }
+                                }
+                                None => return Update::none(),
+                            }
+                        }
+                        (false, TreeEnterAction::None) => return Update::none(),
+                    }
+                }
+                None => return Update::none(),
+            }
+        } else {
+            return Update::none();
+        };
+        msg.into()
+    }
+
     fn on_load(conn: Connection) -> impl FnOnce(std::sync::mpsc::Sender<AppMsg>) + Send + 'static {
         move |tx: std::sync::mpsc::Sender<AppMsg>| {
             info(&format!("DBList: loading databases for {:?}", conn.r#type));
@@ -638,17 +1057,167 @@ impl DBListComponent {
         }
     }
 
-    fn on_loaded(&mut self, dbs: Vec<Database>) -> Update<DBListMsg> {
+    fn on_loaded(&mut self, mut dbs: Vec<Database>) -> Update<DBListMsg> {
+        if self.natural_sort {
+            sort_naturally(&mut dbs);
+        }
+        self.status = None;
+        self.load_error = None;
+        // Counts are fetched again as databases are (still) expanded
+        self.row_counts.clear();
+        self.counts_requested.clear();
+        if std::mem::take(&mut self.refreshing) {
+            self.databases.refresh(dbs);
+            return Update::none();
+        }
         self.databases = Databases::new(dbs);
         self.focus = Focus::Tree;
         self.databases.select_first();
         Update::none()
     }
 
+    /// Fetch row counts for expanded databases not yet requested, one task
+    /// per database so the tree never waits on them
+    fn request_row_counts(&mut self) -> Command {
+        let Some(conn) = self.connection.as_ref().filter(|_| self.show_row_counts) else {
+            return Command::none();
+        };
+        let mut cmds = Vec::new();
+        for db in self.databases.data() {
+            if !db.is_expanded || !self.counts_requested.insert(db.name.clone()) {
+                continue;
+            }
+            let tables: Vec<TableKey> = db
+                .children
+                .iter()
+                .flat_map(|child| match child {
+                    Child::Table(t) if t.kind == TableKind::Table => vec![(db.name.clone(), None, t.name.clone())],
+                    Child::Table(_) => vec![],
+                    Child::Schema(s) => s
+                        .tables
+                        .iter()
+                        .filter(|t| t.kind == TableKind::Table)
+                        .map(|t| (db.name.clone(), Some(s.name.clone()), t.name.clone()))
+                        .collect(),
+                })
+                .collect();
+            let conn = conn.clone();
+            let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+                let counts = tables
+                    .into_iter()
+                    .map(|key| {
+                        let count = db::DB::fetch_row_count(&conn, &key.0, key.1.as_deref(), &key.2, false)
+                            .map_err(|e| error(&format!("DBList: row count of {} failed: {}", key.2, e)))
+                            .ok();
+                        (key, count)
+                    })
+                    .collect();
+                let _ = tx.send(DBListMsg::RowCounts(counts).into());
+            };
+            cmds.push(Command::Spawn(Box::new(task)));
+        }
+        Command::batch(cmds)
+    }
+
+    fn handle_msg(&mut self, msg: DBListMsg) -> Update<DBListMsg> {
+        match msg {
+            DBListMsg::Load(conn) => {
+                self.connection = Some((*conn).clone());
+                self.refreshing = false;
+                Command::Spawn(Box::new(Self::on_load(*conn))).into()
+            }
+            DBListMsg::Refresh => self.refresh().into(),
+            DBListMsg::Loaded(dbs) => self.on_loaded(dbs),
+            DBListMsg::LoadFailed(err) => {
+                if std::mem::take(&mut self.refreshing) {
+                    self.status = Some(format!("refresh failed: {}", err));
+                } else {
+                    // Don't leave the previous connection's tree up
+                    self.databases = Databases::default();
+                }
+                self.load_error = Some(err.clone());
+                DBListMsg::Status(StatusMessage::error(format!("loading databases failed: {}", err))).into()
+            }
+            DBListMsg::RowCounts(counts) => {
+                self.row_counts.extend(counts);
+                Update::none()
+            }
+            DBListMsg::MoveUp => self.move_up().into(),
+            DBListMsg::MoveDown => self.move_down().into(),
+            DBListMsg::MoveTop => self.move_top().into(),
+            DBListMsg::MoveBottom => self.move_bottom().into(),
+            DBListMsg::Expand => self.expand().into(),
+            DBListMsg::Fold => self.fold().into(),
+            DBListMsg::Filter => self.move_focus_to_filter().into(),
+            DBListMsg::LeaveDashboard => Update::none(), // Handled by parent
+            DBListMsg::ToggleExpand => self.toggle_expand().into(),
+            DBListMsg::ExpandAll => self.expand_all().into(),
+            DBListMsg::CollapseAll => self.collapse_all().into(),
+            DBListMsg::SelectTable { .. } => Update::none(), // Handled by parent
+            DBListMsg::OpenInCli { .. } => Update::none(), // Handled by parent
+            DBListMsg::Status(_) => Update::none(), // Handled by parent
+            DBListMsg::FilterPush(c) => self.push_filter_char(c).into(),
+            DBListMsg::FilterPop => self.pop_filter_char().into(),
+            DBListMsg::Click(index) => self.click(index),
+            DBListMsg::FilterConfirm => {
+                self.move_focus_to_tree();
+                Update::none()
+            }
+        }
+    }
+
+    fn refresh(&mut self) -> Command {
+        let Some(conn) = self.connection.clone() else {
+            return Command::none();
+        };
+        self.refreshing = true;
+        self.status = Some("refreshing…".to_string());
+        Command::Spawn(Box::new(Self::on_load(conn)))
+    }
+
+    pub fn databases(&self) -> &[Database] {
+        self.databases.data()
+    }
+
+    /// Select (and make visible) a table picked outside the tree, e.g. by the finder
+    pub fn reveal_table(&mut self, database: &str, schema: Option<&str>, table: &str) -> Option<TableKind> {
+        self.focus = Focus::Tree;
+        self.databases.reveal_table(database, schema, table)
+    }
+
     fn move_focus_to_filter(&mut self) {
         self.focus = Focus::Filter;
     }
 
+    /// Select the clicked row. Clicking it again right away opens a table
+    /// or expands/folds a database or schema.
+    fn click(&mut self, index: usize) -> Update<DBListMsg> {
+        self.move_focus_to_tree();
+        if !self.databases.select_visible(index) {
+            self.last_click = None;
+            return Update::none();
+        }
+        let now = Instant::now();
+        let double = self
+            .last_click
+            .is_some_and(|(row, at)| row == index && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL);
+        self.last_click = (!double).then_some((index, now));
+        if !double {
+            return Update::none();
+        }
+        match self.databases.get_selected() {
+            Some(SelectedRef::Table { database, schema, table, kind }) => DBListMsg::SelectTable {
+                database: database.to_string(),
+                schema: schema.map(str::to_string),
+                table: table.to_string(),
+                kind,
+            }
+            .into(),
+            Some(SelectedRef::Database(_)) | Some(SelectedRef::Schema { .. }) => self.toggle_expand().into(),
+            None => Update::none(),
+        }
+    }
+
     fn move_focus_to_tree(&mut self) {
         self.focus = Focus::Tree;
     }
@@ -688,74 +1257,31 @@ impl DBListComponent {
     fn toggle_expand(&mut self) {
         self.databases.toggle_expand_selected();
     }
+
+    fn expand_all(&mut self) {
+        self.databases.expand_all();
+    }
+
+    fn collapse_all(&mut self) {
+        self.databases.collapse_all();
+    }
 }
 
 impl Component for DBListComponent {
     type Msg = DBListMsg;
 
     fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
-        match msg {
-            DBListMsg::Load(conn) => Command::Spawn(Box::new(Self::on_load(conn))).into(),
-            DBListMsg::Loaded(dbs) => self.on_loaded(dbs),
-            DBListMsg::LoadFailed(_err) => Update::none(),
-            DBListMsg::MoveUp => self.move_up().into(),
-            DBListMsg::MoveDown => self.move_down().into(),
-            DBListMsg::MoveTop => self.move_top().into(),
-            DBListMsg::MoveBottom => self.move_bottom().into(),
-            DBListMsg::Expand => self.expand().into(),
-            DBListMsg::Fold => self.fold().into(),
-            DBListMsg::Filter => self.move_focus_to_filter().into(),
-            DBListMsg::LeaveDashboard => Update::none(), // Handled by parent
-            DBListMsg::ToggleExpand => self.toggle_expand().into(),
-            DBListMsg::SelectTable {
-                database: _,
-                table: _,
-            } => Update::none(), // Handled by parent
-            DBListMsg::FilterPush(c) => self.push_filter_char(c).into(),
-            DBListMsg::FilterPop => self.pop_filter_char().into(),
-            DBListMsg::FilterConfirm => {
-                self.move_focus_to_tree();
-                Update::none()
-            }
-        }
+        let update = self.handle_msg(msg);
+        // Any message may have expanded a database
+        let counts = self.request_row_counts();
+        Update { msg: update.msg, cmd: Command::batch([update.cmd, counts]) }
     }
 
     fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
         use crossterm::event::KeyCode::*;
 
         match self.focus {
-            Focus::Tree => match key.code {
-                Up | Char('k') => DBListMsg::MoveUp.into(),
-                Down | Char('j') => DBListMsg::MoveDown.into(),
-                Char('g') => DBListMsg::MoveTop.into(),
-                Char('G') => DBListMsg::MoveBottom.into(),
-                Right | Char('l') => {
-                    match self.databases.get_selected() {
-                        Some(SelectedRef::Table { database, table, .. }) => DBListMsg::SelectTable {
-                            database: database.to_string(),
-                            table: table.to_string(),
-                        },
-                        Some(SelectedRef::Database(_)) | Some(SelectedRef::Schema { .. }) => DBListMsg::Expand,
-                        None => return Update::none(),
-                    }
-                    .into()
-                }
-                Left | Char('h') => DBListMsg::Fold.into(),
-                Char('/') => DBListMsg::Filter.into(),
-                Esc => DBListMsg::LeaveDashboard.into(),
-                Enter | Tab => {
-                    match self.databases.get_selected() {
-                        Some(SelectedRef::Table { database, table, .. }) => DBListMsg::SelectTable {
-                            database: database.to_string(),
-                            table: table.to_string(),
-                        },
-                        Some(SelectedRef::Database(_)) | Some(SelectedRef::Schema { .. }) => DBListMsg::ToggleExpand,
-                        None => return Update::none(),
-                    }
-                    .into()
-                }
-                _ => Update::none(),
-            },
+            Focus::Tree => self.handle_tree_key(key),
             Focus::Filter => match key.code {
                 Esc | Enter => DBListMsg::FilterConfirm.into(),
                 Char(c) => DBListMsg::FilterPush(c).into(),
@@ -765,6 +1291,56 @@ impl Component for DBListComponent {
         }
     }
 
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        self.keymap.help(&[
+            (Action::MoveUp, "Move up"),
+            (Action::MoveDown, "Move down"),
+            (Action::MoveTop, "First node"),
+            (Action::MoveBottom, "Last node"),
+            (Action::Expand, "Open table, expand node"),
+            (Action::Fold, "Collapse node"),
+            (
+                Action::Select,
+                match self.enter_action {
+                    TreeEnterAction::Toggle => "Open table, toggle node",
+                    TreeEnterAction::SelectFirstTable => "Open table (on a node, its first table)",
+                    TreeEnterAction::None => "Open table",
+                },
+            ),
+            (Action::ToggleExpand, "Open table, toggle node"),
+            (Action::ExpandAll, "Expand all"),
+            (Action::CollapseAll, "Collapse all"),
+            (Action::OpenCli, "Open table in the external CLI"),
+            (Action::Filter, "Filter the tree"),
+            (Action::Refresh, "Reload the tree and table properties"),
+            (Action::Back, "Back to connections"),
+        ])
+    }
+
+    fn handle_mouse(&self, event: MouseEvent) -> Update<Self::Msg> {
+        let area = self.tree_area.get();
+        if !in_area(area, &event) {
+            return Update::none();
+        }
+        match event.kind {
+            MouseEventKind::ScrollUp => DBListMsg::MoveUp.into(),
+            MouseEventKind::ScrollDown => DBListMsg::MoveDown.into(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                // Rows start inside the border, shifted by how far the list is scrolled
+                let inner = area.inner(&Margin { horizontal: 1, vertical: 1 });
+                if !in_area(inner, &event) {
+                    return Update::none();
+                }
+                DBListMsg::Click(self.tree_offset.get() + usize::from(event.row - inner.y)).into()
+            }
+            _ => Update::none(),
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        matches!(self.focus, Focus::Filter)
+    }
+
     fn draw(&self, f: &mut Frame, area: Rect, focused: bool) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -773,9 +1349,12 @@ impl Component for DBListComponent {
 
         let tree_area = chunks[0];
         let filter_area = chunks[1];
+        self.tree_area.set(tree_area);
+        self.tree_offset.set(0);
 
         // Build list items
-        let (list_items, selected_index) = self.databases.build_list_items();
+        let row_counts = self.show_row_counts.then_some(&self.row_counts);
+        let (list_items, selected_index) = self.databases.build_list_items(row_counts, self.icons);
         let items: Vec<ListItem> = if list_items.is_empty() {
             vec![ListItem::new("(no database structure)")]
         } else {
@@ -785,37 +1364,42 @@ impl Component for DBListComponent {
                 .collect()
         };
 
-        let tree_style = if focused && matches!(self.focus, Focus::Tree) {
-            Style::default().fg(Color::Yellow)
-        } else {
-            Style::default().fg(Color::White)
-        };
+        let tree_style = self.theme.border(focused && matches!(self.focus, Focus::Tree));
 
-        let list = List::new(items)
-            .block(
-                Block::default()
-                    .title("Database Structure")
-                    .borders(Borders::ALL)
-                    .border_style(tree_style),
-            )
-            .highlight_style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .highlight_symbol("▶ ");
-
-        let mut state = ListState::default();
-        state.select(selected_index);
-
-        f.render_stateful_widget(list, tree_area, &mut state);
+        let block = Block::default()
+            .title(match &self.status {
+                Some(status) => format!("Database Structure ({})", status),
+                None => "Database Structure".to_string(),
+            })
+            .borders(Borders::ALL)
+            .border_style(tree_style);
+
+        if let Some(err) = self.load_error.as_ref().filter(|_| self.databases.data().is_empty()) {
+            // The load failed and there is no earlier tree to fall back on
+            let text = Paragraph::new(format!("Error: {}", err))
+                .style(Style::default().fg(Color::Red))
+                .wrap(Wrap { trim: false })
+                .block(block);
+            f.render_widget(text, tree_area);
+        } else {
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(
+                    Style::default()
+                        .fg(self.theme.highlight)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .highlight_symbol(self.icons.highlight());
+
+            let mut state = ListState::default();
+            state.select(selected_index);
+
+            f.render_stateful_widget(list, tree_area, &mut state);
+            self.tree_offset.set(state.offset());
+        }
 
         // Filter input
-        let filter_style = if focused && matches!(self.focus, Focus::Filter) {
-            Style::default().fg(Color::Yellow)
-        } else {
-            Style::default().fg(Color::White)
-        };
+        let filter_style = self.theme.border(focused && matches!(self.focus, Focus::Filter));
 
         let filter_block = Block::default()
             .title("Filter")
@@ -832,3 +1416,197 @@ impl Component for DBListComponent {
         f.render_widget(filter_paragraph, filter_area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    /// A tree with the `app` database node selected
+    fn tree(enter_action: TreeEnterAction) -> DBListComponent {
+        let mut dblist =
+            DBListComponent::new(enter_action, false, TreeIcons::Unicode, false, Theme::default(), Keymap::default());
+        let table = |name: &str| Table { name: name.to_string(), engine: None, schema: None, kind: TableKind::Table };
+        let public = Schema {
+            name: "public".to_string(),
+            tables: vec![table("users"), table("orders")],
+            is_expanded: false,
+        };
+        dblist.databases = Databases::new(vec![Database::new("app".to_string(), vec![public.into()])]);
+        dblist.databases.select_first();
+        dblist
+    }
+
+    fn press(dblist: &mut DBListComponent, code: KeyCode) -> Option<DBListMsg> {
+        let msg = dblist.handle_key(KeyEvent::new(code, KeyModifiers::NONE)).msg;
+        match msg {
+            Some(DBListMsg::ToggleExpand) => {
+                let _ = dblist.update(DBListMsg::ToggleExpand);
+            }
+            Some(DBListMsg::MoveDown) => {
+                let _ = dblist.update(DBListMsg::MoveDown);
+            }
+            _ => {}
+        }
+        msg
+    }
+
+    fn expanded(dblist: &DBListComponent) -> bool {
+        dblist.databases.data()[0].is_expanded
+    }
+
+    #[test]
+    fn enter_toggles_a_database_node() {
+        let mut dblist = tree(TreeEnterAction::Toggle);
+        assert!(matches!(press(&mut dblist, KeyCode::Enter), Some(DBListMsg::ToggleExpand)));
+        assert!(expanded(&dblist));
+        press(&mut dblist, KeyCode::Enter);
+        assert!(!expanded(&dblist));
+    }
+
+    #[test]
+    fn enter_opens_the_first_table_under_a_database_node() {
+        let mut dblist = tree(TreeEnterAction::SelectFirstTable);
+        let Some(DBListMsg::SelectTable { database, schema, table, .. }) = press(&mut dblist, KeyCode::Enter) else {
+            panic!("Enter should open a table");
+        };
+        assert_eq!((database.as_str(), schema.as_deref(), table.as_str()), ("app", Some("public"), "users"));
+        assert!(!expanded(&dblist));
+    }
+
+    #[test]
+    fn enter_does_nothing_on_a_database_node_when_disabled() {
+        let mut dblist = tree(TreeEnterAction::None);
+        assert!(press(&mut dblist, KeyCode::Enter).is_none());
+        assert!(!expanded(&dblist));
+        // Tab still toggles
+        press(&mut dblist, KeyCode::Tab);
+        assert!(expanded(&dblist));
+    }
+
+    /// Expanded, collapsed and childless databases and schemas, holding tables and views
+    fn icon_tree() -> Databases {
+        let table = |name: &str, kind| Table { name: name.to_string(), engine: None, schema: None, kind };
+        let schema = |name: &str, tables, is_expanded| Schema { name: name.to_string(), tables, is_expanded };
+        let database = |name: &str, children, is_expanded| {
+            let mut db = Database::new(name.to_string(), children);
+            db.is_expanded = is_expanded;
+            db
+        };
+        Databases::new(vec![
+            database(
+                "app",
+                vec![
+                    schema("public", vec![table("users", TableKind::Table), table("active", TableKind::View)], true)
+                        .into(),
+                    schema("audit", vec![table("log", TableKind::Table)], false).into(),
+                    schema("empty", vec![], false).into(),
+                ],
+                true,
+            ),
+            database(
+                "flat",
+                vec![table("t", TableKind::Table).into(), table("v", TableKind::View).into()],
+                true,
+            ),
+            database("closed", vec![table("t", TableKind::Table).into()], false),
+            database("none", vec![], false),
+        ])
+    }
+
+    fn labels(icons: TreeIcons) -> Vec<String> {
+        icon_tree().build_list_items(None, icons).0.into_iter().map(|(label, _)| label).collect()
+    }
+
+    #[test]
+    fn unicode_icons_mark_each_kind_of_row() {
+        assert_eq!(
+            labels(TreeIcons::Unicode),
+            [
+                "▼ 📁 app",
+                "  ▼ 📂 public",
+                "      📄 users",
+                "      🔎 active",
+                "  ▶ 📂 audit",
+                "    📂 empty",
+                "▼ 📁 flat",
+                "    📄 t",
+                "    🔎 v",
+                "▶ 📁 closed",
+                "  📁 none",
+            ]
+        );
+    }
+
+    #[test]
+    fn ascii_icons_mark_each_kind_of_row() {
+        assert_eq!(
+            labels(TreeIcons::Ascii),
+            [
+                "v [D] app",
+                "  v [S] public",
+                "      [T] users",
+                "      [V] active",
+                "  > [S] audit",
+                "    [S] empty",
+                "v [D] flat",
+                "    [T] t",
+                "    [V] v",
+                "> [D] closed",
+                "  [D] none",
+            ]
+        );
+    }
+
+    #[test]
+    fn ascii_icons_mark_the_selection_with_ascii() {
+        let theme = Theme::default();
+        let mut dblist =
+            DBListComponent::new(TreeEnterAction::Toggle, false, TreeIcons::Ascii, false, theme, Keymap::default());
+        dblist.databases = icon_tree();
+        dblist.databases.select_first();
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(30, 16)).unwrap();
+        terminal.draw(|f| dblist.draw(f, f.size(), true)).unwrap();
+        let text: String = terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect();
+        assert!(text.contains("> v [D] app"));
+        assert!(!text.contains('▶'));
+    }
+
+    fn selected_table(msg: Option<DBListMsg>) -> (String, Option<String>, String) {
+        let Some(DBListMsg::SelectTable { database, schema, table, .. }) = msg else {
+            panic!("expected a table to open");
+        };
+        (database, schema, table)
+    }
+
+    #[test]
+    fn tables_open_with_their_own_database() {
+        // Shaped like MySQL: one node per database, tables directly under it
+        let mut dblist = tree(TreeEnterAction::Toggle);
+        let database = |name: &str, tables: &[&str]| {
+            let children = tables
+                .iter()
+                .map(|t| Table { name: t.to_string(), engine: None, schema: None, kind: TableKind::Table }.into())
+                .collect();
+            Database::new(name.to_string(), children)
+        };
+        dblist.databases = Databases::new(vec![
+            database("shop", &["customers", "orders"]),
+            database("analytics", &["events", "orders"]),
+        ]);
+        dblist.databases.select_first();
+
+        press(&mut dblist, KeyCode::Enter);
+        press(&mut dblist, KeyCode::Char('j'));
+        press(&mut dblist, KeyCode::Char('j'));
+        let shop = selected_table(press(&mut dblist, KeyCode::Enter));
+        assert_eq!(shop, ("shop".to_string(), None, "orders".to_string()));
+
+        press(&mut dblist, KeyCode::Char('j'));
+        press(&mut dblist, KeyCode::Enter);
+        press(&mut dblist, KeyCode::Char('j'));
+        press(&mut dblist, KeyCode::Char('j'));
+        let analytics = selected_table(press(&mut dblist, KeyCode::Enter));
+        assert_eq!(analytics, ("analytics".to_string(), None, "orders".to_string()));
+    }
+}
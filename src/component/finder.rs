@@ -0,0 +1,302 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use super::{Child, Component, Database};
+use crate::theme::Theme;
+use crate::update::Update;
+
+/// A table reachable from the fuzzy finder
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinderEntry {
+    pub database: String,
+    pub schema: Option<String>,
+    pub table: String,
+}
+
+impl FinderEntry {
+    /// Text the query is matched against: `database.schema.table`
+    pub fn label(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}.{}", self.database, schema, self.table),
+            None => format!("{}.{}", self.database, self.table),
+        }
+    }
+}
+
+/// Every table of the loaded tree, in tree order, regardless of expansion or filter
+pub fn build_index(databases: &[Database]) -> Vec<FinderEntry> {
+    let mut entries = Vec::new();
+    for db in databases {
+        for child in &db.children {
+            match child {
+                Child::Table(t) => entries.push(FinderEntry {
+                    database: db.name.clone(),
+                    schema: None,
+                    table: t.name.clone(),
+                }),
+                Child::Schema(s) => entries.extend(s.tables.iter().map(|t| FinderEntry {
+                    database: db.name.clone(),
+                    schema: Some(s.name.clone()),
+                    table: t.name.clone(),
+                })),
+            }
+        }
+    }
+    entries
+}
+
+/// Case-insensitive subsequence match. Higher is better; consecutive matches
+/// and matches at the start of a word (after `.`, `_`, `-` or space) score extra,
+/// gaps cost a little. `None` when `query` isn't a subsequence of `candidate`.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut pos = 0usize;
+    let mut prev: Option<usize> = None;
+    for q in query.to_lowercase().chars() {
+        let idx = pos + candidate[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        match prev {
+            Some(p) if p + 1 == idx => score += 5,
+            Some(p) => score -= (idx - p - 1).min(5) as i64,
+            None => score -= idx.min(5) as i64,
+        }
+        if idx == 0 || matches!(candidate[idx - 1], '.' | '_' | '-' | ' ') {
+            score += 3;
+        }
+        prev = Some(idx);
+        pos = idx + 1;
+    }
+    Some(score)
+}
+
+pub enum FinderMsg {
+    Close,
+    Push(char),
+    Pop,
+    MoveUp,
+    MoveDown,
+    /// Jump to the chosen table; handled by the parent
+    Confirm(FinderEntry),
+}
+
+#[derive(Default)]
+pub struct FinderComponent {
+    open: bool,
+    entries: Vec<FinderEntry>,
+    query: String,
+    /// Indices into `entries`, best match first
+    matches: Vec<usize>,
+    selected: usize,
+    theme: Theme,
+}
+
+impl FinderComponent {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme, ..Self::default() }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self, entries: Vec<FinderEntry>) {
+        self.open = true;
+        self.entries = entries;
+        self.query.clear();
+        self.refresh();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.entries.clear();
+        self.matches.clear();
+    }
+
+    fn refresh(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| fuzzy_score(&self.query, &e.label()).map(|s| (s, i)))
+            .collect();
+        // Stable: equal scores keep tree order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    fn selected_entry(&self) -> Option<&FinderEntry> {
+        self.matches.get(self.selected).map(|&i| &self.entries[i])
+    }
+}
+
+impl Component for FinderComponent {
+    type Msg = FinderMsg;
+
+    fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
+        match msg {
+            FinderMsg::Close => self.close(),
+            FinderMsg::Push(c) => {
+                self.query.push(c);
+                self.refresh();
+            }
+            FinderMsg::Pop => {
+                self.query.pop();
+                self.refresh();
+            }
+            FinderMsg::MoveUp => self.selected = self.selected.saturating_sub(1),
+            FinderMsg::MoveDown => {
+                if self.selected + 1 < self.matches.len() {
+                    self.selected += 1;
+                }
+            }
+            FinderMsg::Confirm(_) => {} // Handled by parent
+        }
+        Update::none()
+    }
+
+    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        use crossterm::event::KeyCode::*;
+
+        match key.code {
+            Esc => FinderMsg::Close.into(),
+            Enter => match self.selected_entry() {
+                Some(entry) => FinderMsg::Confirm(entry.clone()).into(),
+                None => Update::none(),
+            },
+            Up => FinderMsg::MoveUp.into(),
+            Down => FinderMsg::MoveDown.into(),
+            Backspace => FinderMsg::Pop.into(),
+            Char(c) => FinderMsg::Push(c).into(),
+            _ => Update::none(),
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.open
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) {
+        if !self.open {
+            return;
+        }
+        // Centered popup over the dashboard
+        let width = area.width.saturating_mul(3) / 5;
+        let height = area.height.saturating_mul(3) / 5;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(popup);
+
+        let input = Paragraph::new(format!("{}_", self.query)).block(
+            Block::default()
+                .title("Find table (Enter: open, Esc: close)")
+                .borders(Borders::ALL)
+                .border_style(self.theme.border(true)),
+        );
+        f.render_widget(input, chunks[0]);
+
+        let items: Vec<ListItem> = if self.matches.is_empty() {
+            vec![ListItem::new("(no matching tables)")]
+        } else {
+            self.matches
+                .iter()
+                .map(|&i| ListItem::new(self.entries[i].label()))
+                .collect()
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!("{} / {}", self.matches.len(), self.entries.len()))
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border(true)),
+            )
+            .highlight_style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default();
+        state.select((!self.matches.is_empty()).then_some(self.selected));
+        f.render_stateful_widget(list, chunks[1], &mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{Schema, Table, TableKind};
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    fn table(name: &str) -> Table {
+        Table { name: name.to_string(), engine: None, schema: None, kind: TableKind::Table }
+    }
+
+    fn fixture() -> Vec<Database> {
+        let schema = |name: &str, tables: &[&str]| Schema {
+            name: name.to_string(),
+            tables: tables.iter().map(|t| table(t)).collect(),
+            is_expanded: false,
+        };
+        vec![
+            Database::new("shop".to_string(), vec![table("orders").into(), table("customers").into()]),
+            Database::new(
+                "app".to_string(),
+                vec![schema("public", &["users", "user_roles"]).into(), schema("audit", &["events"]).into()],
+            ),
+        ]
+    }
+
+    #[test]
+    fn index_lists_every_table_in_tree_order() {
+        let labels: Vec<String> = build_index(&fixture()).iter().map(FinderEntry::label).collect();
+        assert_eq!(
+            labels,
+            ["shop.orders", "shop.customers", "app.public.users", "app.public.user_roles", "app.audit.events"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("xyz", "app.public.users"), None);
+        let users = fuzzy_score("users", "app.public.users").unwrap();
+        let scattered = fuzzy_score("users", "app.public.user_roles").unwrap();
+        assert!(users > scattered);
+        assert!(fuzzy_score("USR", "app.public.users").is_some());
+    }
+
+    #[test]
+    fn enter_confirms_the_selected_match() {
+        let mut finder = FinderComponent::default();
+        finder.open(build_index(&fixture()));
+        for c in "app".chars() {
+            finder.update(FinderMsg::Push(c));
+        }
+        finder.update(FinderMsg::MoveDown);
+        let update = finder.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        let Some(FinderMsg::Confirm(entry)) = update.msg else {
+            panic!("Enter should confirm a match");
+        };
+        assert_eq!(entry.label(), "app.public.user_roles");
+    }
+
+    #[test]
+    fn confirmed_entry_is_revealed_in_the_tree() {
+        let mut databases = crate::component::dblist::Databases::new(fixture());
+        let entry = &build_index(&fixture())[3];
+        let kind = databases.reveal_table(&entry.database, entry.schema.as_deref(), &entry.table);
+        assert_eq!(kind, Some(TableKind::Table));
+        assert!(databases.data()[1].is_expanded);
+    }
+}
@@ -0,0 +1,192 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use super::Component;
+use crate::history::HistoryEntry;
+use crate::theme::Theme;
+use crate::update::Update;
+
+pub enum HistoryMsg {
+    Close,
+    Push(char),
+    Pop,
+    MoveUp,
+    MoveDown,
+    /// Load the chosen statement into the editor; handled by the parent
+    Confirm(String),
+}
+
+/// Popup listing past statements, newest first, narrowed by what is typed
+#[derive(Default)]
+pub struct HistoryComponent {
+    open: bool,
+    /// Newest first
+    entries: Vec<HistoryEntry>,
+    query: String,
+    /// Indices into `entries` that contain every word of the query
+    matches: Vec<usize>,
+    selected: usize,
+    theme: Theme,
+}
+
+impl HistoryComponent {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme, ..Self::default() }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open with `entries` as read from the history file, oldest first
+    pub fn open(&mut self, mut entries: Vec<HistoryEntry>) {
+        entries.reverse();
+        self.open = true;
+        self.entries = entries;
+        self.query.clear();
+        self.refresh();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.entries.clear();
+        self.matches.clear();
+    }
+
+    /// Case-insensitive; each whitespace-separated word must appear in the
+    /// statement or the connection name
+    fn refresh(&mut self) {
+        let words: Vec<String> = self.query.to_lowercase().split_whitespace().map(str::to_string).collect();
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| {
+                let haystack = format!("{} {}", e.connection, e.sql).to_lowercase();
+                words.iter().all(|w| haystack.contains(w.as_str()))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = 0;
+    }
+
+    fn selected_entry(&self) -> Option<&HistoryEntry> {
+        self.matches.get(self.selected).map(|&i| &self.entries[i])
+    }
+}
+
+impl Component for HistoryComponent {
+    type Msg = HistoryMsg;
+
+    fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
+        match msg {
+            HistoryMsg::Close => self.close(),
+            HistoryMsg::Push(c) => {
+                self.query.push(c);
+                self.refresh();
+            }
+            HistoryMsg::Pop => {
+                self.query.pop();
+                self.refresh();
+            }
+            HistoryMsg::MoveUp => self.selected = self.selected.saturating_sub(1),
+            HistoryMsg::MoveDown => {
+                if self.selected + 1 < self.matches.len() {
+                    self.selected += 1;
+                }
+            }
+            HistoryMsg::Confirm(_) => {} // Handled by parent
+        }
+        Update::none()
+    }
+
+    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        use crossterm::event::KeyCode::*;
+
+        match key.code {
+            Esc => HistoryMsg::Close.into(),
+            Enter => match self.selected_entry() {
+                Some(entry) => HistoryMsg::Confirm(entry.sql.clone()).into(),
+                None => Update::none(),
+            },
+            Up => HistoryMsg::MoveUp.into(),
+            Down => HistoryMsg::MoveDown.into(),
+            Backspace => HistoryMsg::Pop.into(),
+            Char(c) => HistoryMsg::Push(c).into(),
+            _ => Update::none(),
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.open
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) {
+        if !self.open {
+            return;
+        }
+        let width = area.width.saturating_mul(4) / 5;
+        let height = area.height.saturating_mul(4) / 5;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+
+        // Query, matching statements, and the selected one in full
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Percentage(35)])
+            .split(popup);
+
+        let input = Paragraph::new(format!("{}_", self.query)).block(
+            Block::default()
+                .title("Query history (Enter: load into the editor, Esc: close)")
+                .borders(Borders::ALL)
+                .border_style(self.theme.border(true)),
+        );
+        f.render_widget(input, chunks[0]);
+
+        let items: Vec<ListItem> = if self.matches.is_empty() {
+            vec![ListItem::new("(no matching statements)")]
+        } else {
+            self.matches
+                .iter()
+                .map(|&i| {
+                    let e = &self.entries[i];
+                    // One line per statement
+                    let sql = e.sql.split_whitespace().collect::<Vec<_>>().join(" ");
+                    let origin = format!("{}  {}  ", e.time, e.connection);
+                    ListItem::new(Line::from(vec![
+                        Span::styled(origin, Style::default().fg(self.theme.null_cell)),
+                        Span::raw(sql),
+                    ]))
+                })
+                .collect()
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!("{} / {}", self.matches.len(), self.entries.len()))
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border(true)),
+            )
+            .highlight_style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default();
+        state.select((!self.matches.is_empty()).then_some(self.selected));
+        f.render_stateful_widget(list, chunks[1], &mut state);
+
+        let preview = Paragraph::new(self.selected_entry().map(|e| e.sql.as_str()).unwrap_or_default())
+            .block(Block::default().borders(Borders::ALL).border_style(self.theme.border(true)));
+        f.render_widget(preview, chunks[2]);
+    }
+}
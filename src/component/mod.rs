@@ -1,19 +1,29 @@
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{Frame, layout::Rect};
 
 use crate::update::Update;
 
+mod confirm;
 mod connection;
 mod dashboard;
 mod dblist;
+mod finder;
+mod history;
+mod queries;
 mod root;
 mod table;
+mod value_search;
 
+pub use confirm::{ConfirmComponent, ConfirmMsg, ConfirmRequest};
 pub use connection::{ConnectionComponent, ConnectionMsg};
-pub use dashboard::{DashboardComponent, DashboardMsg};
-pub use dblist::{Child, DBListComponent, DBListMsg, Database, Schema, Table};
+pub use dashboard::{DashboardComponent, DashboardMsg, PendingTable};
+pub use dblist::{Child, DBListComponent, DBListMsg, Database, Schema, Table, TableKind};
+pub use finder::{FinderComponent, FinderEntry, FinderMsg};
+pub use history::{HistoryComponent, HistoryMsg};
+pub use queries::{QueriesComponent, QueriesMsg};
 pub use root::{RootComponent, RootMsg};
-pub use table::{TableComponent, TableMsg};
+pub use table::{TableComponent, TableInfo, TableMsg};
+pub use value_search::{ValueSearchComponent, ValueSearchMsg};
 
 pub trait Component {
     type Msg;
@@ -26,4 +36,65 @@ pub trait Component {
 
     /// Draw is side-effectful but only touches the frame.
     fn draw(&self, f: &mut Frame, area: Rect, focused: bool);
+
+    /// Handle a mouse event; its coordinates are on screen, so components compare them
+    /// with the areas they were last drawn in.
+    fn handle_mouse(&self, _event: MouseEvent) -> Update<Self::Msg> {
+        Update::none()
+    }
+
+    /// Keys `handle_key` currently acts on, as (keys, action) pairs for the help overlay.
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        Vec::new()
+    }
+
+    /// Whether keys currently go into a text input, so global keys such as `?` pass through.
+    fn is_typing(&self) -> bool {
+        false
+    }
+}
+
+/// Whether a mouse event happened inside `area`
+fn in_area(area: Rect, event: &MouseEvent) -> bool {
+    (area.left()..area.right()).contains(&event.column) && (area.top()..area.bottom()).contains(&event.row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::keymap::Keymap;
+    use crate::settings::{TreeEnterAction, TreeIcons};
+    use crate::state::State;
+    use crate::theme::Theme;
+
+    /// Draw `component` through the trait object and ask for its help entries
+    fn exercise<M>(component: Box<dyn Component<Msg = M>>) {
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|f| component.draw(f, f.size(), true)).unwrap();
+        let _ = component.keybindings();
+        let _ = component.is_typing();
+    }
+
+    #[test]
+    fn every_component_is_usable_as_dyn_component() {
+        let (theme, keymap) = (Theme::default(), Keymap::default());
+        exercise(Box::new(RootComponent::new()));
+        exercise(Box::new(ConnectionComponent::new(Vec::new(), None, TreeIcons::default(), theme, keymap.clone())));
+        exercise(Box::new(DashboardComponent::new(&Config::default(), &State::default(), theme, keymap.clone())));
+        exercise(Box::new(DBListComponent::new(
+            TreeEnterAction::default(),
+            false,
+            TreeIcons::default(),
+            false,
+            theme,
+            keymap.clone(),
+        )));
+        exercise(Box::new(TableComponent::new(1, 3, 0, theme, keymap)));
+        exercise(Box::new(FinderComponent::new(theme)));
+        exercise(Box::new(ValueSearchComponent::new(theme)));
+        exercise(Box::new(HistoryComponent::new(theme)));
+        exercise(Box::new(QueriesComponent::new(theme)));
+        exercise(Box::new(ConfirmComponent::default()));
+    }
 }
@@ -0,0 +1,173 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use super::{Component, finder::fuzzy_score};
+use crate::queries::SavedQuery;
+use crate::theme::Theme;
+use crate::update::Update;
+
+pub enum QueriesMsg {
+    Close,
+    Push(char),
+    Pop,
+    MoveUp,
+    MoveDown,
+    /// Load the chosen query into the editor; handled by the parent
+    Confirm(SavedQuery),
+}
+
+/// Popup picking a saved query by (fuzzy) name, with its text shown below
+#[derive(Default)]
+pub struct QueriesComponent {
+    open: bool,
+    queries: Vec<SavedQuery>,
+    query: String,
+    /// Indices into `queries`, best match first
+    matches: Vec<usize>,
+    selected: usize,
+    theme: Theme,
+}
+
+impl QueriesComponent {
+    pub fn new(theme: Theme) -> Self {
+        Self { theme, ..Self::default() }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self, queries: Vec<SavedQuery>) {
+        self.open = true;
+        self.queries = queries;
+        self.query.clear();
+        self.refresh();
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.queries.clear();
+        self.matches.clear();
+    }
+
+    fn refresh(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .queries
+            .iter()
+            .enumerate()
+            .filter_map(|(i, q)| fuzzy_score(&self.query, &q.name).map(|s| (s, i)))
+            .collect();
+        // Stable: equal scores keep name order
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    fn selected_query(&self) -> Option<&SavedQuery> {
+        self.matches.get(self.selected).map(|&i| &self.queries[i])
+    }
+}
+
+impl Component for QueriesComponent {
+    type Msg = QueriesMsg;
+
+    fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
+        match msg {
+            QueriesMsg::Close => self.close(),
+            QueriesMsg::Push(c) => {
+                self.query.push(c);
+                self.refresh();
+            }
+            QueriesMsg::Pop => {
+                self.query.pop();
+                self.refresh();
+            }
+            QueriesMsg::MoveUp => self.selected = self.selected.saturating_sub(1),
+            QueriesMsg::MoveDown => {
+                if self.selected + 1 < self.matches.len() {
+                    self.selected += 1;
+                }
+            }
+            QueriesMsg::Confirm(_) => {} // Handled by parent
+        }
+        Update::none()
+    }
+
+    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        use crossterm::event::KeyCode::*;
+
+        match key.code {
+            Esc => QueriesMsg::Close.into(),
+            Enter => match self.selected_query() {
+                Some(query) => QueriesMsg::Confirm(query.clone()).into(),
+                None => Update::none(),
+            },
+            Up => QueriesMsg::MoveUp.into(),
+            Down => QueriesMsg::MoveDown.into(),
+            Backspace => QueriesMsg::Pop.into(),
+            Char(c) => QueriesMsg::Push(c).into(),
+            _ => Update::none(),
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.open
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) {
+        if !self.open {
+            return;
+        }
+        let width = area.width.saturating_mul(4) / 5;
+        let height = area.height.saturating_mul(4) / 5;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Percentage(45)])
+            .split(popup);
+
+        let input = Paragraph::new(format!("{}_", self.query)).block(
+            Block::default()
+                .title("Saved queries (Enter: load into the editor, Esc: close)")
+                .borders(Borders::ALL)
+                .border_style(self.theme.border(true)),
+        );
+        f.render_widget(input, chunks[0]);
+
+        let items: Vec<ListItem> = if self.queries.is_empty() {
+            vec![ListItem::new("(no saved queries yet)")]
+        } else if self.matches.is_empty() {
+            vec![ListItem::new("(no matching queries)")]
+        } else {
+            self.matches.iter().map(|&i| ListItem::new(self.queries[i].name.as_str())).collect()
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!("{} / {}", self.matches.len(), self.queries.len()))
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border(true)),
+            )
+            .highlight_style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default();
+        state.select((!self.matches.is_empty()).then_some(self.selected));
+        f.render_stateful_widget(list, chunks[1], &mut state);
+
+        let preview = Paragraph::new(self.selected_query().map(|q| q.sql.as_str()).unwrap_or_default())
+            .block(Block::default().borders(Borders::ALL).border_style(self.theme.border(true)));
+        f.render_widget(preview, chunks[2]);
+    }
+}
@@ -1,15 +1,36 @@
-use anyhow::Result;
 use crate::update::{MapMsg, Update};
 use crate::component::{
-    Component, ConnectionComponent, ConnectionMsg, DashboardComponent, DashboardMsg,
+    Component, ConfirmComponent, ConfirmMsg, ConfirmRequest, ConnectionComponent, ConnectionMsg,
+    DashboardComponent, DashboardMsg, TableMsg,
 };
+use crate::config::Config;
 use crate::connection::Connection;
-use crossterm::event::KeyEvent;
-use ratatui::{Frame, layout::Rect};
+use crate::keymap::{Action, Keymap};
+use crate::logger::error;
+use crate::state::State;
+use crate::status::{StatusLevel, StatusMessage};
+use crate::theme::Theme;
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
 
 pub enum RootMsg {
     ConnectionSelected(Connection),
     LeaveDashboard,
+    /// Show or hide the keybinding overlay
+    ToggleHelp,
+    /// Sent by the app on a timer to drive animations
+    Tick,
+    /// Replace the status bar's message
+    Status(StatusMessage),
+    /// Ask before sending a message on to a component
+    Confirm(ConfirmRequest),
+    ConfirmMsg(ConfirmMsg),
     Connection(ConnectionMsg),
     Dashboard(DashboardMsg),
 }
@@ -17,7 +38,9 @@ pub enum RootMsg {
 impl From<ConnectionMsg> for RootMsg {
     fn from(msg: ConnectionMsg) -> Self {
         match msg {
-            ConnectionMsg::ConnectionSelected(conn) => RootMsg::ConnectionSelected(conn),
+            ConnectionMsg::ConnectionSelected(conn) => RootMsg::ConnectionSelected(*conn),
+            ConnectionMsg::Status(status) => RootMsg::Status(status),
+            ConnectionMsg::Confirm(request) => RootMsg::Confirm(request),
             m => RootMsg::Connection(m),
         }
     }
@@ -26,10 +49,22 @@ impl From<DashboardMsg> for RootMsg {
     fn from(msg: DashboardMsg) -> Self {
         match msg {
             DashboardMsg::Leave => RootMsg::LeaveDashboard,
+            DashboardMsg::Status(status) => RootMsg::Status(status),
+            DashboardMsg::Confirm(request) => RootMsg::Confirm(request),
             m => RootMsg::Dashboard(m),
         }
     }
 }
+impl From<TableMsg> for RootMsg {
+    fn from(msg: TableMsg) -> Self {
+        RootMsg::from(DashboardMsg::from(msg))
+    }
+}
+impl From<ConfirmMsg> for RootMsg {
+    fn from(msg: ConfirmMsg) -> Self {
+        RootMsg::ConfirmMsg(msg)
+    }
+}
 
 enum Focus {
     Connection,
@@ -40,15 +75,43 @@ pub struct RootComponent {
     focus: Focus,
     connection: ConnectionComponent,
     dashboard: DashboardComponent,
+    confirm: ConfirmComponent,
+    show_help: bool,
+    /// Latest message for the status bar; hidden once expired
+    status: Option<StatusMessage>,
+    theme: Theme,
+    keymap: Keymap,
 }
 
 impl RootComponent {
-    pub fn new() -> Result<Self> {
-        Ok(Self {
+    /// A config that can't be read leaves the connection list empty, with the
+    /// error shown in its place, rather than stopping the app
+    pub fn new() -> Self {
+        let (config, load_error) = match Config::new() {
+            Ok(config) => (config, None),
+            Err(e) => {
+                error(&format!("Config: loading failed: {:#}", e));
+                (Config::default(), Some(format!("{:#}", e)))
+            }
+        };
+        let theme = config.theme.unwrap_or_default();
+        let keymap = Keymap::new(&config.keys);
+        Self {
             focus: Focus::Connection,
-            connection: ConnectionComponent::new()?,
-            dashboard: DashboardComponent::new(),
-        })
+            dashboard: DashboardComponent::new(&config, &State::load(), theme, keymap.clone()),
+            connection: ConnectionComponent::new(
+                config.conn,
+                load_error,
+                config.tree_icons.unwrap_or_default(),
+                theme,
+                keymap.clone(),
+            ),
+            confirm: ConfirmComponent::default(),
+            show_help: false,
+            status: None,
+            theme,
+            keymap,
+        }
     }
     fn move_to_dashboard(&mut self, conn: Connection) -> Update<RootMsg> {
         // Store selected connection and trigger DBList load immediately
@@ -61,6 +124,76 @@ impl RootComponent {
         self.focus = Focus::Connection;
         Update::none()
     }
+
+    /// One-line bar with the latest message, or a hint once it has expired
+    fn draw_status_bar(&self, f: &mut Frame, area: Rect) {
+        let line = match self.status.as_ref().filter(|s| !s.is_expired()) {
+            Some(status) => {
+                let style = match status.level {
+                    StatusLevel::Info => Style::default().fg(Color::Green),
+                    StatusLevel::Error => Style::default().fg(Color::Red),
+                };
+                Line::from(vec![
+                    Span::styled(format!(" {} ", status.time.format("%H:%M:%S")), Style::default().fg(Color::DarkGray)),
+                    // Errors from the drivers can span several lines
+                    Span::styled(status.text.replace('\n', " "), style),
+                ])
+            }
+            None => Line::from(Span::styled(
+                format!(" {}: help", self.keymap.describe(Action::Help)),
+                Style::default().fg(Color::DarkGray),
+            )),
+        };
+        f.render_widget(Paragraph::new(line), area);
+    }
+
+    /// Keys of the focused component in a centered popup, split into columns
+    /// when they don't fit its height
+    fn draw_help(&self, f: &mut Frame, area: Rect) {
+        let mut keys = match self.focus {
+            Focus::Connection => self.connection.keybindings(),
+            Focus::Dashboard => self.dashboard.keybindings(),
+        };
+        keys.extend([(self.keymap.describe(Action::Help), "Close this help"), ("Ctrl+C".to_string(), "Quit")]);
+
+        let width = area.width.saturating_mul(4) / 5;
+        let rows = u16::try_from(keys.len()).unwrap_or(u16::MAX);
+        let height = rows.saturating_add(2).min(area.height.saturating_mul(4) / 5);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+        let block = Block::default()
+            .title(format!("Keys ({} / Esc: close)", self.keymap.describe(Action::Help)))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border(true));
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+
+        let per_column = usize::from(inner.height).max(1);
+        let columns: Vec<_> = keys.chunks(per_column).collect();
+        let areas = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, columns.len() as u32); columns.len()])
+            .split(inner);
+        let key_style = Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD);
+        for (column, area) in columns.iter().zip(areas.iter()) {
+            let key_width = column.iter().map(|(key, _)| key.chars().count()).max().unwrap_or(0);
+            let lines: Vec<Line> = column
+                .iter()
+                .map(|(key, action)| {
+                    Line::from(vec![
+                        Span::styled(format!(" {:<width$}  ", key, width = key_width), key_style),
+                        Span::raw(*action),
+                    ])
+                })
+                .collect();
+            f.render_widget(Paragraph::new(lines), *area);
+        }
+    }
 }
 
 impl Component for RootComponent {
@@ -70,22 +203,79 @@ impl Component for RootComponent {
         match msg {
             RootMsg::ConnectionSelected(conn) => self.move_to_dashboard(conn),
             RootMsg::LeaveDashboard => self.move_to_connection(),
+            RootMsg::ToggleHelp => {
+                self.show_help = !self.show_help;
+                Update::none()
+            }
+            RootMsg::Status(status) => {
+                self.status = Some(status);
+                Update::none()
+            }
+            RootMsg::Tick => self.dashboard.update(DashboardMsg::TableMsg(TableMsg::Tick)).map_auto(),
+            RootMsg::Confirm(request) => {
+                self.confirm.open(request);
+                Update::none()
+            }
+            RootMsg::ConfirmMsg(ConfirmMsg::Accept) => match self.confirm.accept() {
+                Some(msg) => msg.into(),
+                None => Update::none(),
+            },
+            RootMsg::ConfirmMsg(m) => self.confirm.update(m).map_auto(),
             RootMsg::Connection(m) => self.connection.update(m).map_auto(),
             RootMsg::Dashboard(m) => self.dashboard.update(m).map_auto(),
         }
     }
 
     fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        // A pending confirmation takes every key until answered
+        if self.confirm.is_open() {
+            return self.confirm.handle_key(key).map_auto();
+        }
+        // The help overlay is modal
+        if self.show_help {
+            return if self.keymap.is(Action::Help, &key) || key.code == crossterm::event::KeyCode::Esc {
+                RootMsg::ToggleHelp.into()
+            } else {
+                Update::none()
+            };
+        }
+        let typing = match self.focus {
+            Focus::Connection => self.connection.is_typing(),
+            Focus::Dashboard => self.dashboard.is_typing(),
+        };
+        if self.keymap.is(Action::Help, &key) && !typing {
+            return RootMsg::ToggleHelp.into();
+        }
         match self.focus {
             Focus::Connection => self.connection.handle_key(key).map_auto(),
             Focus::Dashboard => self.dashboard.handle_key(key).map_auto(),
         }
     }
 
+    fn handle_mouse(&self, event: MouseEvent) -> Update<Self::Msg> {
+        // Overlays are answered from the keyboard; the panes under them ignore the mouse
+        if self.confirm.is_open() || self.show_help {
+            return Update::none();
+        }
+        match self.focus {
+            Focus::Connection => Update::none(),
+            Focus::Dashboard => self.dashboard.handle_mouse(event).map_auto(),
+        }
+    }
+
     fn draw(&self, f: &mut Frame, area: Rect, focused: bool) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(area);
         match self.focus {
-            Focus::Connection => self.connection.draw(f, area, focused),
-            Focus::Dashboard => self.dashboard.draw(f, area, focused),
+            Focus::Connection => self.connection.draw(f, chunks[0], focused),
+            Focus::Dashboard => self.dashboard.draw(f, chunks[0], focused),
+        }
+        self.draw_status_bar(f, chunks[1]);
+        if self.show_help {
+            self.draw_help(f, area);
         }
+        self.confirm.draw(f, area, focused);
     }
 }
@@ -1,46 +1,245 @@
-use crossterm::event::KeyEvent;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crossterm::event::{KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Paragraph, Tabs},
+    text::{Line, Span, Text},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Clear, Paragraph, Tabs, Wrap,
+    },
 };
 
-use super::Component;
+use super::{
+    Component, ConfirmRequest, FinderEntry, HistoryComponent, HistoryMsg, QueriesComponent, QueriesMsg, TableKind,
+};
 use crate::app::AppMsg;
-use crate::connection::Connection;
-use crate::db::{DB, DBBehavior, Records, TableProperties};
-use crate::logger::{debug, error};
-use crate::update::{Command, Update};
+use crate::connection::{Connection, DEFAULT_FETCH_LIMIT};
+use crate::db::{export, ChunkSink, CliTarget, ColumnInfo, DB, DBBehavior, ForeignKeyInfo, IndexInfo, RecordQuery, Records, RowCount, ServerInfo, TableProperties};
+use crate::history::{self, HistoryEntry};
+use crate::keymap::{Action, Keymap};
+use crate::logger::{debug, error, warn};
+use crate::queries::{self, SavedQuery};
+use crate::status::StatusMessage;
+use crate::theme::Theme;
+use crate::update::{Command, MapMsg, Update};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TableInfo {
     pub database: String,
+    /// Schema of the table on backends that have them
+    pub schema: Option<String>,
     pub table: String,
 }
 
+impl TableInfo {
+    /// `database.schema.table`, or `database.table` without a schema
+    pub fn label(&self) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}.{}", self.database, schema, self.table),
+            None => format!("{}.{}", self.database, self.table),
+        }
+    }
+}
+
+/// Filter and search of a table's Records, restored when it's opened again
+#[derive(Default)]
+struct RecordsView {
+    filter: Option<String>,
+    search: Option<String>,
+}
+
+/// Properties and CREATE statement of a table, reused when it's opened again
+struct CachedProperties {
+    properties: Option<TableProperties>,
+    ddl: Option<String>,
+}
+
 pub enum TableMsg {
+    /// Advance the loading spinner; sent by the app on a timer
+    Tick,
     FocusRecords,
     FocusSQL,
     FocusProperties,
     BackToDBList,
     LoadRecords(Connection),
-    RecordsLoaded(Records),
-    RecordsLoadFailed(String),
+    /// Results carry the generation of the fetch that produced them and are
+    /// dropped when a newer fetch has started since
+    RecordsLoaded(u64, Records),
+    /// Rows of a fetch still reading; `true` on the first chunk of a read,
+    /// which replaces the grid. `RecordsLoaded` then brings the rest.
+    RecordsChunk(u64, bool, Records),
+    RecordsLoadFailed(u64, String),
+    /// Fetch the properties unless they are loaded (or were cached) already
     LoadProperties(Connection),
-    PropertiesLoaded(TableProperties),
-    PropertiesLoadFailed(String),
+    PropertiesLoaded(u64, TableProperties),
+    PropertiesLoadFailed(u64, String),
+    /// Replace the (possibly estimated) row count with an exact COUNT(*)
+    CountRowsExact(Connection),
+    RowCountLoaded(RowCount),
+    RowCountLoadFailed(String),
     LaunchSQLCli(Connection),
+    /// Type into the SQL tab's editor
+    EditSql,
+    StopEditSql,
+    SqlPush(char),
+    SqlNewline,
+    SqlPop,
+    /// Run a statement on the current connection; the result replaces the Records grid
+    RunQuery(String),
+    /// Read the query history and open the browser over it
+    OpenHistory,
+    HistoryLoaded(Vec<HistoryEntry>),
+    HistoryLoadFailed(String),
+    History(HistoryMsg),
+    /// Put a statement picked from the history into the editor
+    LoadFromHistory(String),
+    /// Ask for a name to save the editor's statement under
+    StartSaveQuery,
+    SaveNamePush(char),
+    SaveNamePop,
+    CancelSaveQuery,
+    SaveQuery(String),
+    QuerySaved(String, PathBuf),
+    QuerySaveFailed(String),
+    /// Read the saved queries and open the picker over them
+    OpenQueries,
+    QueriesLoaded(Vec<SavedQuery>),
+    QueriesLoadFailed(String),
+    Queries(QueriesMsg),
+    /// Put a saved query into the editor
+    LoadQuery(SavedQuery),
+    /// Show the execution plan of the editor's statement in a popup; `true`
+    /// runs it for actual timings (EXPLAIN ANALYZE)
+    Explain(bool),
+    PlanLoaded(u64, Vec<String>),
+    PlanFailed(u64, String),
+    ScrollPlanBy(i32),
+    ScrollPlanColsBy(i32),
+    ClosePlan,
+    QueryResult(u64, Records),
     // Scrolling controls for Records view
     ScrollRecordsBy(i32),
+    /// Scroll by half the visible rows, down for a positive direction
+    ScrollRecordsHalfPage(i32),
     ScrollTop,
     ScrollBottom,
+    /// Type a row number, then scroll that row to the top
+    StartGoto,
+    GotoPush(char),
+    GotoPop,
+    ConfirmGoto,
+    CancelGoto,
+    /// Fetch the page of rows after / before the loaded one
+    NextPage(Connection),
+    PrevPage(Connection),
+    /// Edit the WHERE predicate applied to the Records query
+    EditWhere,
+    WherePush(char),
+    WherePop,
+    /// Apply the edited predicate (clearing it when empty) and reload from the first page
+    ApplyWhere(Connection),
+    CancelWhere,
+    /// Find text in the loaded records (client-side, case-insensitive)
+    StartSearch,
+    SearchPush(char),
+    SearchPop,
+    /// Keep the typed search (clearing it when empty) and jump to the first match
+    ConfirmSearch,
+    CancelSearch,
+    /// Move the current cell to the next / previous match, wrapping around
+    NextMatch,
+    PrevMatch,
     // Horizontal column paging for Records view
     ScrollColsBy(i32),
     ColsStart,
     ColsEnd,
+    /// Cycle the Records footer between off, sum and avg
+    CycleAggregate,
+    /// Switch the grid between formatted and raw values
+    ToggleRawValues,
+    /// Switch the Records search between case-insensitive and exact matching
+    ToggleSearchCase,
+    /// Switch the grid between content-fitted and fixed column widths
+    ToggleFitColumns,
+    /// Switch the Records grid between one line per row and wrapped cells
+    ToggleWrap,
+    /// Bookmark (or un-bookmark) the current Records row
+    ToggleBookmark,
+    /// Scroll to the next / previous bookmarked row, wrapping around
+    NextBookmark,
+    PrevBookmark,
+    /// Write the loaded records to a CSV file in the working directory
+    ExportCsv,
+    /// Write the loaded records to a JSON file in the working directory
+    ExportJson,
+    Exported(PathBuf),
+    ExportFailed(String),
+    /// Edit the current cell; refused (with the reason shown) without a primary key
+    StartEdit,
+    EditPush(char),
+    EditPop,
+    /// Set the edited cell to SQL NULL instead of text; typing replaces it
+    EditSetNull,
+    /// Write the edited value with an UPDATE, then re-fetch the page
+    CommitEdit(Connection),
+    CancelEdit,
+    /// Ask to delete the current row; refused (with the reason shown) without a primary key
+    DeleteRow,
+    /// Delete the row with these primary-key values, once confirmed
+    ConfirmDelete(Connection, Vec<(String, String)>),
+    /// Open the insert form, one input per column
+    StartInsert,
+    InsertPush(char),
+    InsertPop,
+    /// Move between the form's fields
+    InsertFieldBy(i32),
+    /// Check the required columns are set, then INSERT and re-fetch the page
+    InsertRow(Connection),
+    CancelInsert,
+    /// The page re-fetched after an edit or delete, with a note on what changed
+    RowsWritten(u64, Records, String),
+    WriteFailed(String),
+    /// Show the full value of the current cell in a popup
+    InspectCell,
+    CloseCellPopup,
+    ScrollCellPopupBy(i32),
+    /// Switch the Properties tab between columns and indexes, loading the
+    /// indexes the first time
+    ToggleIndexes(Connection),
+    IndexesLoaded(Vec<IndexInfo>),
+    IndexesLoadFailed(String),
+    /// Switch the Properties tab between columns and foreign keys, loading
+    /// the foreign keys the first time
+    ToggleForeignKeys(Connection),
+    ForeignKeysLoaded(Vec<ForeignKeyInfo>),
+    ForeignKeysLoadFailed(String),
+    /// Switch the Properties tab between columns and the table's CREATE
+    /// statement, loading it the first time
+    ToggleDdl(Connection),
+    DdlLoaded(String),
+    DdlLoadFailed(String),
+    /// Put the shown CREATE statement on the clipboard
+    CopyDdl,
+    /// Put the loaded records on the clipboard as a Markdown table
+    CopyMarkdown,
+    /// Open the table referenced by the highlighted foreign key
+    FollowForeignKey,
+    /// Reveal a table in the tree and open it; handled by the dashboard
+    JumpToTable(FinderEntry),
+    /// Show a message in the status bar; handled by the root
+    Status(StatusMessage),
+    /// Ask before a destructive action; handled by the root
+    Confirm(ConfirmRequest),
     // Scrolling controls for Properties view
     ScrollPropsBy(i32),
+    ScrollPropsHalfPage(i32),
     ScrollPropsTop,
     ScrollPropsBottom,
     // Horizontal for Properties
@@ -49,6 +248,362 @@ pub enum TableMsg {
     PropsColsEnd,
 }
 
+impl From<QueriesMsg> for TableMsg {
+    fn from(msg: QueriesMsg) -> Self {
+        match msg {
+            QueriesMsg::Confirm(query) => TableMsg::LoadQuery(query),
+            m => TableMsg::Queries(m),
+        }
+    }
+}
+
+impl From<HistoryMsg> for TableMsg {
+    fn from(msg: HistoryMsg) -> Self {
+        match msg {
+            HistoryMsg::Confirm(sql) => TableMsg::LoadFromHistory(sql),
+            m => TableMsg::History(m),
+        }
+    }
+}
+
+/// Client-side aggregate shown in the Records footer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Off,
+    Sum,
+    Avg,
+}
+
+impl Aggregate {
+    fn next(self) -> Self {
+        match self {
+            Aggregate::Off => Aggregate::Sum,
+            Aggregate::Sum => Aggregate::Avg,
+            Aggregate::Avg => Aggregate::Off,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Aggregate::Off => "",
+            Aggregate::Sum => "sum",
+            Aggregate::Avg => "avg",
+        }
+    }
+}
+
+/// Aggregate each column over the loaded rows. A column counts as numeric when it has
+/// at least one non-empty value and every non-empty value parses as a finite
+/// number (text such as `inf` or `NaN` doesn't); other columns yield `None`.
+pub fn aggregate_columns(recs: &Records, agg: Aggregate) -> Vec<Option<f64>> {
+    (0..recs.columns.len())
+        .map(|col| {
+            let mut sum = 0.0;
+            let mut n = 0usize;
+            for row in &recs.rows {
+                let v = row.get(col).map(|v| v.trim()).unwrap_or("");
+                if v.is_empty() {
+                    continue;
+                }
+                sum += v.parse::<f64>().ok().filter(|x| x.is_finite())?;
+                n += 1;
+            }
+            match (agg, n) {
+                (_, 0) | (Aggregate::Off, _) => None,
+                (Aggregate::Sum, _) => Some(sum),
+                (Aggregate::Avg, _) => Some(sum / n as f64),
+            }
+        })
+        .collect()
+}
+
+/// Whether the Properties grid shows the Charset and Collation columns. Only
+/// MySQL reports them, so other backends get no empty columns.
+fn has_charset(props: &TableProperties) -> bool {
+    props.columns.iter().any(|c| c.charset.is_some() || c.collation.is_some())
+}
+
+/// Records column width when not fitted to the content
+const FIXED_COLUMN_WIDTH: u16 = 16;
+/// Bounds of a fitted records column
+const MIN_FITTED_WIDTH: u16 = 3;
+const MAX_FITTED_WIDTH: u16 = 40;
+/// Rows moved per scroll-wheel notch
+const MOUSE_SCROLL_ROWS: i32 = 3;
+/// Frames of the spinner shown while records or properties load
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Columns shown from `scroll` on: as many as fit in `avail` cells (plus one
+/// cell of spacing each), and at least one. Near the end the window starts
+/// earlier so it stays full, like the rows do.
+fn column_window(widths: &[u16], scroll: usize, avail: u16) -> (usize, usize) {
+    let fits = |cols: &[u16]| cols.iter().map(|&w| usize::from(w) + 1).sum::<usize>() <= usize::from(avail);
+    let mut max_start = widths.len().saturating_sub(1);
+    while max_start > 0 && fits(&widths[max_start - 1..]) {
+        max_start -= 1;
+    }
+    let start = scroll.min(max_start);
+    let mut end = (start + 1).min(widths.len());
+    while end < widths.len() && fits(&widths[start..=end]) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Rows shown from `scroll` on when row `i` takes `heights[i]` of the `avail`
+/// lines: as many as fit, and at least one. Like `column_window`, the window
+/// starts earlier near the end so it stays full.
+fn line_window(heights: &[u16], scroll: usize, avail: u16) -> (usize, usize) {
+    let fits = |rows: &[u16]| rows.iter().map(|&h| usize::from(h)).sum::<usize>() <= usize::from(avail);
+    let mut max_start = heights.len().saturating_sub(1);
+    while max_start > 0 && fits(&heights[max_start - 1..]) {
+        max_start -= 1;
+    }
+    let start = scroll.min(max_start);
+    let mut end = (start + 1).min(heights.len());
+    while end < heights.len() && fits(&heights[start..=end]) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// Width of the Properties grid's Type column; longer types wrap
+const PROPS_TYPE_WIDTH: u16 = 14;
+
+/// `text` in lines of at most `width` characters, broken at spaces where it
+/// can be and inside words that are wider than that
+fn wrap_cell(text: &str, width: u16) -> Text<'static> {
+    let width = usize::from(width).max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let chars: Vec<char> = word.chars().collect();
+        for piece in chars.chunks(width) {
+            let len = line.chars().count();
+            if len > 0 && len + 1 + piece.len() > width {
+                lines.push(Line::from(std::mem::take(&mut line)));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(piece);
+        }
+    }
+    lines.push(Line::from(line));
+    Text::from(lines)
+}
+
+/// Lines a wrapped Records cell takes by default before it is cut
+pub const DEFAULT_WRAP_LINES: usize = 3;
+
+/// `text` wrapped like `wrap_cell` but kept to `max_lines` lines (at least
+/// one); a value cut short ends in `…`
+fn wrap_cell_capped(text: &str, width: u16, max_lines: usize) -> Text<'static> {
+    let mut wrapped = wrap_cell(text, width);
+    let max_lines = max_lines.max(1);
+    if wrapped.lines.len() > max_lines {
+        wrapped.lines.truncate(max_lines);
+        if let Some(last) = wrapped.lines.last_mut() {
+            let mut line: String = last.spans.iter().map(|s| s.content.as_ref()).collect();
+            if line.chars().count() >= usize::from(width.max(1)) {
+                line.pop();
+            }
+            line.push('…');
+            *last = Line::from(line);
+        }
+    }
+    wrapped
+}
+
+/// A right-aligned one-line cell, for the yes/no indicator columns
+fn indicator_cell(text: &str) -> Text<'static> {
+    Text::from(Line::from(text.to_string()).alignment(Alignment::Right))
+}
+
+/// How many leading columns stay in view: up to `frozen`, but never every
+/// column nor more than half of the `avail` cells
+fn frozen_count(frozen: usize, widths: &[u16], avail: u16) -> usize {
+    let mut n = frozen.min(widths.len().saturating_sub(1));
+    while n > 0 && widths[..n].iter().map(|&w| usize::from(w) + 1).sum::<usize>() > usize::from(avail) / 2 {
+        n -= 1;
+    }
+    n
+}
+
+/// Statements starting with these keywords only read
+const READ_KEYWORDS: [&str; 8] = ["select", "with", "show", "explain", "describe", "desc", "values", "table"];
+
+/// Whether running `sql` may change data: true when any of its `;`-separated
+/// statements starts with something other than a read-only keyword, after
+/// leading comments and parentheses
+fn may_write(sql: &str) -> bool {
+    sql.split(';').any(|statement| {
+        let mut rest = statement;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+            if let Some(comment) = rest.strip_prefix("--") {
+                rest = comment.split_once('\n').map_or("", |(_, r)| r);
+            } else if let Some(comment) = rest.strip_prefix("/*") {
+                rest = comment.split_once("*/").map_or("", |(_, r)| r);
+            } else {
+                break;
+            }
+        }
+        let keyword: String = rest.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        !keyword.is_empty() && !READ_KEYWORDS.contains(&keyword.to_lowercase().as_str())
+    })
+}
+
+/// `value` pretty-printed when it is a JSON object or array
+fn pretty_json(value: &str) -> Option<String> {
+    if !value.trim_start().starts_with(['{', '[']) {
+        return None;
+    }
+    let parsed: serde_json::Value = serde_json::from_str(value).ok()?;
+    serde_json::to_string_pretty(&parsed).ok()
+}
+
+/// Color pretty-printed JSON: keys, strings, numbers and `true`/`false`/`null`
+fn highlight_json(pretty: &str, key_color: Color) -> Vec<Line<'_>> {
+    pretty
+        .lines()
+        .map(|line| {
+            let mut spans = Vec::new();
+            let mut rest = line;
+            while let Some(c) = rest.chars().next() {
+                let len = match c {
+                    // Up to the closing quote, skipping escaped ones
+                    '"' => {
+                        let mut escaped = false;
+                        let close = rest[1..].char_indices().find(|&(_, ch)| {
+                            let end = ch == '"' && !escaped;
+                            escaped = ch == '\\' && !escaped;
+                            end
+                        });
+                        close.map_or(rest.len(), |(i, _)| i + 2)
+                    }
+                    '-' | '0'..='9' => rest
+                        .find(|ch: char| !(ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E')))
+                        .unwrap_or(rest.len()),
+                    'a'..='z' => rest.find(|ch: char| !ch.is_ascii_lowercase()).unwrap_or(rest.len()),
+                    ' ' => rest.find(|ch: char| ch != ' ').unwrap_or(rest.len()),
+                    _ => c.len_utf8(),
+                };
+                let (token, tail) = rest.split_at(len);
+                let color = match c {
+                    '"' if tail.starts_with(':') => Some(key_color),
+                    '"' => Some(Color::Green),
+                    '-' | '0'..='9' => Some(Color::Yellow),
+                    'a'..='z' => Some(Color::Magenta),
+                    _ => None,
+                };
+                spans.push(match color {
+                    Some(color) => Span::styled(token, Style::default().fg(color)),
+                    None => Span::raw(token),
+                });
+                rest = tail;
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// `xxd`-style dump: offset, 16 bytes in hex, then the printable ASCII
+fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_aggregate(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        format!("{:.0}", v)
+    } else {
+        format!("{:.2}", v)
+    }
+}
+
+/// A cell value being edited in the Records view
+struct CellEdit {
+    row: usize,
+    col: usize,
+    input: String,
+    /// Write NULL rather than `input`
+    null: bool,
+}
+
+/// Why column `column` can't be set to NULL, if its properties say so. Without
+/// loaded properties the database is left to decide.
+fn null_refusal(props: Option<&TableProperties>, column: &str) -> Option<String> {
+    let info = props?.columns.iter().find(|c| c.name == column)?;
+    (!info.nullable).then(|| format!("cannot set NULL: {} is NOT NULL", column))
+}
+
+/// One column of the insert form. `value` is `None` until typed into, and the
+/// column is then left out of the INSERT so the database applies its default.
+struct InsertField {
+    column: ColumnInfo,
+    value: Option<String>,
+    /// Generated by the database (serial / identity / auto-increment key)
+    auto: bool,
+}
+
+impl InsertField {
+    fn new(column: ColumnInfo, single_pk: bool) -> Self {
+        // A key with a default is generated (nextval, identity, ...); so is a
+        // lone integer key without one (SQLite rowid, MySQL AUTO_INCREMENT)
+        let auto = column.primary_key
+            && (column.default.is_some() || (single_pk && column.data_type.to_lowercase().contains("int")));
+        Self { column, value: None, auto }
+    }
+
+    /// Must be typed: NOT NULL, with no default and not generated
+    fn required(&self) -> bool {
+        !self.column.nullable && self.column.default.is_none() && !self.auto
+    }
+
+    fn hint(&self) -> String {
+        let c = &self.column;
+        let mut hint = c.data_type.clone();
+        if self.auto {
+            hint.push_str(", generated");
+        } else if let Some(d) = &c.default {
+            hint.push_str(&format!(", default {}", d));
+        } else if c.nullable {
+            hint.push_str(", nullable");
+        } else {
+            hint.push_str(", required");
+        }
+        hint
+    }
+}
+
+struct InsertForm {
+    fields: Vec<InsertField>,
+    selected: usize,
+    /// Why the last submit was refused
+    error: Option<String>,
+}
+
+/// What the Properties tab lists
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PropsView {
+    Columns,
+    Indexes,
+    ForeignKeys,
+    Ddl,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TableFocus {
     Records,
@@ -57,48 +612,1248 @@ pub enum TableFocus {
 }
 
 pub struct TableComponent {
+    theme: Theme,
+    keymap: Keymap,
+    /// Leading records columns that stay in view while scrolling sideways
+    frozen_columns: usize,
+    /// Most lines a cell takes in wrap mode
+    wrap_lines: usize,
     table_info: Option<TableInfo>,
+    /// Views open read-only
+    kind: TableKind,
     connection: Option<Connection>,
+    /// Version and latency of the connection's server, shown in the tab bar
+    server_info: Option<ServerInfo>,
     focus: TableFocus,
     records: Option<Records>,
     properties: Option<TableProperties>,
+    /// Why the properties couldn't be read; kept until a load succeeds
+    properties_error: Option<String>,
+    props_view: PropsView,
+    /// Indexes of the table, or why they couldn't be read
+    indexes: Option<Result<Vec<IndexInfo>, String>>,
+    /// Foreign keys of the table, or why they couldn't be read
+    foreign_keys: Option<Result<Vec<ForeignKeyInfo>, String>>,
+    /// CREATE statement of the table, or why it couldn't be read
+    ddl: Option<Result<String, String>>,
+    row_count: Option<RowCount>,
+    aggregate: Aggregate,
+    /// Show values exactly as the database returned them, without the
+    /// per-type formatting and alignment
+    raw_values: bool,
+    /// Size records columns to their content instead of a fixed width
+    fit_columns: bool,
+    /// Wrap long records values over several lines instead of cutting them
+    wrap: bool,
+    /// Bookmarked rows per table, identified by primary-key values
+    bookmarks: HashMap<TableInfo, HashSet<Vec<String>>>,
+    /// Table offset of the first loaded row
+    page_offset: usize,
+    /// Offsets of the pages before the current one, newest last. Pages cut
+    /// short by the memory cap vary in length, so PrevPage returns to these.
+    page_starts: Vec<usize>,
+    /// Rows fetched per Records page, from the connection's `fetch_limit`
+    page_size: usize,
+    /// WHERE predicate of the Records query
+    filter: Option<String>,
+    /// Predicate being typed; `None` when not editing
+    where_input: Option<String>,
+    /// Why the last records fetch failed
+    records_error: Option<String>,
+    /// Active search over the loaded records, as typed
+    search: Option<String>,
+    /// Match the search's case exactly instead of ignoring it
+    search_case_sensitive: bool,
+    /// Filter and search each table was left with this session
+    records_views: HashMap<TableInfo, RecordsView>,
+    /// Properties and DDL fetched this session, until the tree is refreshed
+    /// or another connection is opened
+    properties_cache: HashMap<TableInfo, CachedProperties>,
+    /// Search being typed; `None` when not editing
+    search_input: Option<String>,
+    /// Row number being typed; `None` when not editing
+    goto_input: Option<String>,
+    /// Cell value being edited
+    edit: Option<CellEdit>,
+    /// New row being filled in
+    insert_form: Option<InsertForm>,
+    /// Outcome of the last export or edit, shown under the Records view
+    records_status: Option<String>,
+    /// Full value of the current cell shown over the Records view
+    show_cell_popup: bool,
+    cell_popup_scroll: usize,
+    /// Statement in the SQL tab's editor
+    sql_input: String,
+    sql_editing: bool,
+    /// Past statements, opened over the SQL tab
+    history: HistoryComponent,
+    /// Statements kept in the history file
+    history_size: usize,
+    /// Saved queries, opened over the SQL tab
+    saved_queries: QueriesComponent,
+    /// Name the editor's statement was last loaded or saved under
+    query_name: Option<String>,
+    /// Name being typed for saving the statement
+    save_input: Option<String>,
+    /// Execution plan popup over the table view
+    show_plan: bool,
+    /// The plan's lines, or why it couldn't be produced; `None` while it loads
+    plan: Option<Result<Vec<String>, String>>,
+    plan_analyze: bool,
+    plan_scroll: usize,
+    plan_col_scroll: usize,
+    /// Bumped by every EXPLAIN, so a plan arriving after the popup was closed
+    /// or replaced is dropped
+    plan_generation: u64,
+    /// The Records grid holds a query result rather than a page of the table
+    query_result: bool,
     records_scroll: usize,
     records_col_scroll: usize,
     properties_scroll: usize,
     properties_col_scroll: usize,
+    /// Rows the records / properties grid showed when last drawn, for half-page scrolling
+    records_visible: Cell<usize>,
+    properties_visible: Cell<usize>,
+    /// Bumped by every fetch that replaces the records / properties, so a
+    /// result arriving after the user moved on is recognised as stale
+    records_generation: u64,
+    properties_generation: u64,
+    /// A fetch replacing the records / properties is in flight
+    records_loading: bool,
+    /// The fetch in flight has sent chunks, so its final rows are appended
+    records_streamed: bool,
+    properties_loading: bool,
+    spinner_frame: usize,
 }
 
 impl TableComponent {
-    pub fn new() -> Self {
+    pub fn new(frozen_columns: usize, wrap_lines: usize, history_size: usize, theme: Theme, keymap: Keymap) -> Self {
         Self {
+            theme,
+            keymap,
+            frozen_columns,
+            wrap_lines,
             table_info: None,
+            kind: TableKind::Table,
             connection: None,
+            server_info: None,
             focus: TableFocus::Records,
             records: None,
             properties: None,
+            properties_error: None,
+            props_view: PropsView::Columns,
+            indexes: None,
+            foreign_keys: None,
+            ddl: None,
+            row_count: None,
+            aggregate: Aggregate::Off,
+            raw_values: false,
+            fit_columns: true,
+            wrap: false,
+            bookmarks: HashMap::new(),
+            page_offset: 0,
+            page_starts: Vec::new(),
+            page_size: DEFAULT_FETCH_LIMIT,
+            filter: None,
+            where_input: None,
+            records_error: None,
+            search: None,
+            search_case_sensitive: false,
+            records_views: HashMap::new(),
+            properties_cache: HashMap::new(),
+            search_input: None,
+            goto_input: None,
+            edit: None,
+            insert_form: None,
+            records_status: None,
+            show_cell_popup: false,
+            cell_popup_scroll: 0,
+            sql_input: String::new(),
+            sql_editing: false,
+            history: HistoryComponent::new(theme),
+            history_size,
+            saved_queries: QueriesComponent::new(theme),
+            query_name: None,
+            save_input: None,
+            show_plan: false,
+            plan: None,
+            plan_analyze: false,
+            plan_scroll: 0,
+            plan_col_scroll: 0,
+            plan_generation: 0,
+            query_result: false,
             records_scroll: 0,
             records_col_scroll: 0,
             properties_scroll: 0,
             properties_col_scroll: 0,
+            records_visible: Cell::new(0),
+            properties_visible: Cell::new(0),
+            records_generation: 0,
+            properties_generation: 0,
+            records_loading: false,
+            records_streamed: false,
+            properties_loading: false,
+            spinner_frame: 0,
         }
     }
 
-    pub fn set_table(&mut self, database: String, table: String) {
-        self.table_info = Some(TableInfo { database, table });
+    pub fn set_table(&mut self, database: String, schema: Option<String>, table: String, kind: TableKind) {
+        // Keep the previous table's filter and search for when it's reopened
+        if let Some(previous) = self.table_info.take() {
+            let view = RecordsView { filter: self.filter.take(), search: self.search.take() };
+            let cached = CachedProperties {
+                properties: self.properties.take(),
+                ddl: self.ddl.take().and_then(Result::ok),
+            };
+            if cached.properties.is_some() || cached.ddl.is_some() {
+                self.properties_cache.insert(previous.clone(), cached);
+            }
+            self.records_views.insert(previous, view);
+        }
+        let info = TableInfo { database, schema, table };
+        let view = self.records_views.remove(&info).unwrap_or_default();
+        let cached = self.properties_cache.remove(&info);
+        self.table_info = Some(info);
+        self.kind = kind;
         self.records = None;
-        self.properties = None;
+        self.properties_error = None;
+        self.props_view = PropsView::Columns;
+        self.indexes = None;
+        self.foreign_keys = None;
+        (self.properties, self.ddl) = match cached {
+            Some(cached) => (cached.properties, cached.ddl.map(Ok)),
+            None => (None, None),
+        };
+        self.row_count = None;
+        self.page_offset = 0;
+        self.page_starts.clear();
+        self.filter = view.filter;
+        self.where_input = None;
+        self.records_error = None;
+        self.search = view.search;
+        self.search_input = None;
+        self.goto_input = None;
+        self.edit = None;
+        self.insert_form = None;
+        self.records_status = None;
+        self.show_cell_popup = false;
+        self.query_result = false;
         self.records_scroll = 0;
         self.records_col_scroll = 0;
         self.properties_scroll = 0;
         self.properties_col_scroll = 0;
+        // Anything still loading belongs to the previous table
+        self.records_generation += 1;
+        self.properties_generation += 1;
+        self.records_loading = false;
+        self.properties_loading = false;
+    }
+
+    /// Replace the grid with freshly fetched table rows, back at the top
+    fn show_records(&mut self, mut recs: Records) {
+        if let Some(props) = &self.properties {
+            recs.set_kinds(&props.columns);
+        }
+        self.records = Some(recs);
+        self.records_error = None;
+        self.query_result = false;
+        self.show_cell_popup = false;
+        self.records_scroll = 0;
+        self.records_col_scroll = 0;
+    }
+
+    fn spinner(&self) -> char {
+        SPINNER[self.spinner_frame % SPINNER.len()]
+    }
+
+    pub fn set_connection(&mut self, conn: Connection) {
+        self.page_size = conn.fetch_limit();
+        self.connection = Some(conn);
+    }
+
+    /// Forget every table's cached properties and DDL, including the open
+    /// table's, e.g. once another connection is opened
+    pub fn forget_properties(&mut self) {
+        self.properties_cache.clear();
+        self.properties = None;
+        self.ddl = None;
+    }
+
+    /// Forget the cached properties and DDL and fetch the open table's again;
+    /// the tree's Refresh does this
+    pub fn refresh_properties(&mut self, conn: &Connection) -> Command {
+        self.properties_cache.clear();
+        if self.table_info.is_none() {
+            return Command::none();
+        }
+        self.ddl = None;
+        let ddl = match self.props_view {
+            PropsView::Ddl => self.load_ddl(conn.clone()),
+            _ => Command::none(),
+        };
+        Command::batch([self.load_properties(conn.clone()), ddl])
+    }
+
+    pub fn set_server_info(&mut self, info: Option<ServerInfo>) {
+        self.server_info = info;
+    }
+
+    /// The row bookmarks and other row actions apply to: the top visible row
+    fn current_row(&self) -> Option<usize> {
+        let recs = self.records.as_ref()?;
+        let last = recs.rows.len().checked_sub(1)?;
+        Some(self.records_scroll.min(last))
+    }
+
+    /// The cell the popup inspects: the current row's first visible column
+    fn current_cell(&self) -> Option<(usize, usize)> {
+        let row = self.current_row()?;
+        let last_col = self.records.as_ref()?.columns.len().checked_sub(1)?;
+        Some((row, self.records_col_scroll.min(last_col)))
+    }
+
+    /// Text the grid shows for `value` in column `col`
+    fn display_value<'a>(&self, recs: &Records, col: usize, value: &'a str) -> std::borrow::Cow<'a, str> {
+        if self.raw_values {
+            std::borrow::Cow::Borrowed(value)
+        } else {
+            recs.kind(col).format(value)
+        }
+    }
+
+    /// Lines each loaded row takes in wrap mode with the `shown` columns at
+    /// `widths`: those of its tallest cell, at most `wrap_lines`
+    fn wrapped_heights(&self, recs: &Records, shown: &[usize], widths: &[u16]) -> Vec<u16> {
+        recs.rows
+            .iter()
+            .map(|r| {
+                let tallest = shown
+                    .iter()
+                    .map(|&col| {
+                        let v = r.get(col).map_or("", String::as_str);
+                        wrap_cell_capped(&self.display_value(recs, col, v), widths[col], self.wrap_lines).height()
+                    })
+                    .max()
+                    .unwrap_or(1);
+                u16::try_from(tallest).unwrap_or(u16::MAX).max(1)
+            })
+            .collect()
+    }
+
+    /// Width of each records column: the widest of its header and the values
+    /// in `rows`, between 3 and `MAX_FITTED_WIDTH`
+    fn fitted_widths(&self, recs: &Records, rows: std::ops::Range<usize>) -> Vec<u16> {
+        (0..recs.columns.len())
+            .map(|col| {
+                let widest = recs.rows[rows.clone()]
+                    .iter()
+                    .map(|r| r.get(col).map_or(0, |v| self.display_value(recs, col, v).chars().count()))
+                    .fold(recs.columns[col].chars().count(), usize::max);
+                u16::try_from(widest).unwrap_or(u16::MAX).clamp(MIN_FITTED_WIDTH, MAX_FITTED_WIDTH)
+            })
+            .collect()
+    }
+
+    /// Last valid row and column offsets of the records grid. `records_scroll`
+    /// is the current row, not the top of the window, so it runs up to the
+    /// last row; draw scrolls the window to keep that row in view.
+    fn records_last(&self) -> (usize, usize) {
+        self.records.as_ref().map_or((0, 0), |recs| {
+            (recs.rows.len().saturating_sub(1), recs.columns.len().saturating_sub(1))
+        })
+    }
+
+    /// Last valid row and column offsets of the grid the Properties tab shows;
+    /// column counts follow the headers drawn for each view
+    fn properties_last(&self) -> (usize, usize) {
+        let (rows, cols) = match self.props_view {
+            PropsView::Columns => match &self.properties {
+                Some(props) if has_charset(props) => (props.columns.len(), 7),
+                props => (props.as_ref().map_or(0, |p| p.columns.len()), 5),
+            },
+            PropsView::Indexes => (self.indexes.as_ref().and_then(|i| i.as_ref().ok()).map_or(0, Vec::len), 3),
+            PropsView::ForeignKeys => (self.foreign_keys.as_ref().and_then(|k| k.as_ref().ok()).map_or(0, Vec::len), 4),
+            // Scrolls by line; long lines wrap instead
+            PropsView::Ddl => (self.ddl.as_ref().and_then(|d| d.as_ref().ok()).map_or(0, |d| d.lines().count()), 1),
+        };
+        (rows.saturating_sub(1), cols - 1)
+    }
+
+    /// Rows in half of a `visible`-row view, at least one, signed by `direction`
+    fn half_page(visible: usize, direction: i32) -> i32 {
+        i32::try_from((visible / 2).max(1)).unwrap_or(i32::MAX) * direction.signum()
+    }
+
+    /// Move `offset` by `delta`, staying within `0..=last`
+    fn scroll_by(offset: usize, delta: i32, last: usize) -> usize {
+        let moved = if delta < 0 {
+            offset.saturating_sub(delta.unsigned_abs() as usize)
+        } else {
+            offset.saturating_add(delta as usize)
+        };
+        moved.min(last)
+    }
+
+    /// Whether `value` contains the search text; `needle` comes from
+    /// `search_needle`, lowercased unless matching case-sensitively
+    fn cell_matches(&self, needle: &str, value: &str) -> bool {
+        !needle.is_empty()
+            && if self.search_case_sensitive {
+                value.contains(needle)
+            } else {
+                value.to_lowercase().contains(needle)
+            }
+    }
+
+    /// Cells matching the search being typed, or else the active one, in row-major order
+    fn search_matches(&self) -> Vec<(usize, usize)> {
+        let (Some(recs), Some(needle)) = (&self.records, self.search_needle()) else {
+            return Vec::new();
+        };
+        recs.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, v)| self.cell_matches(&needle, v))
+                    .map(move |(c, _)| (r, c))
+            })
+            .collect()
+    }
+
+    fn search_needle(&self) -> Option<String> {
+        let text = self.search_input.as_ref().or(self.search.as_ref())?;
+        Some(if self.search_case_sensitive { text.clone() } else { text.to_lowercase() })
+    }
+
+    /// Make the next (or previous) match after the current cell the current cell
+    fn jump_to_match(&mut self, forward: bool) {
+        let matches = self.search_matches();
+        let Some(current) = self.current_cell() else {
+            return;
+        };
+        let target = if forward {
+            matches.iter().find(|&&m| m > current).or(matches.first())
+        } else {
+            matches.iter().rev().find(|&&m| m < current).or(matches.last())
+        };
+        if let Some(&(row, col)) = target {
+            self.records_scroll = row;
+            self.records_col_scroll = col;
+        }
+    }
+
+    /// Positions in the loaded records of the primary-key columns; empty when
+    /// the table has none, properties haven't loaded, or one isn't loaded
+    fn pk_columns(&self) -> Vec<usize> {
+        let Some(recs) = &self.records else {
+            return Vec::new();
+        };
+        let pk: Vec<&str> = self
+            .properties
+            .iter()
+            .flat_map(|p| p.columns.iter().filter(|c| c.primary_key))
+            .map(|c| c.name.as_str())
+            .collect();
+        let idx: Vec<usize> = pk
+            .iter()
+            .filter_map(|name| recs.columns.iter().position(|c| c == name))
+            .collect();
+        if idx.len() == pk.len() {
+            idx
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Stable identity of a row: its primary-key values, or the whole row when
+    /// the table has no primary key (or properties haven't loaded yet)
+    fn row_key(&self, row: &[String]) -> Vec<String> {
+        let pk_idx = self.pk_columns();
+        if pk_idx.is_empty() {
+            row.to_vec()
+        } else {
+            pk_idx.iter().map(|&i| row.get(i).cloned().unwrap_or_default()).collect()
+        }
+    }
+
+    fn is_bookmarked(&self, row: &[String]) -> bool {
+        // Bookmarks identify rows of the table, not of a query result
+        let Some(info) = self.table_info.as_ref().filter(|_| !self.query_result) else {
+            return false;
+        };
+        self.bookmarks
+            .get(info)
+            .is_some_and(|marks| marks.contains(&self.row_key(row)))
+    }
+
+    fn toggle_bookmark(&mut self) {
+        let (Some(info), Some(idx)) = (self.table_info.clone(), self.current_row()) else {
+            return;
+        };
+        if self.query_result {
+            return;
+        }
+        let Some(row) = self.records.as_ref().map(|r| &r.rows[idx]) else {
+            return;
+        };
+        let key = self.row_key(row);
+        let marks = self.bookmarks.entry(info).or_default();
+        if !marks.remove(&key) {
+            marks.insert(key);
+        }
+    }
+
+    fn jump_to_bookmark(&mut self, forward: bool) {
+        let (Some(recs), Some(current)) = (&self.records, self.current_row()) else {
+            return;
+        };
+        let total = recs.rows.len();
+        let next = (1..=total)
+            .map(|step| if forward { (current + step) % total } else { (current + total - step) % total })
+            .find(|&i| self.is_bookmarked(&recs.rows[i]));
+        if let Some(i) = next {
+            self.records_scroll = i;
+        }
+    }
+
+    /// Fetch the page of records starting at `page_offset`
+    fn load_records(&mut self, conn: Connection) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        self.records_generation += 1;
+        self.records_loading = true;
+        self.records_streamed = false;
+        let generation = self.records_generation;
+        let mut query = RecordQuery {
+            limit: self.page_size,
+            offset: self.page_offset,
+            filter: self.filter.clone(),
+            order: None,
+            chunks: None,
+        };
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            // Show rows as they're read rather than after the whole page
+            let chunk_tx = tx.clone();
+            query.chunks = Some(ChunkSink(Arc::new(move |first, recs| {
+                let _ = chunk_tx.send(TableMsg::RecordsChunk(generation, first, recs).into());
+            })));
+            let res = DB::fetch_records(&conn, &info.database, info.schema.as_deref(), &info.table, &query);
+            let msg = match res {
+                Ok(recs) => TableMsg::RecordsLoaded(generation, recs).into(),
+                Err(e) => {
+                    error(&format!("Table: load failed: {}", e));
+                    TableMsg::RecordsLoadFailed(generation, e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    /// Run `sql` in a background task. Unlike the external CLI this never
+    /// suspends the terminal, so the TUI keeps drawing while it runs.
+    fn run_query(&mut self, sql: String) -> Command {
+        let Some(conn) = self.connection.clone() else {
+            return Command::none();
+        };
+        self.records_generation += 1;
+        self.records_loading = true;
+        let generation = self.records_generation;
+        let history_size = self.history_size;
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let name = conn.name.clone().unwrap_or_default();
+            if let Err(e) = history::record(&name, &sql, history_size) {
+                warn(&format!("Table: recording query history failed: {}", e));
+            }
+            let msg = match DB::run_query(&conn, &sql) {
+                Ok(recs) => TableMsg::QueryResult(generation, recs).into(),
+                Err(e) => {
+                    error(&format!("Table: query failed: {}", e));
+                    TableMsg::RecordsLoadFailed(generation, e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn explain(&mut self, analyze: bool) -> Command {
+        let Some(conn) = self.connection.clone() else {
+            return Command::none();
+        };
+        let sql = self.sql_input.clone();
+        self.plan_generation += 1;
+        self.show_plan = true;
+        self.plan = None;
+        self.plan_analyze = analyze;
+        self.plan_scroll = 0;
+        self.plan_col_scroll = 0;
+        let generation = self.plan_generation;
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match DB::explain(&conn, &sql, analyze) {
+                Ok(lines) => TableMsg::PlanLoaded(generation, lines).into(),
+                Err(e) => {
+                    error(&format!("Table: explain failed: {}", e));
+                    TableMsg::PlanFailed(generation, e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn load_history() -> Command {
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match history::load() {
+                Ok(entries) => TableMsg::HistoryLoaded(entries).into(),
+                Err(e) => {
+                    error(&format!("Table: loading query history failed: {}", e));
+                    TableMsg::HistoryLoadFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn save_query(name: String, sql: String) -> Command {
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match queries::save(&name, &sql) {
+                Ok(path) => TableMsg::QuerySaved(name, path).into(),
+                Err(e) => {
+                    error(&format!("Table: saving query failed: {}", e));
+                    TableMsg::QuerySaveFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn load_saved_queries() -> Command {
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match queries::load_all() {
+                Ok(queries) => TableMsg::QueriesLoaded(queries).into(),
+                Err(e) => {
+                    error(&format!("Table: loading saved queries failed: {}", e));
+                    TableMsg::QueriesLoadFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn load_indexes(&self, conn: Connection) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match DB::fetch_indexes(&conn, &info.database, info.schema.as_deref(), &info.table) {
+                Ok(indexes) => TableMsg::IndexesLoaded(indexes).into(),
+                Err(e) => {
+                    error(&format!("Props: loading indexes failed: {}", e));
+                    TableMsg::IndexesLoadFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn load_foreign_keys(&self, conn: Connection) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match DB::fetch_foreign_keys(&conn, &info.database, info.schema.as_deref(), &info.table) {
+                Ok(keys) => TableMsg::ForeignKeysLoaded(keys).into(),
+                Err(e) => {
+                    error(&format!("Props: loading foreign keys failed: {}", e));
+                    TableMsg::ForeignKeysLoadFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn load_ddl(&self, conn: Connection) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match DB::fetch_ddl(&conn, &info.database, info.schema.as_deref(), &info.table) {
+                Ok(ddl) => TableMsg::DdlLoaded(ddl).into(),
+                Err(e) => {
+                    error(&format!("Props: loading DDL failed: {}", e));
+                    TableMsg::DdlLoadFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    /// Show `view` in the Properties tab, or go back to the columns when it
+    /// is already shown
+    fn toggle_props_view(&mut self, view: PropsView) {
+        self.props_view = if self.props_view == view { PropsView::Columns } else { view };
+        self.properties_scroll = 0;
+        self.properties_col_scroll = 0;
+    }
+
+    /// The foreign key in the top row of the Properties grid, which Enter follows
+    fn highlighted_foreign_key(&self) -> Option<&ForeignKeyInfo> {
+        let keys = self.foreign_keys.as_ref()?.as_ref().ok()?;
+        keys.get(self.properties_scroll.min(keys.len().saturating_sub(1)))
+    }
+
+    fn follow_foreign_key(&self) -> Update<TableMsg> {
+        let (Some(info), Some(fk)) = (&self.table_info, self.highlighted_foreign_key()) else {
+            return Update::none();
+        };
+        TableMsg::JumpToTable(FinderEntry {
+            database: fk.referenced_database.clone().unwrap_or_else(|| info.database.clone()),
+            schema: fk.referenced_schema.clone(),
+            table: fk.referenced_table.clone(),
+        })
+        .into()
+    }
+
+    fn load_properties(&mut self, conn: Connection) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        self.properties_generation += 1;
+        self.properties_loading = true;
+        let generation = self.properties_generation;
+        debug(&format!("Props: loading {}", info.label()));
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let res = DB::fetch_properties(&conn, &info.database, info.schema.as_deref(), &info.table);
+            let msg = match res {
+                Ok(props) => TableMsg::PropertiesLoaded(generation, props).into(),
+                Err(e) => {
+                    error(&format!("Props: load failed: {}", e));
+                    TableMsg::PropertiesLoadFailed(generation, e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn load_row_count(&self, conn: Connection, exact: bool) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let res = DB::fetch_row_count(&conn, &info.database, info.schema.as_deref(), &info.table, exact);
+            let msg = match res {
+                Ok(count) => TableMsg::RowCountLoaded(count).into(),
+                Err(e) => {
+                    error(&format!("Table: row count failed: {}", e));
+                    TableMsg::RowCountLoadFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    /// Why the table can't be written to from the grid, if it can't
+    fn table_write_refusal(&self) -> Option<&'static str> {
+        if self.connection.as_ref().is_some_and(Connection::is_read_only) {
+            Some("the connection is read-only")
+        } else if self.kind == TableKind::View {
+            Some("views are read-only")
+        } else if self.query_result {
+            Some("the grid holds a query result")
+        } else if self.properties.is_none() && self.properties_error.is_some() {
+            Some("table properties failed to load")
+        } else if self.properties.is_none() {
+            Some("table properties are still loading")
+        } else {
+            None
+        }
+    }
+
+    /// Why rows of the loaded records can't be changed, if they can't: writes
+    /// identify a row by its full primary key
+    fn write_refusal(&self) -> Option<&'static str> {
+        if let Some(why) = self.table_write_refusal() {
+            Some(why)
+        } else if self.pk_columns().is_empty() {
+            Some("the table has no primary key (or not all of its columns are loaded)")
+        } else {
+            None
+        }
+    }
+
+    /// (column, value) pairs of the primary key of loaded row `row`; empty
+    /// when the row is too short to hold all of them
+    fn row_pk(&self, row: usize) -> Vec<(String, String)> {
+        let Some(recs) = &self.records else {
+            return Vec::new();
+        };
+        self.pk_columns()
+            .into_iter()
+            .map(|i| Some((recs.columns[i].clone(), recs.rows[row].get(i)?.clone())))
+            .collect::<Option<_>>()
+            .unwrap_or_default()
+    }
+
+    /// Start editing the current cell, or say why it can't be edited
+    fn start_edit(&mut self) -> Result<(), String> {
+        let (Some((row, col)), Some(recs)) = (self.current_cell(), &self.records) else {
+            return Ok(());
+        };
+        if let Some(why) = self.write_refusal() {
+            return Err(format!("cannot edit: {}", why));
+        }
+        if recs.blobs.contains_key(&(row, col)) {
+            return Err("cannot edit a binary value".to_string());
+        }
+        let Some(input) = recs.rows[row].get(col).cloned() else {
+            return Err("this row has no value in that column".to_string());
+        };
+        self.edit = Some(CellEdit { row, col, input, null: false });
+        Ok(())
+    }
+
+    /// Run `write` on the current table, then re-fetch the loaded page so the
+    /// grid shows what the database stored. `done` describes the changed row
+    /// count.
+    fn write_and_reload(
+        &mut self,
+        conn: Connection,
+        write: impl FnOnce(&Connection, &TableInfo) -> anyhow::Result<u64> + Send + 'static,
+        done: fn(u64) -> String,
+    ) -> Command {
+        let Some(info) = self.table_info.clone() else {
+            return Command::none();
+        };
+        let query = RecordQuery {
+            limit: self.page_size,
+            offset: self.page_offset,
+            filter: self.filter.clone(),
+            order: None,
+            chunks: None,
+        };
+        self.records_generation += 1;
+        self.records_loading = true;
+        let generation = self.records_generation;
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let msg = match write(&conn, &info) {
+                Ok(n) => match DB::fetch_records(&conn, &info.database, info.schema.as_deref(), &info.table, &query) {
+                    Ok(recs) => TableMsg::RowsWritten(generation, recs, done(n)).into(),
+                    Err(e) => {
+                        error(&format!("Table: load failed: {}", e));
+                        TableMsg::RecordsLoadFailed(generation, e.to_string()).into()
+                    }
+                },
+                Err(e) => {
+                    error(&format!("Table: write failed: {}", e));
+                    TableMsg::WriteFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    fn commit_edit(&mut self, conn: Connection, edit: CellEdit) -> Command {
+        let Some(recs) = &self.records else {
+            return Command::none();
+        };
+        let column = recs.columns[edit.col].clone();
+        let key = self.row_pk(edit.row);
+        if key.is_empty() {
+            return Command::none();
+        }
+        let value = (!edit.null).then_some(edit.input);
+        let write = move |conn: &Connection, info: &TableInfo| {
+            DB::update_cell(conn, &info.database, info.schema.as_deref(), &info.table, &column, value.as_deref(), &key)
+        };
+        self.write_and_reload(conn, write, |n| format!("updated {} row(s)", n))
+    }
+
+    fn delete_row(&mut self, conn: Connection, key: Vec<(String, String)>) -> Command {
+        if key.is_empty() {
+            return Command::none();
+        }
+        let write = move |conn: &Connection, info: &TableInfo| {
+            DB::delete_row(conn, &info.database, info.schema.as_deref(), &info.table, &key)
+        };
+        self.write_and_reload(conn, write, |n| format!("deleted {} row(s)", n))
+    }
+
+    fn start_insert(&mut self) -> Result<(), String> {
+        if let Some(why) = self.table_write_refusal() {
+            return Err(format!("cannot insert: {}", why));
+        }
+        let Some(props) = &self.properties else {
+            return Ok(());
+        };
+        let single_pk = props.columns.iter().filter(|c| c.primary_key).count() == 1;
+        let fields = props
+            .columns
+            .iter()
+            .map(|c| InsertField::new(c.clone(), single_pk))
+            .collect();
+        self.insert_form = Some(InsertForm { fields, selected: 0, error: None });
+        Ok(())
+    }
+
+    fn insert_row(&mut self, conn: Connection, form: InsertForm) -> Command {
+        let values: Vec<(String, String)> = form
+            .fields
+            .into_iter()
+            .filter_map(|f| Some((f.column.name, f.value?)))
+            .collect();
+        let write = move |conn: &Connection, info: &TableInfo| {
+            DB::insert_row(conn, &info.database, info.schema.as_deref(), &info.table, &values)
+        };
+        self.write_and_reload(conn, write, |n| format!("inserted {} row(s)", n))
+    }
+
+    /// Popup listing the insert form's fields, scrolled to keep the selected one in view
+    fn draw_insert_form(&self, f: &mut Frame, area: Rect, form: &InsertForm) {
+        use ratatui::widgets::{Cell as TuiCell, Row, Table as TuiTable};
+        let width = area.width.saturating_mul(4) / 5;
+        let height = area.height.saturating_mul(4) / 5;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+        let visible = usize::from(popup.height.saturating_sub(2)).max(1);
+        let start = form.selected.saturating_sub(visible - 1);
+        let rows = form.fields.iter().enumerate().skip(start).take(visible).map(|(i, field)| {
+            let selected = i == form.selected;
+            let value = match &field.value {
+                Some(v) if selected => TuiCell::from(format!("{}_", v)),
+                Some(v) => TuiCell::from(v.as_str()),
+                None => {
+                    let (placeholder, color) = if field.auto {
+                        ("(generated)", Color::DarkGray)
+                    } else if field.column.default.is_some() {
+                        ("(default)", Color::DarkGray)
+                    } else if field.column.nullable {
+                        ("(NULL)", self.theme.null_cell)
+                    } else {
+                        ("", Color::DarkGray)
+                    };
+                    TuiCell::from(placeholder).style(Style::default().fg(color))
+                }
+            };
+            let name_style = if field.required() {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let row = Row::new([
+                TuiCell::from(if selected { ">" } else { "" }),
+                TuiCell::from(field.column.name.as_str()).style(name_style),
+                value,
+                TuiCell::from(field.hint()).style(Style::default().fg(Color::DarkGray)),
+            ]);
+            if selected {
+                row.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                row
+            }
+        });
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Percentage(25),
+            Constraint::Percentage(40),
+            Constraint::Percentage(35),
+        ];
+        let table = self.table_info.as_ref().map_or("", |t| t.table.as_str());
+        let mut block = Block::default()
+            .title(format!(
+                "Insert into {}  (↑/↓ field; type to set, Backspace on empty unsets; Enter: insert; Esc: cancel)",
+                table
+            ))
+            .borders(Borders::ALL)
+            .border_style(self.theme.border(true));
+        if let Some(e) = &form.error {
+            block = block.title_bottom(ratatui::text::Line::styled(
+                format!(" {} ", e),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        f.render_widget(TuiTable::new(rows, widths).block(block), popup);
+    }
+
+    /// Draw the Properties tab's CREATE statement, scrolled by `properties_scroll`
+    fn draw_ddl(&self, f: &mut Frame, area: Rect, style: Style) {
+        let keys = format!(
+            "{}: copy, {}: columns",
+            self.keymap.describe(Action::Copy),
+            self.keymap.describe(Action::ToggleDdl)
+        );
+        let (title, text) = match &self.ddl {
+            None => ("DDL".to_string(), Text::from(format!("{} Loading DDL...", self.spinner()))),
+            Some(Err(e)) => (
+                "DDL".to_string(),
+                Text::styled(format!("Error: {}", e), Style::default().fg(Color::Red)),
+            ),
+            Some(Ok(ddl)) => (format!("DDL  ({})", keys), Text::from(ddl.as_str())),
+        };
+        self.properties_visible.set(usize::from(area.height.saturating_sub(2)));
+        let scroll = u16::try_from(self.properties_scroll).unwrap_or(u16::MAX);
+        let paragraph = Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(Block::default().title(title).borders(Borders::ALL).border_style(style));
+        f.render_widget(paragraph, area);
+    }
+
+    /// Draw the Properties tab's grid (columns, indexes or foreign keys), windowed by
+    /// `properties_scroll` / `properties_col_scroll`. Cells may span several lines.
+    fn draw_props_grid(
+        &self,
+        f: &mut Frame,
+        area: Rect,
+        style: Style,
+        name: &str,
+        headers: &[(&str, u16, Alignment)],
+        rows: &[Vec<Text<'static>>],
+    ) {
+        use ratatui::widgets::{Cell as TuiCell, Row, Table as TuiTable};
+        let header_labels: Vec<&str> = headers.iter().map(|(label, _, _)| *label).collect();
+        let widths_all: Vec<u16> = headers.iter().map(|(_, width, _)| *width).collect();
+        // Horizontal column window calculation based on available width
+        let border_cols = 2u16; // left+right borders
+        let avail_w = area.width.saturating_sub(border_cols);
+        // Like the records, the window backs off near the end so a wider area stays filled
+        let (col_start, col_end) = column_window(&widths_all, self.properties_col_scroll, avail_w);
+        let header = Row::new(headers[col_start..col_end].iter().map(|(label, _, alignment)| {
+            TuiCell::from(Line::from(*label).alignment(*alignment))
+                .style(Style::default().fg(self.theme.header).add_modifier(Modifier::BOLD))
+        }));
+        // Visible slice based on height and properties_scroll
+        let border_rows = 2u16;
+        let header_rows = 1u16;
+        let avail = area
+            .height
+            .saturating_sub(border_rows)
+            .saturating_sub(header_rows);
+        // Rows as tall as their tallest shown cell
+        let heights: Vec<u16> = rows
+            .iter()
+            .map(|fields| {
+                let tallest = fields[col_start..col_end].iter().map(Text::height).max().unwrap_or(1);
+                u16::try_from(tallest).unwrap_or(u16::MAX).max(1)
+            })
+            .collect();
+        let total = rows.len();
+        let (start, end) = if avail == 0 { (0, 0) } else { line_window(&heights, self.properties_scroll, avail) };
+        let visible_count = end - start;
+        self.properties_visible.set(visible_count);
+        // Enter follows the foreign key in the top row, so mark it
+        let highlight = self.props_view == PropsView::ForeignKeys;
+        let visible_rows = rows[start..end].iter().enumerate().map(|(i, fields_all)| {
+            let row = Row::new(fields_all[col_start..col_end].iter().cloned()).height(heights[start + i]);
+            if highlight && i == 0 {
+                row.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                row
+            }
+        });
+        let widths = widths_all[col_start..col_end]
+            .iter()
+            .cloned()
+            .map(Constraint::Length)
+            .collect::<Vec<_>>();
+        let (indexes, foreign_keys) =
+            (self.keymap.describe(Action::ToggleIndexes), self.keymap.describe(Action::ToggleForeignKeys));
+        let views = match self.props_view {
+            PropsView::Columns => format!("{}: indexes, {}: foreign keys", indexes, foreign_keys),
+            PropsView::Indexes => format!("{}: columns, {}: foreign keys", indexes, foreign_keys),
+            PropsView::ForeignKeys => format!(
+                "{}: open referenced table, {}: columns, {}: indexes",
+                self.keymap.describe(Action::Select),
+                foreign_keys,
+                indexes
+            ),
+            PropsView::Ddl => unreachable!("the DDL is drawn by draw_ddl"),
+        };
+        let views = format!("{}, {}: DDL", views, self.keymap.describe(Action::ToggleDdl));
+        let title = if total > 0 && visible_count > 0 {
+            format!(
+                "{}  rows [{}-{} / {}], cols [{}-{} / {}]  (↑/↓, PgUp/PgDn, Home/End; ←/→; {})",
+                name,
+                start.saturating_add(1), end, total,
+                col_start.saturating_add(1), col_end, header_labels.len(),
+                views
+            )
+        } else {
+            format!("{}  ({})", name, views)
+        };
+        let table = TuiTable::new(visible_rows, widths)
+            .header(header)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(style),
+            );
+        f.render_widget(table, area);
+    }
+
+    /// Write the loaded records to a file in the working directory with
+    /// `write`, reporting the path (or error) back as a message
+    fn export(
+        &self,
+        write: fn(&Records, &std::path::Path, &str) -> anyhow::Result<PathBuf>,
+    ) -> Command {
+        let (Some(info), Some(recs)) = (self.table_info.clone(), self.records.clone()) else {
+            return Command::none();
+        };
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let res = std::env::current_dir()
+                .map_err(anyhow::Error::from)
+                .and_then(|dir| write(&recs, &dir, &info.table));
+            let msg = match res {
+                Ok(path) => TableMsg::Exported(path).into(),
+                Err(e) => {
+                    error(&format!("Table: export failed: {}", e));
+                    TableMsg::ExportFailed(e.to_string()).into()
+                }
+            };
+            let _ = tx.send(msg);
+        };
+        Command::Spawn(Box::new(task))
+    }
+
+    /// Type, nullability and comment of `column` from the loaded properties,
+    /// shown above a cell's value; `None` until properties are loaded
+    fn column_summary(&self, column: &str) -> Option<String> {
+        let info = self.properties.as_ref()?.columns.iter().find(|c| c.name == column)?;
+        let mut summary = info.data_type.clone();
+        if !info.nullable {
+            summary.push_str(" NOT NULL");
+        }
+        if let Some(comment) = &info.comment {
+            summary.push_str(" — ");
+            summary.push_str(comment);
+        }
+        Some(summary)
+    }
+
+    /// Centered popup with the full, wrapped value of the current cell, or a
+    /// hex dump of its leading bytes for a binary value, under a header with
+    /// the column's type and comment. JSON objects and arrays are
+    /// pretty-printed and colored unless raw values are shown.
+    fn draw_cell_popup(&self, f: &mut Frame, area: Rect, recs: &Records) {
+        let Some((row, col)) = self.current_cell() else {
+            return;
+        };
+        let (text, detail, json) = match recs.blobs.get(&(row, col)) {
+            Some(blob) => {
+                let shown = if blob.bytes.len() < blob.len {
+                    format!(", first {} shown", blob.bytes.len())
+                } else {
+                    String::new()
+                };
+                (hex_dump(&blob.bytes), format!("blob {} bytes{}", blob.len, shown), false)
+            }
+            None => {
+                let value = recs.rows[row].get(col).cloned().unwrap_or_default();
+                let chars = value.chars().count();
+                match pretty_json(&value).filter(|_| !self.raw_values) {
+                    Some(pretty) => (pretty, format!("JSON, {} chars", chars), true),
+                    None => (value, format!("{} chars", chars), false),
+                }
+            }
+        };
+        let width = area.width.saturating_mul(4) / 5;
+        let height = area.height.saturating_mul(3) / 5;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+        let title = format!(
+            "{} (row {}, {})  (↑/↓, PgUp/PgDn; Esc: close)",
+            recs.columns[col],
+            self.page_offset + row + 1,
+            detail
+        );
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.border(true));
+        let mut inner = block.inner(popup);
+        f.render_widget(block, popup);
+        if let Some(summary) = self.column_summary(&recs.columns[col]) {
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(0)])
+                .split(inner);
+            f.render_widget(Paragraph::new(summary).style(Style::default().fg(self.theme.header)), parts[0]);
+            inner = parts[1];
+        }
+        // Wrapped line count, approximated by characters; keeps at least one
+        // line of text in view
+        let inner_w = usize::from(inner.width).max(1);
+        let lines: usize = text.split('\n').map(|l| l.chars().count().div_ceil(inner_w).max(1)).sum();
+        let scroll = u16::try_from(self.cell_popup_scroll.min(lines.saturating_sub(1))).unwrap_or(u16::MAX);
+        let body = if json {
+            Text::from(highlight_json(&text, self.theme.highlight))
+        } else {
+            Text::from(text.as_str())
+        };
+        let paragraph = Paragraph::new(body).wrap(Wrap { trim: false }).scroll((scroll, 0));
+        f.render_widget(paragraph, inner);
     }
 
-    pub fn set_connection(&mut self, conn: Connection) {
-        self.connection = Some(conn);
+    fn draw_plan(&self, f: &mut Frame, area: Rect) {
+        let width = area.width.saturating_mul(9) / 10;
+        let height = area.height.saturating_mul(4) / 5;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+        let name = if self.plan_analyze { "EXPLAIN ANALYZE" } else { "EXPLAIN" };
+        let title = format!("{}  (↑/↓, PgUp/PgDn, ←/→; Esc: close)", name);
+        // Plans are indented trees, so long lines scroll sideways instead of wrapping
+        let (text, scroll) = match &self.plan {
+            None => (Text::from(format!("{} Explaining...", self.spinner())), (0, 0)),
+            Some(Err(e)) => (Text::styled(format!("Error: {}", e), Style::default().fg(Color::Red)), (0, 0)),
+            Some(Ok(lines)) => {
+                let widest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+                let row = self.plan_scroll.min(lines.len().saturating_sub(1));
+                let col = self.plan_col_scroll.min(widest.saturating_sub(1));
+                let scroll = (u16::try_from(row).unwrap_or(u16::MAX), u16::try_from(col).unwrap_or(u16::MAX));
+                (Text::from(lines.iter().map(|l| Line::from(l.as_str())).collect::<Vec<_>>()), scroll)
+            }
+        };
+        let paragraph = Paragraph::new(text)
+            .scroll(scroll)
+            .block(Block::default().title(title).borders(Borders::ALL).border_style(self.theme.border(true)));
+        f.render_widget(paragraph, popup);
     }
 
-
-    fn launch_external_cli(conn: &Connection) -> Box<dyn FnOnce() -> Result<(), Box<dyn std::error::Error>> + Send> {
+    /// Terminal-suspending task that runs the connection's CLI tool, optionally
+    /// pre-focused on a table. Shared with the dashboard's "open in CLI" action.
+    pub fn launch_external_cli(
+        conn: &Connection,
+        target: Option<CliTarget>,
+    ) -> Box<dyn FnOnce() -> Result<(), Box<dyn std::error::Error>> + Send> {
         let conn = conn.clone();
         
         Box::new(move || {
@@ -107,8 +1862,11 @@ impl TableComponent {
             if !DB::is_cli_tool_available_for(&conn) {
                 return Err(format!("CLI tool '{}' not found. Please install it first.", tool_name).into());
             }
+            if let Some(t) = &target {
+                debug(&format!("Opening {} on {}.{}", tool_name, t.database, t.table));
+            }
 
-            match DB::launch_cli_tool_for(&conn) {
+            match DB::launch_cli_tool_for(&conn, target.as_ref()) {
                 Ok(status) => {
                     if status.success() {
                         Ok(())
@@ -139,67 +1897,426 @@ impl Component for TableComponent {
                 self.focus = TableFocus::Properties;
                 Update::none()
             }
+            TableMsg::Tick => {
+                self.spinner_frame = self.spinner_frame.wrapping_add(1);
+                Update::none()
+            }
             TableMsg::BackToDBList => TableMsg::BackToDBList.into(),
             TableMsg::LoadRecords(conn) => {
-                let Some(info) = self.table_info.clone() else {
+                let Some(info) = &self.table_info else {
                     return Update::none();
                 };
-                debug(&format!("Table: loading {}.{}", info.database, info.table));
-                let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
-                    let res = DB::fetch_records(&conn, &info.database, &info.table, 200, 0);
-                    let msg = match res {
-                        Ok(recs) => TableMsg::RecordsLoaded(recs).into(),
-                        Err(e) => {
-                            error(&format!("Table: load failed: {}", e));
-                            TableMsg::RecordsLoadFailed(e.to_string()).into()
-                        }
-                    };
-                    let _ = tx.send(msg);
+                debug(&format!("Table: loading {}", info.label()));
+                let count = self.load_row_count(conn.clone(), false);
+                // Primary keys identify bookmarked rows
+                let props = match self.properties {
+                    Some(_) => Command::none(),
+                    None => self.load_properties(conn.clone()),
                 };
-                Command::Spawn(Box::new(task)).into()
+                Command::batch([self.load_records(conn), count, props]).into()
+            }
+            TableMsg::RecordsLoaded(generation, recs) => {
+                if generation != self.records_generation {
+                    debug("Table: dropped stale records");
+                    return Update::none();
+                }
+                self.records_loading = false;
+                match (std::mem::take(&mut self.records_streamed), &mut self.records) {
+                    (true, Some(shown)) => shown.append(recs),
+                    _ => self.show_records(recs),
+                }
+                if let Some(recs) = self.records.as_ref().filter(|r| r.truncated) {
+                    warn(&format!("Table: memory cap reached, kept {} rows", recs.rows.len()));
+                }
+                Update::none()
+            }
+            TableMsg::RecordsChunk(generation, first, recs) => {
+                if generation != self.records_generation {
+                    return Update::none();
+                }
+                match (first || !self.records_streamed, &mut self.records) {
+                    (false, Some(shown)) => shown.append(recs),
+                    _ => self.show_records(recs),
+                }
+                self.records_streamed = true;
+                Update::none()
             }
-            TableMsg::RecordsLoaded(recs) => {
+            TableMsg::QueryResult(generation, recs) => {
+                if generation != self.records_generation {
+                    return Update::none();
+                }
+                self.records_loading = false;
+                if recs.truncated {
+                    warn(&format!("Table: memory cap reached, kept {} rows", recs.rows.len()));
+                }
                 self.records = Some(recs);
+                self.records_error = None;
+                self.query_result = true;
+                self.show_cell_popup = false;
                 self.records_scroll = 0;
                 self.records_col_scroll = 0;
                 Update::none()
             }
-            TableMsg::RecordsLoadFailed(_e) => Update::none(),
-            TableMsg::LoadProperties(conn) => {
-                let Some(info) = self.table_info.clone() else {
+            TableMsg::RecordsLoadFailed(generation, e) => {
+                if generation != self.records_generation {
+                    return Update::none();
+                }
+                self.records_loading = false;
+                // Shown in place of the grid; most often a bad WHERE predicate
+                self.records = None;
+                self.records_error = Some(e.clone());
+                TableMsg::Status(StatusMessage::error(format!("loading records failed: {}", e))).into()
+            }
+            TableMsg::NextPage(conn) => {
+                // A query result is shown whole, up to the memory cap
+                let Some(recs) = self.records.as_ref().filter(|_| !self.query_result) else {
                     return Update::none();
                 };
-                debug(&format!("Props: loading {}.{}", info.database, info.table));
-                let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
-                    let res = DB::fetch_properties(&conn, &info.database, &info.table);
-                    let msg = match res {
-                        Ok(props) => TableMsg::PropertiesLoaded(props).into(),
-                        Err(e) => {
-                            error(&format!("Props: load failed: {}", e));
-                            TableMsg::PropertiesLoadFailed(e.to_string()).into()
-                        }
-                    };
-                    let _ = tx.send(msg);
+                // A short page is the last one; a page cut short by the memory
+                // cap continues right after its last loaded row
+                if recs.rows.is_empty() || (recs.rows.len() < self.page_size && !recs.truncated) {
+                    return Update::none();
+                }
+                self.page_starts.push(self.page_offset);
+                self.page_offset += recs.rows.len();
+                self.load_records(conn).into()
+            }
+            TableMsg::PrevPage(conn) => {
+                if self.query_result {
+                    return Update::none();
+                }
+                let Some(start) = self.page_starts.pop() else {
+                    return Update::none();
+                };
+                self.page_offset = start;
+                self.load_records(conn).into()
+            }
+            TableMsg::EditWhere => {
+                self.where_input = Some(self.filter.clone().unwrap_or_default());
+                Update::none()
+            }
+            TableMsg::WherePush(c) => {
+                if let Some(input) = &mut self.where_input {
+                    input.push(c);
+                }
+                Update::none()
+            }
+            TableMsg::WherePop => {
+                if let Some(input) = &mut self.where_input {
+                    input.pop();
+                }
+                Update::none()
+            }
+            TableMsg::ApplyWhere(conn) => {
+                let Some(input) = self.where_input.take() else {
+                    return Update::none();
+                };
+                let input = input.trim();
+                self.filter = (!input.is_empty()).then(|| input.to_string());
+                self.page_offset = 0;
+                self.page_starts.clear();
+                self.records = None;
+                self.records_error = None;
+                self.load_records(conn).into()
+            }
+            TableMsg::CancelWhere => {
+                self.where_input = None;
+                Update::none()
+            }
+            TableMsg::StartSearch => {
+                self.search_input = Some(String::new());
+                Update::none()
+            }
+            TableMsg::SearchPush(c) => {
+                if let Some(input) = &mut self.search_input {
+                    input.push(c);
+                }
+                Update::none()
+            }
+            TableMsg::SearchPop => {
+                if let Some(input) = &mut self.search_input {
+                    input.pop();
+                }
+                Update::none()
+            }
+            TableMsg::ConfirmSearch => {
+                let Some(input) = self.search_input.take() else {
+                    return Update::none();
                 };
-                Command::Spawn(Box::new(task)).into()
+                self.search = (!input.is_empty()).then_some(input);
+                // Start at the current cell itself when it matches
+                if let (Some(needle), Some((row, col))) = (self.search_needle(), self.current_cell()) {
+                    let here = self.records.as_ref().and_then(|r| r.rows[row].get(col)).map_or("", String::as_str);
+                    if !self.cell_matches(&needle, here) {
+                        self.jump_to_match(true);
+                    }
+                }
+                Update::none()
+            }
+            TableMsg::CancelSearch => {
+                self.search_input = None;
+                self.search = None;
+                Update::none()
             }
-            TableMsg::PropertiesLoaded(props) => {
+            TableMsg::NextMatch => self.jump_to_match(true).into(),
+            TableMsg::PrevMatch => self.jump_to_match(false).into(),
+            TableMsg::LoadProperties(conn) => match self.properties {
+                Some(_) => Update::none(),
+                None => self.load_properties(conn).into(),
+            },
+            TableMsg::PropertiesLoaded(generation, props) => {
+                if generation != self.properties_generation {
+                    debug("Props: dropped stale properties");
+                    return Update::none();
+                }
+                self.properties_loading = false;
+                // Query results have no declared column types
+                if let Some(recs) = self.records.as_mut().filter(|_| !self.query_result) {
+                    recs.set_kinds(&props.columns);
+                }
                 self.properties = Some(props);
+                self.properties_error = None;
                 self.properties_scroll = 0;
                 Update::none()
             }
-            TableMsg::PropertiesLoadFailed(_e) => Update::none(),
+            TableMsg::PropertiesLoadFailed(generation, e) => {
+                if generation != self.properties_generation {
+                    return Update::none();
+                }
+                self.properties_loading = false;
+                self.properties_error = Some(e.clone());
+                TableMsg::Status(StatusMessage::error(format!("loading properties failed: {}", e))).into()
+            }
+            TableMsg::ToggleIndexes(conn) => {
+                self.toggle_props_view(PropsView::Indexes);
+                if self.props_view == PropsView::Indexes && self.indexes.is_none() {
+                    return self.load_indexes(conn).into();
+                }
+                Update::none()
+            }
+            TableMsg::IndexesLoaded(indexes) => {
+                self.indexes = Some(Ok(indexes));
+                Update::none()
+            }
+            TableMsg::IndexesLoadFailed(e) => {
+                self.indexes = Some(Err(e.clone()));
+                TableMsg::Status(StatusMessage::error(format!("loading indexes failed: {}", e))).into()
+            }
+            TableMsg::ToggleForeignKeys(conn) => {
+                self.toggle_props_view(PropsView::ForeignKeys);
+                if self.props_view == PropsView::ForeignKeys && self.foreign_keys.is_none() {
+                    return self.load_foreign_keys(conn).into();
+                }
+                Update::none()
+            }
+            TableMsg::ForeignKeysLoaded(keys) => {
+                self.foreign_keys = Some(Ok(keys));
+                Update::none()
+            }
+            TableMsg::ForeignKeysLoadFailed(e) => {
+                self.foreign_keys = Some(Err(e.clone()));
+                TableMsg::Status(StatusMessage::error(format!("loading foreign keys failed: {}", e))).into()
+            }
+            TableMsg::ToggleDdl(conn) => {
+                self.toggle_props_view(PropsView::Ddl);
+                if self.props_view == PropsView::Ddl && self.ddl.is_none() {
+                    return self.load_ddl(conn).into();
+                }
+                Update::none()
+            }
+            TableMsg::DdlLoaded(ddl) => {
+                self.ddl = Some(Ok(ddl));
+                Update::none()
+            }
+            TableMsg::DdlLoadFailed(e) => {
+                self.ddl = Some(Err(e.clone()));
+                TableMsg::Status(StatusMessage::error(format!("loading DDL failed: {}", e))).into()
+            }
+            TableMsg::CopyDdl => {
+                let Some(Ok(ddl)) = &self.ddl else {
+                    return Update::none();
+                };
+                let status = match crate::clipboard::copy(ddl) {
+                    Ok(()) => StatusMessage::info("copied the DDL to the clipboard"),
+                    Err(e) => {
+                        error(&format!("Props: copying DDL failed: {}", e));
+                        StatusMessage::error(format!("copying failed: {}", e))
+                    }
+                };
+                TableMsg::Status(status).into()
+            }
+            TableMsg::CopyMarkdown => {
+                let Some(recs) = &self.records else {
+                    return Update::none();
+                };
+                let status = match crate::clipboard::copy(&export::markdown_table(recs)) {
+                    Ok(()) => StatusMessage::info(format!("copied {} rows as a Markdown table", recs.rows.len())),
+                    Err(e) => {
+                        error(&format!("Table: copying as Markdown failed: {}", e));
+                        StatusMessage::error(format!("copying failed: {}", e))
+                    }
+                };
+                TableMsg::Status(status).into()
+            }
+            TableMsg::FollowForeignKey => self.follow_foreign_key(),
+            // Intercepted by the dashboard's From<TableMsg>
+            TableMsg::JumpToTable(_) | TableMsg::Status(_) | TableMsg::Confirm(_) => Update::none(),
+            TableMsg::CountRowsExact(conn) => self.load_row_count(conn, true).into(),
+            TableMsg::RowCountLoaded(count) => {
+                self.row_count = Some(count);
+                Update::none()
+            }
+            TableMsg::RowCountLoadFailed(e) => {
+                TableMsg::Status(StatusMessage::error(format!("counting rows failed: {}", e))).into()
+            }
             TableMsg::LaunchSQLCli(conn) => {
-                let task = Self::launch_external_cli(&conn);
+                let task = Self::launch_external_cli(&conn, None);
                 Command::SuspendTerminal(task).into()
             }
+            TableMsg::EditSql => {
+                self.sql_editing = true;
+                Update::none()
+            }
+            TableMsg::StopEditSql => {
+                self.sql_editing = false;
+                Update::none()
+            }
+            TableMsg::SqlPush(c) => {
+                self.sql_input.push(c);
+                Update::none()
+            }
+            TableMsg::SqlNewline => {
+                self.sql_input.push('\n');
+                Update::none()
+            }
+            TableMsg::SqlPop => {
+                self.sql_input.pop();
+                Update::none()
+            }
+            TableMsg::RunQuery(sql) => {
+                if sql.trim().is_empty() {
+                    return Update::none();
+                }
+                // Show progress (and then the result or error) in the Records view
+                self.sql_editing = false;
+                self.focus = TableFocus::Records;
+                self.records = None;
+                self.records_error = None;
+                self.query_result = true;
+                self.show_cell_popup = false;
+                self.run_query(sql).into()
+            }
+            TableMsg::OpenHistory => Self::load_history().into(),
+            TableMsg::HistoryLoaded(entries) => {
+                self.history.open(entries);
+                Update::none()
+            }
+            TableMsg::HistoryLoadFailed(e) => {
+                TableMsg::Status(StatusMessage::error(format!("loading query history failed: {}", e))).into()
+            }
+            TableMsg::History(m) => self.history.update(m).map_auto(),
+            TableMsg::LoadFromHistory(sql) => {
+                self.history.close();
+                self.sql_input = sql;
+                self.sql_editing = true;
+                Update::none()
+            }
+            TableMsg::StartSaveQuery => {
+                if !self.sql_input.trim().is_empty() {
+                    self.save_input = Some(self.query_name.clone().unwrap_or_default());
+                }
+                Update::none()
+            }
+            TableMsg::SaveNamePush(c) => {
+                if let Some(input) = &mut self.save_input {
+                    input.push(c);
+                }
+                Update::none()
+            }
+            TableMsg::SaveNamePop => {
+                if let Some(input) = &mut self.save_input {
+                    input.pop();
+                }
+                Update::none()
+            }
+            TableMsg::CancelSaveQuery => {
+                self.save_input = None;
+                Update::none()
+            }
+            TableMsg::SaveQuery(name) => {
+                self.save_input = None;
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    return Update::none();
+                }
+                Self::save_query(name, self.sql_input.clone()).into()
+            }
+            TableMsg::QuerySaved(name, path) => {
+                self.query_name = Some(name);
+                TableMsg::Status(StatusMessage::info(format!("saved to {}", path.display()))).into()
+            }
+            TableMsg::QuerySaveFailed(e) => {
+                TableMsg::Status(StatusMessage::error(format!("saving the query failed: {}", e))).into()
+            }
+            TableMsg::Explain(analyze) => {
+                if self.sql_input.trim().is_empty() {
+                    return Update::none();
+                }
+                self.explain(analyze).into()
+            }
+            TableMsg::PlanLoaded(generation, lines) => {
+                if generation == self.plan_generation {
+                    self.plan = Some(Ok(lines));
+                }
+                Update::none()
+            }
+            TableMsg::PlanFailed(generation, e) => {
+                if generation == self.plan_generation {
+                    self.plan = Some(Err(e));
+                }
+                Update::none()
+            }
+            TableMsg::ScrollPlanBy(delta) => {
+                // Clamped in draw
+                self.plan_scroll = self.plan_scroll.saturating_add_signed(delta as isize);
+                Update::none()
+            }
+            TableMsg::ScrollPlanColsBy(delta) => {
+                self.plan_col_scroll = self.plan_col_scroll.saturating_add_signed(delta as isize);
+                Update::none()
+            }
+            TableMsg::ClosePlan => {
+                self.show_plan = false;
+                self.plan = None;
+                self.plan_generation += 1;
+                Update::none()
+            }
+            TableMsg::OpenQueries => Self::load_saved_queries().into(),
+            TableMsg::QueriesLoaded(queries) => {
+                self.saved_queries.open(queries);
+                Update::none()
+            }
+            TableMsg::QueriesLoadFailed(e) => {
+                TableMsg::Status(StatusMessage::error(format!("loading saved queries failed: {}", e))).into()
+            }
+            TableMsg::Queries(m) => self.saved_queries.update(m).map_auto(),
+            TableMsg::LoadQuery(query) => {
+                self.saved_queries.close();
+                self.sql_input = query.sql.trim_end().to_string();
+                self.query_name = Some(query.name);
+                self.sql_editing = true;
+                Update::none()
+            }
             TableMsg::ScrollRecordsBy(delta) => {
                 if matches!(self.focus, TableFocus::Records) {
-                    if delta < 0 {
-                        self.records_scroll = self.records_scroll.saturating_sub((-delta) as usize);
-                    } else if delta > 0 {
-                        self.records_scroll = self.records_scroll.saturating_add(delta as usize);
-                    }
+                    self.records_scroll = Self::scroll_by(self.records_scroll, delta, self.records_last().0);
+                }
+                Update::none()
+            }
+            TableMsg::ScrollRecordsHalfPage(direction) => {
+                if matches!(self.focus, TableFocus::Records) {
+                    let delta = Self::half_page(self.records_visible.get(), direction);
+                    self.records_scroll = Self::scroll_by(self.records_scroll, delta, self.records_last().0);
                 }
                 Update::none()
             }
@@ -209,24 +2326,44 @@ impl Component for TableComponent {
                 }
                 Update::none()
             }
+            TableMsg::StartGoto => {
+                self.goto_input = Some(String::new());
+                Update::none()
+            }
+            TableMsg::GotoPush(c) => {
+                if let Some(input) = self.goto_input.as_mut().filter(|_| c.is_ascii_digit()) {
+                    input.push(c);
+                }
+                Update::none()
+            }
+            TableMsg::GotoPop => {
+                if let Some(input) = &mut self.goto_input {
+                    input.pop();
+                }
+                Update::none()
+            }
+            TableMsg::ConfirmGoto => {
+                // Row numbers count from the table's first row, as in the title
+                if let Some(n) = self.goto_input.take().and_then(|input| input.parse::<usize>().ok()) {
+                    let first = if self.query_result { 0 } else { self.page_offset };
+                    self.records_scroll = n.saturating_sub(1).saturating_sub(first).min(self.records_last().0);
+                }
+                Update::none()
+            }
+            TableMsg::CancelGoto => {
+                self.goto_input = None;
+                Update::none()
+            }
             TableMsg::ScrollBottom => {
                 if matches!(self.focus, TableFocus::Records) {
-                    // Will be clamped in draw
-                    self.records_scroll = usize::MAX / 2;
+                    // The last row becomes current; draw keeps the window filled
+                    self.records_scroll = self.records_last().0;
                 }
                 Update::none()
             }
             TableMsg::ScrollColsBy(delta) => {
                 if matches!(self.focus, TableFocus::Records) {
-                    if delta < 0 {
-                        self.records_col_scroll = self
-                            .records_col_scroll
-                            .saturating_sub((-delta) as usize);
-                    } else if delta > 0 {
-                        self.records_col_scroll = self
-                            .records_col_scroll
-                            .saturating_add(delta as usize);
-                    }
+                    self.records_col_scroll = Self::scroll_by(self.records_col_scroll, delta, self.records_last().1);
                 }
                 Update::none()
             }
@@ -238,187 +2375,668 @@ impl Component for TableComponent {
             }
             TableMsg::ColsEnd => {
                 if matches!(self.focus, TableFocus::Records) {
-                    self.records_col_scroll = usize::MAX / 2;
+                    self.records_col_scroll = self.records_last().1;
                 }
                 Update::none()
             }
-            TableMsg::ScrollPropsBy(delta) => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    if delta < 0 {
-                        self.properties_scroll = self.properties_scroll.saturating_sub((-delta) as usize);
-                    } else if delta > 0 {
-                        self.properties_scroll = self.properties_scroll.saturating_add(delta as usize);
+            TableMsg::CycleAggregate => {
+                self.aggregate = self.aggregate.next();
+                Update::none()
+            }
+            TableMsg::ToggleRawValues => {
+                self.raw_values = !self.raw_values;
+                Update::none()
+            }
+            TableMsg::ToggleSearchCase => {
+                self.search_case_sensitive = !self.search_case_sensitive;
+                Update::none()
+            }
+            TableMsg::ToggleFitColumns => {
+                self.fit_columns = !self.fit_columns;
+                Update::none()
+            }
+            TableMsg::ToggleWrap => {
+                self.wrap = !self.wrap;
+                Update::none()
+            }
+            TableMsg::ToggleBookmark => self.toggle_bookmark().into(),
+            TableMsg::NextBookmark => self.jump_to_bookmark(true).into(),
+            TableMsg::PrevBookmark => self.jump_to_bookmark(false).into(),
+            TableMsg::StartEdit => {
+                if let Err(why) = self.start_edit() {
+                    self.records_status = Some(why);
+                }
+                Update::none()
+            }
+            TableMsg::EditPush(c) => {
+                if let Some(edit) = &mut self.edit {
+                    if std::mem::take(&mut edit.null) {
+                        edit.input.clear();
                     }
+                    edit.input.push(c);
                 }
                 Update::none()
             }
-            TableMsg::ScrollPropsTop => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    self.properties_scroll = 0;
+            TableMsg::EditPop => {
+                if let Some(edit) = &mut self.edit {
+                    if std::mem::take(&mut edit.null) {
+                        edit.input.clear();
+                    } else {
+                        edit.input.pop();
+                    }
                 }
                 Update::none()
             }
-            TableMsg::ScrollPropsBottom => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    self.properties_scroll = usize::MAX / 2;
+            TableMsg::EditSetNull => {
+                if let Some(edit) = &mut self.edit {
+                    edit.null = true;
                 }
                 Update::none()
             }
-            TableMsg::ScrollPropsColsBy(delta) => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    if delta < 0 {
-                        self.properties_col_scroll = self
-                            .properties_col_scroll
-                            .saturating_sub((-delta) as usize);
-                    } else if delta > 0 {
-                        self.properties_col_scroll = self
-                            .properties_col_scroll
-                            .saturating_add(delta as usize);
+            TableMsg::CommitEdit(conn) => {
+                let Some(edit) = self.edit.take() else {
+                    return Update::none();
+                };
+                if edit.null {
+                    let column = self.records.as_ref().and_then(|r| r.columns.get(edit.col));
+                    if let Some(why) = column.and_then(|c| null_refusal(self.properties.as_ref(), c)) {
+                        // Keep the editor open to type a value instead
+                        self.records_status = Some(why);
+                        self.edit = Some(edit);
+                        return Update::none();
                     }
                 }
+                // Leave an unchanged value alone; NULL shows as empty, so it is
+                // only written when set explicitly
+                let unchanged = !edit.null
+                    && self.records.as_ref().is_some_and(|r| r.rows[edit.row].get(edit.col) == Some(&edit.input));
+                if unchanged {
+                    return Update::none();
+                }
+                self.commit_edit(conn, edit).into()
+            }
+            TableMsg::CancelEdit => {
+                self.edit = None;
                 Update::none()
             }
-            TableMsg::PropsColsStart => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    self.properties_col_scroll = 0;
+            TableMsg::DeleteRow => {
+                let (Some(row), Some(conn)) = (self.current_row(), self.connection.clone()) else {
+                    return Update::none();
+                };
+                if let Some(why) = self.write_refusal() {
+                    self.records_status = Some(format!("cannot delete: {}", why));
+                    return Update::none();
+                }
+                let key = self.row_pk(row);
+                let prompt = format!(
+                    "Delete the row where {}?",
+                    key.iter().map(|(col, v)| format!("{} = {}", col, v)).collect::<Vec<_>>().join(", ")
+                );
+                TableMsg::Confirm(ConfirmRequest::new("Delete row", prompt, TableMsg::ConfirmDelete(conn, key))).into()
+            }
+            TableMsg::ConfirmDelete(conn, key) => self.delete_row(conn, key).into(),
+            TableMsg::StartInsert => {
+                if let Err(why) = self.start_insert() {
+                    self.records_status = Some(why);
                 }
                 Update::none()
             }
-            TableMsg::PropsColsEnd => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    self.properties_col_scroll = usize::MAX / 2;
+            TableMsg::InsertPush(c) => {
+                if let Some(form) = &mut self.insert_form {
+                    form.fields[form.selected].value.get_or_insert_with(String::new).push(c);
                 }
                 Update::none()
             }
-        }
-    }
-
-    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
-        use crossterm::event::KeyCode::*;
-
-        match key.code {
-            // Tab switching based on ARCHITECTURE.md
-            Char('1') => TableMsg::FocusRecords.into(),
-            Char('2') => TableMsg::FocusSQL.into(),
-            Char('3') => TableMsg::FocusProperties.into(),
-            // Back to DBList focus
-            Tab | Esc => TableMsg::BackToDBList.into(),
-            // Scrolling shortcuts: route based on focus
-            Up => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBy(-1).into()
-                } else {
-                    TableMsg::ScrollRecordsBy(-1).into()
+            TableMsg::InsertPop => {
+                if let Some(form) = &mut self.insert_form {
+                    // Backspace on an empty value goes back to "not set"
+                    let value = &mut form.fields[form.selected].value;
+                    match value {
+                        Some(v) if !v.is_empty() => {
+                            v.pop();
+                        }
+                        _ => *value = None,
+                    }
                 }
+                Update::none()
             }
-            Down => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBy(1).into()
-                } else {
-                    TableMsg::ScrollRecordsBy(1).into()
+            TableMsg::InsertFieldBy(delta) => {
+                if let Some(form) = &mut self.insert_form {
+                    let last = form.fields.len().saturating_sub(1);
+                    form.selected = form.selected.saturating_add_signed(delta as isize).min(last);
                 }
+                Update::none()
             }
-            PageUp => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBy(-10).into()
-                } else {
-                    TableMsg::ScrollRecordsBy(-10).into()
+            TableMsg::InsertRow(conn) => {
+                let Some(form) = &mut self.insert_form else {
+                    return Update::none();
+                };
+                let missing: Vec<&str> = form
+                    .fields
+                    .iter()
+                    .filter(|f| f.required() && f.value.is_none())
+                    .map(|f| f.column.name.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    form.error = Some(format!("required: {}", missing.join(", ")));
+                    return Update::none();
+                }
+                match self.insert_form.take() {
+                    Some(form) => self.insert_row(conn, form).into(),
+                    None => Update::none(),
                 }
             }
-            PageDown => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBy(10).into()
-                } else {
-                    TableMsg::ScrollRecordsBy(10).into()
+            TableMsg::CancelInsert => {
+                self.insert_form = None;
+                Update::none()
+            }
+            TableMsg::RowsWritten(generation, mut recs, note) => {
+                if generation != self.records_generation {
+                    return Update::none();
                 }
+                self.records_loading = false;
+                if let Some(props) = &self.properties {
+                    recs.set_kinds(&props.columns);
+                }
+                // Keep the cursor where it was
+                self.records = Some(recs);
+                self.records_error = None;
+                self.records_status = Some(note);
+                Update::none()
+            }
+            TableMsg::WriteFailed(e) => {
+                self.records_loading = false;
+                self.records_status = Some(format!("write failed: {}", e));
+                TableMsg::Status(StatusMessage::error(format!("write failed: {}", e))).into()
+            }
+            TableMsg::InspectCell => {
+                self.show_cell_popup = self.current_cell().is_some();
+                self.cell_popup_scroll = 0;
+                Update::none()
+            }
+            TableMsg::ScrollCellPopupBy(delta) => {
+                // Clamped in draw
+                self.cell_popup_scroll = self.cell_popup_scroll.saturating_add_signed(delta as isize);
+                Update::none()
+            }
+            TableMsg::CloseCellPopup => {
+                self.show_cell_popup = false;
+                Update::none()
+            }
+            TableMsg::ExportCsv => self
+                .export(|recs, dir, table| export::write_csv(recs, dir, table, export::DEFAULT_CSV_DELIMITER))
+                .into(),
+            TableMsg::ExportJson => self
+                .export(|recs, dir, table| export::write_json(recs, dir, table, export::JsonValues::Strings))
+                .into(),
+            TableMsg::Exported(path) => {
+                self.records_status = Some(format!("exported to {}", path.display()));
+                TableMsg::Status(StatusMessage::info(format!("exported to {}", path.display()))).into()
+            }
+            TableMsg::ExportFailed(e) => {
+                self.records_status = Some(format!("export failed: {}", e));
+                TableMsg::Status(StatusMessage::error(format!("export failed: {}", e))).into()
             }
-            Home => {
+            TableMsg::ScrollPropsBy(delta) => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsTop.into()
-                } else {
-                    TableMsg::ScrollTop.into()
+                    self.properties_scroll = Self::scroll_by(self.properties_scroll, delta, self.properties_last().0);
                 }
+                Update::none()
             }
-            End => {
+            TableMsg::ScrollPropsHalfPage(direction) => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBottom.into()
-                } else {
-                    TableMsg::ScrollBottom.into()
+                    let delta = Self::half_page(self.properties_visible.get(), direction);
+                    self.properties_scroll = Self::scroll_by(self.properties_scroll, delta, self.properties_last().0);
                 }
+                Update::none()
             }
-            // Horizontal column paging: route to Records or Properties based on focus
-            Left | Char('h') => {
+            TableMsg::ScrollPropsTop => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    // Shift properties columns left by 1
-                    TableMsg::ScrollPropsColsBy(-1).into()
-                } else {
-                    TableMsg::ScrollColsBy(-1).into()
+                    self.properties_scroll = 0;
                 }
+                Update::none()
             }
-            Right | Char('l') => {
+            TableMsg::ScrollPropsBottom => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsColsBy(1).into()
-                } else {
-                    TableMsg::ScrollColsBy(1).into()
+                    self.properties_scroll = self.properties_last().0;
                 }
+                Update::none()
             }
-            // Jump columns by 5 using '[' and ']'
-            Char('[') => {
+            TableMsg::ScrollPropsColsBy(delta) => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsColsBy(-5).into()
-                } else {
-                    TableMsg::ScrollColsBy(-5).into()
+                    self.properties_col_scroll =
+                        Self::scroll_by(self.properties_col_scroll, delta, self.properties_last().1);
                 }
+                Update::none()
             }
-            Char(']') => {
+            TableMsg::PropsColsStart => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsColsBy(5).into()
-                } else {
-                    TableMsg::ScrollColsBy(5).into()
+                    self.properties_col_scroll = 0;
                 }
+                Update::none()
             }
-            // Go to first/last column with Ctrl-A / Ctrl-E
-            Char('a') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+            TableMsg::PropsColsEnd => {
                 if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::PropsColsStart.into()
-                } else {
-                    TableMsg::ColsStart.into()
+                    self.properties_col_scroll = self.properties_last().1;
                 }
+                Update::none()
             }
-            Char('e') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::PropsColsEnd.into()
-                } else {
-                    TableMsg::ColsEnd.into()
-                }
+        }
+    }
+
+    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        use crossterm::event::KeyCode::*;
+
+        // The history browser and saved query picker take all keys while open
+        if self.history.is_open() {
+            return self.history.handle_key(key).map_auto();
+        }
+        if self.saved_queries.is_open() {
+            return self.saved_queries.handle_key(key).map_auto();
+        }
+
+        if self.save_input.is_some() {
+            return match key.code {
+                Enter => TableMsg::SaveQuery(self.save_input.clone().unwrap_or_default()).into(),
+                Esc => TableMsg::CancelSaveQuery.into(),
+                Backspace => TableMsg::SaveNamePop.into(),
+                Char(c) => TableMsg::SaveNamePush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        if self.insert_form.is_some() {
+            return match key.code {
+                Enter => match &self.connection {
+                    Some(conn) => TableMsg::InsertRow(conn.clone()).into(),
+                    None => TableMsg::CancelInsert.into(),
+                },
+                Esc => TableMsg::CancelInsert.into(),
+                Up | BackTab => TableMsg::InsertFieldBy(-1).into(),
+                Down | Tab => TableMsg::InsertFieldBy(1).into(),
+                Backspace => TableMsg::InsertPop.into(),
+                Char(c) => TableMsg::InsertPush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        if self.edit.is_some() {
+            return match key.code {
+                Enter => match &self.connection {
+                    Some(conn) => TableMsg::CommitEdit(conn.clone()).into(),
+                    None => TableMsg::CancelEdit.into(),
+                },
+                Esc => TableMsg::CancelEdit.into(),
+                Backspace => TableMsg::EditPop.into(),
+                Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => TableMsg::EditSetNull.into(),
+                Char(c) => TableMsg::EditPush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        if self.where_input.is_some() {
+            return match key.code {
+                Enter => match &self.connection {
+                    Some(conn) => TableMsg::ApplyWhere(conn.clone()).into(),
+                    None => TableMsg::CancelWhere.into(),
+                },
+                Esc => TableMsg::CancelWhere.into(),
+                Backspace => TableMsg::WherePop.into(),
+                Char(c) => TableMsg::WherePush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        if self.search_input.is_some() {
+            return match key.code {
+                Enter => TableMsg::ConfirmSearch.into(),
+                Esc => TableMsg::CancelSearch.into(),
+                Backspace => TableMsg::SearchPop.into(),
+                Char(c) => TableMsg::SearchPush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        if self.goto_input.is_some() {
+            return match key.code {
+                Enter => TableMsg::ConfirmGoto.into(),
+                Esc => TableMsg::CancelGoto.into(),
+                Backspace => TableMsg::GotoPop.into(),
+                Char(c) => TableMsg::GotoPush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        let is = |action| self.keymap.is(action, &key);
+        // The plan popup is modal
+        if self.show_plan {
+            let msg = if is(Action::Back) || is(Action::Select) {
+                TableMsg::ClosePlan
+            } else if is(Action::MoveUp) {
+                TableMsg::ScrollPlanBy(-1)
+            } else if is(Action::MoveDown) {
+                TableMsg::ScrollPlanBy(1)
+            } else if is(Action::PageUp) {
+                TableMsg::ScrollPlanBy(-10)
+            } else if is(Action::PageDown) {
+                TableMsg::ScrollPlanBy(10)
+            } else if is(Action::ScrollLeft) {
+                TableMsg::ScrollPlanColsBy(-8)
+            } else if is(Action::ScrollRight) {
+                TableMsg::ScrollPlanColsBy(8)
+            } else {
+                return Update::none();
+            };
+            return msg.into();
+        }
+        // History, saved queries and plans work even while typing in the editor
+        if matches!(self.focus, TableFocus::SQL) {
+            if is(Action::Explain) {
+                return TableMsg::Explain(false).into();
+            } else if is(Action::ExplainAnalyze) {
+                return TableMsg::Explain(true).into();
+            } else if is(Action::QueryHistory) {
+                return TableMsg::OpenHistory.into();
+            } else if is(Action::SaveQuery) {
+                return TableMsg::StartSaveQuery.into();
+            } else if is(Action::OpenQuery) {
+                return TableMsg::OpenQueries.into();
             }
-            Char('k') => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBy(-1).into()
-                } else {
-                    TableMsg::ScrollRecordsBy(-1).into()
-                }
+        }
+        // Runs the editor's statement even while typing in it
+        if matches!(self.focus, TableFocus::SQL) && is(Action::RunQuery) {
+            let sql = self.sql_input.clone();
+            if !may_write(&sql) {
+                return TableMsg::RunQuery(sql).into();
             }
-            Char('j') => {
-                if matches!(self.focus, TableFocus::Properties) {
-                    TableMsg::ScrollPropsBy(1).into()
+            let prompt = format!("This statement may change data. Run it?\n\n{}", sql.trim());
+            return TableMsg::Confirm(ConfirmRequest::new("Run statement", prompt, TableMsg::RunQuery(sql))).into();
+        }
+
+        if self.sql_editing && matches!(self.focus, TableFocus::SQL) {
+            return match key.code {
+                Esc => TableMsg::StopEditSql.into(),
+                Enter => TableMsg::SqlNewline.into(),
+                Backspace => TableMsg::SqlPop.into(),
+                Char(c) => TableMsg::SqlPush(c).into(),
+                _ => Update::none(),
+            };
+        }
+
+        // The cell popup is modal
+        if self.show_cell_popup {
+            let msg = if is(Action::Back) || is(Action::Select) {
+                TableMsg::CloseCellPopup
+            } else if is(Action::MoveUp) {
+                TableMsg::ScrollCellPopupBy(-1)
+            } else if is(Action::MoveDown) {
+                TableMsg::ScrollCellPopupBy(1)
+            } else if is(Action::PageUp) {
+                TableMsg::ScrollCellPopupBy(-10)
+            } else if is(Action::PageDown) {
+                TableMsg::ScrollCellPopupBy(10)
+            } else {
+                return Update::none();
+            };
+            return msg.into();
+        }
+
+        // Scrolling keys act on the Properties grid there and on the records elsewhere
+        let props = matches!(self.focus, TableFocus::Properties);
+        let records = matches!(self.focus, TableFocus::Records);
+        let scroll_rows = |n| if props { TableMsg::ScrollPropsBy(n) } else { TableMsg::ScrollRecordsBy(n) };
+        let scroll_cols = |n| if props { TableMsg::ScrollPropsColsBy(n) } else { TableMsg::ScrollColsBy(n) };
+        // Actions that need the connection do nothing before a table is opened
+        let with_conn = |make: fn(Connection) -> TableMsg| match &self.connection {
+            Some(conn) => make(conn.clone()).into(),
+            None => Update::none(),
+        };
+
+        let msg = if is(Action::FocusRecords) {
+            TableMsg::FocusRecords
+        } else if is(Action::FocusSql) {
+            TableMsg::FocusSQL
+        } else if is(Action::FocusProperties) {
+            TableMsg::FocusProperties
+        } else if is(Action::Back) || is(Action::SwitchPanel) {
+            TableMsg::BackToDBList
+        } else if is(Action::MoveUp) {
+            scroll_rows(-1)
+        } else if is(Action::MoveDown) {
+            scroll_rows(1)
+        } else if is(Action::PageUp) {
+            scroll_rows(-10)
+        } else if is(Action::PageDown) {
+            scroll_rows(10)
+        } else if is(Action::HalfPageUp) {
+            if props { TableMsg::ScrollPropsHalfPage(-1) } else { TableMsg::ScrollRecordsHalfPage(-1) }
+        } else if is(Action::HalfPageDown) {
+            if props { TableMsg::ScrollPropsHalfPage(1) } else { TableMsg::ScrollRecordsHalfPage(1) }
+        } else if is(Action::MoveTop) {
+            if props { TableMsg::ScrollPropsTop } else { TableMsg::ScrollTop }
+        } else if is(Action::MoveBottom) {
+            if props { TableMsg::ScrollPropsBottom } else { TableMsg::ScrollBottom }
+        } else if is(Action::ScrollLeft) {
+            scroll_cols(-1)
+        } else if is(Action::ScrollRight) {
+            scroll_cols(1)
+        } else if is(Action::JumpLeft) {
+            scroll_cols(-5)
+        } else if is(Action::JumpRight) {
+            scroll_cols(5)
+        } else if is(Action::FirstColumn) {
+            if props { TableMsg::PropsColsStart } else { TableMsg::ColsStart }
+        } else if is(Action::LastColumn) {
+            if props { TableMsg::PropsColsEnd } else { TableMsg::ColsEnd }
+        } else if records {
+            // With an active search NextMatch/PrevMatch take precedence over paging
+            if self.search.is_some() && is(Action::NextMatch) {
+                TableMsg::NextMatch
+            } else if self.search.is_some() && is(Action::PrevMatch) {
+                TableMsg::PrevMatch
+            } else if is(Action::ExportCsv) {
+                TableMsg::ExportCsv
+            } else if is(Action::ExportJson) {
+                TableMsg::ExportJson
+            } else if is(Action::CopyMarkdown) {
+                TableMsg::CopyMarkdown
+            } else if is(Action::Search) {
+                TableMsg::StartSearch
+            } else if is(Action::ToggleSearchCase) {
+                TableMsg::ToggleSearchCase
+            } else if is(Action::GotoRow) {
+                TableMsg::StartGoto
+            } else if is(Action::NextPage) {
+                return with_conn(TableMsg::NextPage);
+            } else if is(Action::PrevPage) {
+                return with_conn(TableMsg::PrevPage);
+            } else if is(Action::EditWhere) {
+                TableMsg::EditWhere
+            } else if is(Action::EditCell) {
+                TableMsg::StartEdit
+            } else if is(Action::DeleteRow) {
+                TableMsg::DeleteRow
+            } else if is(Action::InsertRow) {
+                TableMsg::StartInsert
+            } else if is(Action::CycleAggregate) {
+                TableMsg::CycleAggregate
+            } else if is(Action::ToggleRawValues) {
+                TableMsg::ToggleRawValues
+            } else if is(Action::ToggleFitColumns) {
+                TableMsg::ToggleFitColumns
+            } else if is(Action::ToggleWrap) {
+                TableMsg::ToggleWrap
+            } else if is(Action::ToggleBookmark) {
+                TableMsg::ToggleBookmark
+            } else if is(Action::NextBookmark) {
+                TableMsg::NextBookmark
+            } else if is(Action::PrevBookmark) {
+                TableMsg::PrevBookmark
+            } else if is(Action::CountRows) {
+                // Force an exact row count for the table
+                return with_conn(TableMsg::CountRowsExact);
+            } else if is(Action::Select) {
+                TableMsg::InspectCell
+            } else {
+                return Update::none();
+            }
+        } else if props {
+            if is(Action::ToggleIndexes) {
+                return with_conn(TableMsg::ToggleIndexes);
+            } else if is(Action::ToggleForeignKeys) {
+                return with_conn(TableMsg::ToggleForeignKeys);
+            } else if is(Action::ToggleDdl) {
+                return with_conn(TableMsg::ToggleDdl);
+            } else if is(Action::Copy) && self.props_view == PropsView::Ddl {
+                TableMsg::CopyDdl
+            } else if is(Action::Select) && self.props_view == PropsView::ForeignKeys {
+                TableMsg::FollowForeignKey
+            } else {
+                return Update::none();
+            }
+        } else if is(Action::EditSql) || is(Action::Select) {
+            TableMsg::EditSql
+        } else if is(Action::OpenCli) {
+            return with_conn(TableMsg::LaunchSQLCli);
+        } else {
+            return Update::none();
+        };
+        msg.into()
+    }
+
+    fn keybindings(&self) -> Vec<(String, &'static str)> {
+        if self.show_plan {
+            return self.keymap.help(&[
+                (Action::MoveUp, "Scroll up"),
+                (Action::MoveDown, "Scroll down"),
+                (Action::PageUp, "Scroll up a page"),
+                (Action::PageDown, "Scroll down a page"),
+                (Action::ScrollLeft, "Scroll left"),
+                (Action::ScrollRight, "Scroll right"),
+                (Action::Back, "Close"),
+            ]);
+        }
+        if self.show_cell_popup {
+            return self.keymap.help(&[
+                (Action::MoveUp, "Scroll up"),
+                (Action::MoveDown, "Scroll down"),
+                (Action::PageUp, "Scroll up a page"),
+                (Action::PageDown, "Scroll down a page"),
+                (Action::Back, "Close"),
+                (Action::Select, "Close"),
+            ]);
+        }
+        let mut keys = self.keymap.help(&[
+            (Action::FocusRecords, "Records tab"),
+            (Action::FocusSql, "SQL tab"),
+            (Action::FocusProperties, "Properties tab"),
+            (Action::Back, "Back to the tree"),
+            (Action::SwitchPanel, "Back to the tree"),
+        ]);
+        let scrolling = [
+            (Action::MoveUp, "Scroll up"),
+            (Action::MoveDown, "Scroll down"),
+            (Action::PageUp, "Scroll up a page"),
+            (Action::PageDown, "Scroll down a page"),
+            (Action::HalfPageUp, "Scroll up half a page"),
+            (Action::HalfPageDown, "Scroll down half a page"),
+            (Action::MoveTop, "First row"),
+            (Action::MoveBottom, "Last row"),
+            (Action::ScrollLeft, "Scroll columns left"),
+            (Action::ScrollRight, "Scroll columns right"),
+            (Action::JumpLeft, "Scroll 5 columns left"),
+            (Action::JumpRight, "Scroll 5 columns right"),
+            (Action::FirstColumn, "First column"),
+            (Action::LastColumn, "Last column"),
+        ];
+        match self.focus {
+            TableFocus::Records => {
+                keys.extend(self.keymap.help(&scrolling));
+                keys.extend(self.keymap.help(if self.search.is_some() {
+                    &[(Action::NextMatch, "Next match"), (Action::PrevMatch, "Previous match")]
                 } else {
-                    TableMsg::ScrollRecordsBy(1).into()
-                }
+                    &[(Action::NextPage, "Next page"), (Action::PrevPage, "Previous page")]
+                }));
+                keys.extend(self.keymap.help(&[
+                    (Action::Search, "Search the loaded rows"),
+                    (Action::ToggleSearchCase, "Case-sensitive / insensitive search"),
+                    (Action::GotoRow, "Go to a row number"),
+                    (Action::EditWhere, "Filter rows (WHERE)"),
+                    (Action::Select, "Show the full cell value"),
+                    (Action::EditCell, "Edit the cell"),
+                    (Action::DeleteRow, "Delete the row"),
+                    (Action::InsertRow, "Insert a row"),
+                    (Action::CycleAggregate, "Cycle the sum / avg footer"),
+                    (Action::ToggleRawValues, "Formatted / raw values"),
+                    (Action::ToggleFitColumns, "Fitted / fixed column widths"),
+                    (Action::ToggleWrap, "Wrap long values"),
+                    (Action::ToggleBookmark, "Bookmark the row"),
+                    (Action::NextBookmark, "Next bookmark"),
+                    (Action::PrevBookmark, "Previous bookmark"),
+                    (Action::CountRows, "Exact row count"),
+                    (Action::ExportCsv, "Export to CSV"),
+                    (Action::ExportJson, "Export to JSON"),
+                    (Action::CopyMarkdown, "Copy the rows as a Markdown table"),
+                ]));
             }
-            Enter => {
-                if matches!(self.focus, TableFocus::SQL) {
-                    if let Some(conn) = &self.connection {
-                        TableMsg::LaunchSQLCli(conn.clone()).into()
-                    } else {
-                        Update::none()
+            TableFocus::SQL => keys.extend(self.keymap.help(&[
+                (Action::EditSql, "Edit the statement"),
+                (Action::Select, "Edit the statement"),
+                (Action::RunQuery, "Run the statement"),
+                (Action::Explain, "Show the statement's plan"),
+                (Action::ExplainAnalyze, "Run the statement and show its plan with timings"),
+                (Action::QueryHistory, "Browse the query history"),
+                (Action::SaveQuery, "Save the statement under a name"),
+                (Action::OpenQuery, "Load a saved query"),
+                (Action::OpenCli, "Open the external CLI"),
+            ])),
+            TableFocus::Properties => {
+                keys.extend(self.keymap.help(&scrolling));
+                keys.extend(self.keymap.help(&[
+                    (Action::ToggleIndexes, "Toggle indexes"),
+                    (Action::ToggleForeignKeys, "Toggle foreign keys"),
+                    (Action::ToggleDdl, "Toggle the CREATE statement"),
+                ]));
+                match self.props_view {
+                    PropsView::ForeignKeys => {
+                        keys.extend(self.keymap.help(&[(Action::Select, "Open the referenced table")]))
                     }
-                } else {
-                    Update::none()
+                    PropsView::Ddl => keys.extend(self.keymap.help(&[(Action::Copy, "Copy it to the clipboard")])),
+                    PropsView::Columns | PropsView::Indexes => {}
                 }
             }
-            _ => Update::none(),
         }
+        keys
+    }
+
+    fn handle_mouse(&self, event: MouseEvent) -> Update<Self::Msg> {
+        let delta = match event.kind {
+            MouseEventKind::ScrollUp => -MOUSE_SCROLL_ROWS,
+            MouseEventKind::ScrollDown => MOUSE_SCROLL_ROWS,
+            _ => return Update::none(),
+        };
+        let msg = if self.show_plan {
+            TableMsg::ScrollPlanBy(delta)
+        } else if self.show_cell_popup {
+            TableMsg::ScrollCellPopupBy(delta)
+        } else if matches!(self.focus, TableFocus::Properties) {
+            TableMsg::ScrollPropsBy(delta)
+        } else {
+            TableMsg::ScrollRecordsBy(delta)
+        };
+        msg.into()
+    }
+
+    fn is_typing(&self) -> bool {
+        self.history.is_typing()
+            || self.saved_queries.is_typing()
+            || self.save_input.is_some()
+            || self.insert_form.is_some()
+            || self.edit.is_some()
+            || self.where_input.is_some()
+            || self.search_input.is_some()
+            || self.goto_input.is_some()
+            || (self.sql_editing && matches!(self.focus, TableFocus::SQL))
     }
 
     fn draw(&self, f: &mut Frame, area: Rect, focused: bool) {
@@ -437,24 +3055,25 @@ impl Component for TableComponent {
                 .split(area);
 
             // Tab bar
-            let tab_style = if focused {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default().fg(Color::White)
-            };
+            let tab_style = self.theme.border(focused);
 
+            let mut tabs_block = Block::default()
+                .title(match self.kind {
+                    TableKind::Table => table_info.label(),
+                    TableKind::View => format!("{} (view)", table_info.label()),
+                })
+                .borders(Borders::ALL)
+                .border_style(tab_style);
+            if let Some(info) = &self.server_info {
+                tabs_block = tabs_block.title(Title::from(format!(" {} ", info)).alignment(Alignment::Right));
+            }
             let tabs_widget = Tabs::new(tabs)
-                .block(
-                    Block::default()
-                        .title(format!("{}.{}", table_info.database, table_info.table))
-                        .borders(Borders::ALL)
-                        .border_style(tab_style),
-                )
+                .block(tabs_block)
                 .select(selected_tab)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(self.theme.unfocused_border))
                 .highlight_style(
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(self.theme.highlight)
                         .add_modifier(Modifier::BOLD),
                 );
 
@@ -462,190 +3081,518 @@ impl Component for TableComponent {
 
             // Content area
             let content_area = chunks[1];
-            let content_style = if focused {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default().fg(Color::White)
-            };
+            let content_style = self.theme.border(focused);
 
             match self.focus {
                 TableFocus::Records => {
+                    // Cell editor, or the WHERE input while editing or applied, above the grid
+                    let content_area = if self.edit.is_some() || self.where_input.is_some() || self.filter.is_some() {
+                        let parts = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(3), Constraint::Min(0)])
+                            .split(content_area);
+                        let (text, title, style) = match (&self.edit, &self.where_input) {
+                            (Some(edit), _) => {
+                                let column = self.records.as_ref().map_or("", |r| r.columns[edit.col].as_str());
+                                let text = if edit.null { "NULL".to_string() } else { format!("{}_", edit.input) };
+                                (
+                                    text,
+                                    format!("SET {}  (Enter: save; Ctrl-N: NULL; Esc: cancel)", column),
+                                    self.theme.border(true),
+                                )
+                            }
+                            (None, Some(input)) => (
+                                format!("{}_", input),
+                                "WHERE  (Enter: apply, empty clears; Esc: cancel)".to_string(),
+                                self.theme.border(true),
+                            ),
+                            (None, None) => (
+                                self.filter.clone().unwrap_or_default(),
+                                format!("WHERE  ({}: edit)", self.keymap.describe(Action::EditWhere)),
+                                self.theme.border(false),
+                            ),
+                        };
+                        let where_box = Paragraph::new(text).block(
+                            Block::default().title(title).borders(Borders::ALL).border_style(style),
+                        );
+                        f.render_widget(where_box, parts[0]);
+                        parts[1]
+                    } else {
+                        content_area
+                    };
                     if let Some(recs) = &self.records {
                         use ratatui::widgets::{Cell as TuiCell, Row, Table as TuiTable};
-                        // Determine visible columns based on width and horizontal scroll
-                        let border_cols = 2u16; // left+right border
-                        let col_width: u16 = 16; // fixed column width for rendering
-                        let avail_w = content_area.width.saturating_sub(border_cols);
-                        let visible_cols = std::cmp::max(1u16, avail_w / col_width) as usize;
-                        let total_cols = recs.columns.len();
-                        let max_col_start = total_cols.saturating_sub(visible_cols);
-                        let col_start = self.records_col_scroll.min(max_col_start);
-                        let col_end = (col_start + visible_cols).min(total_cols);
-
-                        let header = Row::new(recs.columns[col_start..col_end].iter().map(|c| {
-                            TuiCell::from(c.as_str())
-                                .style(Style::default().add_modifier(Modifier::BOLD))
-                        }));
                         // Compute visible rows slice based on area height and scroll offset
                         let border_rows = 2u16; // top+bottom border
                         let header_rows = 1u16; // header row
+                        let footer_rows = u16::from(self.aggregate != Aggregate::Off);
                         let avail = content_area
                             .height
                             .saturating_sub(border_rows)
-                            .saturating_sub(header_rows);
+                            .saturating_sub(header_rows)
+                            .saturating_sub(footer_rows);
                         let visible_count = usize::try_from(avail).unwrap_or(0);
                         let total = recs.rows.len();
                         let max_start = total.saturating_sub(visible_count);
                         let start = self.records_scroll.min(max_start);
                         let end = start.saturating_add(visible_count).min(total);
-                        let rows = recs.rows[start..end]
-                            .iter()
-                            .map(|r| Row::new(r[col_start..col_end].iter().map(|v| v.as_str())));
-                        let widths: Vec<Constraint> = (col_start..col_end)
-                            .map(|_| Constraint::Length(col_width))
+
+                        // Determine visible columns based on their widths and horizontal scroll
+                        let border_cols = 2u16; // left+right border
+                        let gutter_w = 2u16; // current-row / bookmark marker plus column spacing
+                        let avail_w = content_area.width.saturating_sub(border_cols).saturating_sub(gutter_w);
+                        let total_cols = recs.columns.len();
+                        let col_widths = if self.fit_columns {
+                            self.fitted_widths(recs, start..end)
+                        } else {
+                            vec![FIXED_COLUMN_WIDTH; total_cols]
+                        };
+                        // Frozen columns stay at the left; the others scroll in the space to their right
+                        let frozen = frozen_count(self.frozen_columns, &col_widths, avail_w);
+                        let frozen_w: u16 = col_widths[..frozen].iter().map(|&w| w + 1).sum();
+                        let (col_start, col_end) = column_window(
+                            &col_widths[frozen..],
+                            self.records_col_scroll.saturating_sub(frozen),
+                            avail_w.saturating_sub(frozen_w),
+                        );
+                        let (col_start, col_end) = (col_start + frozen, col_end + frozen);
+                        let shown: Vec<usize> = (0..frozen).chain(col_start..col_end).collect();
+
+                        // Wrapped rows are taller, so fewer of them fit: a window of the
+                        // one-line rows above, whose fitted widths still hold
+                        let heights = if self.wrap {
+                            self.wrapped_heights(recs, &shown, &col_widths)
+                        } else {
+                            vec![1; total]
+                        };
+                        let (start, end) = if !self.wrap {
+                            (start, end)
+                        } else if avail == 0 {
+                            (0, 0)
+                        } else {
+                            line_window(&heights, self.records_scroll, avail)
+                        };
+                        self.records_visible.set(if self.wrap { end - start } else { visible_count });
+
+                        // Primary-key columns are underlined; a query result's columns aren't the table's
+                        let pk: HashSet<usize> = if self.query_result {
+                            HashSet::new()
+                        } else {
+                            self.pk_columns().into_iter().collect()
+                        };
+                        let header = Row::new(std::iter::once(TuiCell::from("")).chain(
+                            shown.iter().map(|&col| {
+                                let mut line = Line::from(recs.columns[col].as_str());
+                                if !self.raw_values && recs.kind(col).right_aligned() {
+                                    line = line.alignment(Alignment::Right);
+                                }
+                                let mut style = Style::default().fg(self.theme.header).add_modifier(Modifier::BOLD);
+                                if pk.contains(&col) {
+                                    style = style.add_modifier(Modifier::UNDERLINED);
+                                }
+                                TuiCell::from(line).style(style)
+                            }),
+                        ));
+                        let current = self.current_row();
+                        let current_col = self.current_cell().map(|(_, c)| c);
+                        let needle = self.search_needle().unwrap_or_default();
+                        let rows = recs.rows[start..end].iter().enumerate().map(|(i, r)| {
+                            let marker = match (self.is_bookmarked(r), current == Some(start + i)) {
+                                (true, _) => TuiCell::from("*").style(Style::default().fg(Color::Magenta)),
+                                (false, true) => TuiCell::from(">"),
+                                (false, false) => TuiCell::from(""),
+                            };
+                            let is_current = current == Some(start + i);
+                            let row = Row::new(std::iter::once(marker).chain(
+                                shown.iter().map(|&col| {
+                                    // A row may be narrower than the header when the
+                                    // backend's column list and result disagree
+                                    let v = r.get(col).map_or("", String::as_str);
+                                    let mut style = Style::default();
+                                    if self.cell_matches(&needle, v) {
+                                        style = style.fg(Color::Black).bg(Color::Yellow);
+                                    }
+                                    if is_current && current_col == Some(col) {
+                                        style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+                                    }
+                                    let value = self.display_value(recs, col, v);
+                                    let right = !self.raw_values && recs.kind(col).right_aligned();
+                                    if self.wrap {
+                                        let mut text = wrap_cell_capped(&value, col_widths[col], self.wrap_lines);
+                                        if right {
+                                            text.lines =
+                                                text.lines.into_iter().map(|l| l.alignment(Alignment::Right)).collect();
+                                        }
+                                        return TuiCell::from(text).style(style);
+                                    }
+                                    let mut line = Line::from(Span::raw(value));
+                                    if right {
+                                        line = line.alignment(Alignment::Right);
+                                    }
+                                    TuiCell::from(line).style(style)
+                                }),
+                            )).height(heights[start + i]);
+                            if is_current {
+                                row.style(Style::default().add_modifier(Modifier::REVERSED))
+                            } else {
+                                row
+                            }
+                        });
+                        let widths: Vec<Constraint> = std::iter::once(Constraint::Length(1))
+                            .chain(shown.iter().map(|&col| Constraint::Length(col_widths[col])))
                             .collect();
-                        let title = if total > 0 && visible_count > 0 {
+                        // Loaded window vs. the table's real size, e.g. "1-20 / 1-200 loaded, ≈1200000 total"
+                        // The row count is for the whole table, so it is left out while filtered
+                        let row_count = self.row_count.filter(|_| self.filter.is_none());
+                        let table_total = match row_count {
+                            Some(count) => format!(", {} total", count),
+                            None => String::new(),
+                        };
+                        let count_hint = match row_count {
+                            Some(count) if count.is_estimate() => {
+                                format!(" ({}: exact count)", self.keymap.describe(Action::CountRows))
+                            }
+                            _ => String::new(),
+                        };
+                        let capped = if recs.truncated {
+                            format!(" (memory cap: only {} rows loaded)", total)
+                        } else {
+                            String::new()
+                        };
+                        let footer_label = match self.aggregate {
+                            Aggregate::Off => String::new(),
+                            agg => format!(", footer: {} of loaded rows", agg.label()),
+                        };
+                        let first = self.page_offset;
+                        let title = if self.query_result {
+                            format!(
+                                "Query result  rows [{}-{} / {}]{}, cols [{}-{} / {}]{}  (↑/↓, PgUp/PgDn, Home/End; ←/→, [/], Ctrl-A/E; w back to table; / find; Enter cell; Ctrl-S csv, Ctrl-O json)",
+                                (start + 1).min(end), end, total, capped,
+                                col_start.saturating_add(1), col_end, total_cols, footer_label
+                            )
+                        } else if total > 0 && visible_count > 0 {
                             format!(
-                                "Records  rows [{}-{} / {}], cols [{}-{} / {}]  (↑/↓, PgUp/PgDn, Home/End; ←/→, [/], Ctrl-A/E)",
-                                start.saturating_add(1), end, total,
-                                col_start.saturating_add(1), col_end, total_cols
+                                "Records  rows [{}-{} / {}-{} loaded{}]{}{}, cols [{}-{} / {}]{}  (↑/↓, PgUp/PgDn, Home/End; ←/→, [/], Ctrl-A/E; n/p page; w where; / find; e edit, d delete, i insert; Enter cell; Ctrl-S csv, Ctrl-O json)",
+                                first + start + 1, first + end, first + 1, first + total, table_total, count_hint, capped,
+                                col_start.saturating_add(1), col_end, total_cols, footer_label
                             )
                         } else {
                             "Records".to_string()
                         };
-                        let table = TuiTable::new(rows, widths).header(header).block(
-                            Block::default()
-                                .title(title)
-                                .borders(Borders::ALL)
-                                .border_style(content_style),
-                        );
+                        // The row number being typed leads the title
+                        let title = match &self.goto_input {
+                            Some(input) => format!("Go to row: {}_  (Enter: jump; Esc: cancel)  {}", input, title),
+                            None => title,
+                        };
+                        // Another page or a reload is on its way
+                        let title = if self.records_loading {
+                            format!("{} {}", self.spinner(), title)
+                        } else {
+                            title
+                        };
+                        let footer_widths = widths.clone();
+                        let mut block = Block::default()
+                            .title(title)
+                            .borders(Borders::ALL)
+                            .border_style(content_style);
+                        if let Some(status) = &self.records_status {
+                            block = block.title_bottom(format!(" {} ", status));
+                        }
+                        if self.search_input.is_some() || self.search.is_some() {
+                            let text = match &self.search_input {
+                                Some(input) => format!("/{}_", input),
+                                None => format!("/{}", self.search.as_deref().unwrap_or("")),
+                            };
+                            let case = if self.search_case_sensitive { " (match case)" } else { "" };
+                            let count = self.search_matches().len();
+                            let hint = if self.search_input.is_some() {
+                                "Enter: find, Esc: cancel".to_string()
+                            } else {
+                                format!(
+                                    "{}/{}: next/prev, {}: new",
+                                    self.keymap.describe(Action::NextMatch),
+                                    self.keymap.describe(Action::PrevMatch),
+                                    self.keymap.describe(Action::Search)
+                                )
+                            };
+                            block = block.title(
+                                Title::from(format!(" {}{}  {} matches on this page ({}) ", text, case, count, hint))
+                                    .position(Position::Bottom)
+                                    .alignment(Alignment::Right),
+                            );
+                        }
+                        let table = TuiTable::new(rows, widths).header(header).block(block);
                         f.render_widget(table, content_area);
+
+                        // Aggregate footer pinned just above the bottom border
+                        if self.aggregate != Aggregate::Off && content_area.height > border_rows + header_rows {
+                            let values = aggregate_columns(recs, self.aggregate);
+                            let footer = Row::new(std::iter::once(TuiCell::from("")).chain(
+                                shown.iter().map(|&col| {
+                                    // Under the numbers they total
+                                    let line = Line::from(values[col].map(format_aggregate).unwrap_or_default());
+                                    TuiCell::from(if self.raw_values { line } else { line.alignment(Alignment::Right) })
+                                }),
+                            ))
+                            .style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD));
+                            let footer_area = Rect {
+                                x: content_area.x + 1,
+                                y: content_area.bottom() - 2,
+                                width: content_area.width.saturating_sub(border_cols),
+                                height: 1,
+                            };
+                            f.render_widget(TuiTable::new([footer], footer_widths), footer_area);
+                        }
+
+                        if self.show_cell_popup {
+                            self.draw_cell_popup(f, content_area, recs);
+                        }
+                        if let Some(form) = &self.insert_form {
+                            self.draw_insert_form(f, content_area, form);
+                        }
                     } else {
+                        let (title, failed, pending) = if self.query_result {
+                            ("Query result", "Query failed", "Running query...")
+                        } else {
+                            ("Records", "Failed to load records", "Loading records...")
+                        };
                         let records_block = Block::default()
-                            .title("Records")
+                            .title(title)
                             .borders(Borders::ALL)
                             .border_style(content_style);
-                        let records_content =
-                            Paragraph::new("Loading records...").block(records_block);
+                        let records_content = match &self.records_error {
+                            Some(e) => Paragraph::new(format!("{}:\n{}", failed, e))
+                                .style(Style::default().fg(Color::Red))
+                                .wrap(Wrap { trim: false }),
+                            None if self.records_loading => Paragraph::new(format!("{} {}", self.spinner(), pending)),
+                            None => Paragraph::new(pending),
+                        }
+                        .block(records_block);
                         f.render_widget(records_content, content_area);
                     }
                 }
                 TableFocus::SQL => {
-                    let sql_block = Block::default()
-                        .title("SQL")
-                        .borders(Borders::ALL)
-                        .border_style(content_style);
-
-                    let (tool_info, instructions) = if let Some(conn) = &self.connection {
-                        let tool_name = DB::cli_tool_name_for(conn);
-                        let available = DB::is_cli_tool_available_for(conn);
-                        
-                        if available {
-                            (
-                                format!("External CLI tool: {} (available)", tool_name),
-                                "Press [Enter] to launch external SQL CLI\n\nThis will open the appropriate CLI tool:\n• PostgreSQL: pgcli\n• MySQL: mycli\n• SQLite: litecli".to_string()
-                            )
-                        } else {
-                            (
-                                format!("External CLI tool: {} (NOT INSTALLED)", tool_name),
-                                format!("Please install {} to use SQL functionality:\n\npip install {}", tool_name, tool_name)
-                            )
-                        }
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(3), Constraint::Length(3)])
+                        .split(content_area);
+
+                    let label = match &self.query_name {
+                        Some(name) => format!("SQL [{}]", name),
+                        None => "SQL".to_string(),
+                    };
+                    let (text, title, style) = if self.sql_editing {
+                        (
+                            format!("{}_", self.sql_input),
+                            format!(
+                                "{}  ({}: run; Enter: newline; Esc: stop editing)",
+                                label,
+                                self.keymap.describe(Action::RunQuery)
+                            ),
+                            self.theme.border(true),
+                        )
                     } else {
-                        ("No connection available".to_string(), "No database connection available".to_string())
+                        (
+                            self.sql_input.clone(),
+                            format!(
+                                "{}  ({}: edit; {}: run; {}: external CLI)",
+                                label,
+                                self.keymap.describe(Action::EditSql),
+                                self.keymap.describe(Action::RunQuery),
+                                self.keymap.describe(Action::OpenCli)
+                            ),
+                            content_style,
+                        )
                     };
+                    // Keep the end of a long statement (and the cursor) in view
+                    let inner_h = usize::from(parts[0].height.saturating_sub(2));
+                    let lines = text.split('\n').count();
+                    let scroll = u16::try_from(lines.saturating_sub(inner_h)).unwrap_or(u16::MAX);
+                    let editor = Paragraph::new(text)
+                        .scroll((scroll, 0))
+                        .block(Block::default().title(title).borders(Borders::ALL).border_style(style));
+                    f.render_widget(editor, parts[0]);
 
-                    let sql_content = Paragraph::new(format!("{}\n\n{}", tool_info, instructions))
-                        .block(sql_block);
-
-                    f.render_widget(sql_content, content_area);
+                    // The name prompt takes the place of the CLI line while saving
+                    if let Some(input) = &self.save_input {
+                        let prompt = Paragraph::new(format!("{}_", input)).block(
+                            Block::default()
+                                .title("Save query as  (Enter: save; Esc: cancel)")
+                                .borders(Borders::ALL)
+                                .border_style(self.theme.border(true)),
+                        );
+                        f.render_widget(prompt, parts[1]);
+                    } else {
+                        let tool_info = match &self.connection {
+                            Some(conn) => {
+                                let tool_name = DB::cli_tool_name_for(conn);
+                                if DB::is_cli_tool_available_for(conn) {
+                                    format!("{} is available; press [o] to open it", tool_name)
+                                } else {
+                                    format!("{} is not installed (pip install {})", tool_name, tool_name)
+                                }
+                            }
+                            None => "No connection available".to_string(),
+                        };
+                        let cli_info = Paragraph::new(tool_info).block(
+                            Block::default()
+                                .title("External CLI")
+                                .borders(Borders::ALL)
+                                .border_style(content_style),
+                        );
+                        f.render_widget(cli_info, parts[1]);
+                    }
+                }
+                TableFocus::Properties if self.props_view == PropsView::Ddl => {
+                    self.draw_ddl(f, content_area, content_style)
                 }
                 TableFocus::Properties => {
-                    if let Some(props) = &self.properties {
-                        use ratatui::widgets::{Cell as TuiCell, Row, Table as TuiTable};
-                        // Build headers and widths with concise labels
-                        let header_labels = ["Column", "Type", "N", "Def", "PK"];
-                        let widths_all: [u16; 5] = [20, 14, 3, 20, 3];
-                        // Horizontal column window calculation based on available width
-                        let border_cols = 2u16; // left+right borders
-                        let avail_w = content_area.width.saturating_sub(border_cols);
-                        // Calculate start from scroll offset
-                        let col_start = self.properties_col_scroll.min(header_labels.len().saturating_sub(1));
-                        // Determine how many columns fit from col_start
-                        let mut sum = 0u16;
-                        let mut col_end = col_start;
-                        while col_end < header_labels.len() {
-                            let w = widths_all[col_end];
-                            if sum + w > avail_w { break; }
-                            sum += w;
-                            col_end += 1;
+                    let grid = match self.props_view {
+                        PropsView::Indexes => self.indexes.as_ref().map(|indexes| {
+                            let rows = indexes.as_ref().map(|indexes| {
+                                indexes
+                                    .iter()
+                                    .map(|i| {
+                                        vec![
+                                            Text::from(i.name.clone()),
+                                            Text::from(i.columns.join(", ")),
+                                            indicator_cell(if i.unique { "✔" } else { "" }),
+                                        ]
+                                    })
+                                    .collect::<Vec<_>>()
+                            });
+                            let headers = &[
+                                ("Index", 24u16, Alignment::Left),
+                                ("Columns", 40, Alignment::Left),
+                                ("U", 3, Alignment::Right),
+                            ][..];
+                            ("Indexes", headers, rows)
+                        }),
+                        PropsView::ForeignKeys => self.foreign_keys.as_ref().map(|keys| {
+                            let rows = keys.as_ref().map(|keys| {
+                                keys.iter()
+                                    .map(|k| {
+                                        let target = match (&k.referenced_database, &k.referenced_schema) {
+                                            (Some(prefix), _) | (None, Some(prefix)) => {
+                                                format!("{}.{}", prefix, k.referenced_table)
+                                            }
+                                            (None, None) => k.referenced_table.clone(),
+                                        };
+                                        vec![
+                                            Text::from(k.column.clone()),
+                                            Text::from(format!("{}({})", target, k.referenced_column)),
+                                            Text::from(k.on_delete.clone()),
+                                            Text::from(k.on_update.clone()),
+                                        ]
+                                    })
+                                    .collect::<Vec<_>>()
+                            });
+                            let headers = &[
+                                ("Column", 20u16, Alignment::Left),
+                                ("References", 40, Alignment::Left),
+                                ("On delete", 12, Alignment::Left),
+                                ("On update", 12, Alignment::Left),
+                            ][..];
+                            ("Foreign keys", headers, rows)
+                        }),
+                        PropsView::Columns => match (&self.properties, &self.properties_error) {
+                            (None, Some(e)) => Some(("Properties", &[][..], Err(e))),
+                            (props, _) => props.as_ref().map(|props| {
+                            let with_charset = has_charset(props);
+                            let rows = props
+                                .columns
+                                .iter()
+                                .map(|c| {
+                                    let default = match &c.default {
+                                        Some(default) => Text::from(default.clone()),
+                                        None => Text::styled("NULL", Style::default().fg(self.theme.null_cell)),
+                                    };
+                                    let mut row = vec![
+                                        Text::from(c.name.clone()),
+                                        wrap_cell(&c.data_type, PROPS_TYPE_WIDTH),
+                                        indicator_cell(if c.nullable { "YES" } else { "NO" }),
+                                        default,
+                                        indicator_cell(if c.primary_key { "✔" } else { "" }),
+                                    ];
+                                    if with_charset {
+                                        row.push(Text::from(c.charset.clone().unwrap_or_default()));
+                                        row.push(Text::from(c.collation.clone().unwrap_or_default()));
+                                    }
+                                    row
+                                })
+                                .collect::<Vec<_>>();
+                            // Concise labels
+                            let headers = if with_charset {
+                                &[
+                                    ("Column", 20u16, Alignment::Left),
+                                    ("Type", PROPS_TYPE_WIDTH, Alignment::Left),
+                                    ("N", 3, Alignment::Right),
+                                    ("Def", 20, Alignment::Left),
+                                    ("PK", 3, Alignment::Right),
+                                    ("Charset", 10, Alignment::Left),
+                                    ("Collation", 22, Alignment::Left),
+                                ][..]
+                            } else {
+                                &[
+                                    ("Column", 20u16, Alignment::Left),
+                                    ("Type", PROPS_TYPE_WIDTH, Alignment::Left),
+                                    ("N", 3, Alignment::Right),
+                                    ("Def", 20, Alignment::Left),
+                                    ("PK", 3, Alignment::Right),
+                                ][..]
+                            };
+                            ("Properties", headers, Ok(rows))
+                        }),
+                        },
+                        PropsView::Ddl => unreachable!("the DDL is drawn by draw_ddl"),
+                    };
+                    match grid {
+                        Some((name, headers, Ok(rows))) => {
+                            self.draw_props_grid(f, content_area, content_style, name, headers, &rows)
+                        }
+                        Some((name, _, Err(e))) => {
+                            let block = Block::default().title(name).borders(Borders::ALL).border_style(content_style);
+                            let text = Paragraph::new(format!("Error: {}", e))
+                                .style(Style::default().fg(Color::Red))
+                                .wrap(Wrap { trim: false })
+                                .block(block);
+                            f.render_widget(text, content_area);
+                        }
+                        None => {
+                            let name = match self.props_view {
+                                PropsView::Columns => "Properties",
+                                PropsView::Indexes => "Indexes",
+                                PropsView::ForeignKeys => "Foreign keys",
+                                PropsView::Ddl => "DDL",
+                            };
+                            let properties_block = Block::default()
+                                .title(name)
+                                .borders(Borders::ALL)
+                                .border_style(content_style);
+                            // Indexes and foreign keys are only missing while they load
+                            let loading = match self.props_view {
+                                PropsView::Columns => self.properties_loading,
+                                PropsView::Indexes | PropsView::ForeignKeys | PropsView::Ddl => true,
+                            };
+                            let pending = format!("Loading {}...", name.to_lowercase());
+                            let pending = if loading { format!("{} {}", self.spinner(), pending) } else { pending };
+                            let properties_content = Paragraph::new(pending).block(properties_block);
+                            f.render_widget(properties_content, content_area);
                         }
-                        if col_end == col_start { col_end = (col_start + 1).min(header_labels.len()); }
-                        let header = Row::new(header_labels[col_start..col_end].iter().map(|c| {
-                            TuiCell::from(*c).style(Style::default().add_modifier(Modifier::BOLD))
-                        }));
-                        // Visible slice based on height and properties_scroll
-                        let border_rows = 2u16;
-                        let header_rows = 1u16;
-                        let avail = content_area
-                            .height
-                            .saturating_sub(border_rows)
-                            .saturating_sub(header_rows);
-                        let visible_count = usize::try_from(avail).unwrap_or(0);
-                        let total = props.columns.len();
-                        let max_start = total.saturating_sub(visible_count);
-                        let start = self.properties_scroll.min(max_start);
-                        let end = start.saturating_add(visible_count).min(total);
-                        let rows = props.columns[start..end].iter().map(|c| {
-                            let fields_all = [
-                                c.name.as_str(),
-                                c.data_type.as_str(),
-                                if c.nullable { "YES" } else { "NO" },
-                                c.default.as_deref().unwrap_or(""),
-                                if c.primary_key { "✔" } else { "" },
-                            ];
-                            Row::new(fields_all[col_start..col_end].iter().cloned())
-                        });
-                        let widths = widths_all[col_start..col_end]
-                            .iter()
-                            .cloned()
-                            .map(Constraint::Length)
-                            .collect::<Vec<_>>();
-                        let title = if total > 0 && visible_count > 0 {
-                            format!(
-                                "Properties  rows [{}-{} / {}], cols [{}-{} / {}]  (↑/↓, PgUp/PgDn, Home/End; ←/→)",
-                                start.saturating_add(1), end, total,
-                                col_start.saturating_add(1), col_end, header_labels.len()
-                            )
-                        } else {
-                            "Properties".to_string()
-                        };
-                        let table = TuiTable::new(rows, widths)
-                            .header(header)
-                            .block(
-                                Block::default()
-                                    .title(title)
-                                    .borders(Borders::ALL)
-                                    .border_style(content_style),
-                            );
-                        f.render_widget(table, content_area);
-                    } else {
-                        let properties_block = Block::default()
-                            .title("Properties")
-                            .borders(Borders::ALL)
-                            .border_style(content_style);
-                        let properties_content =
-                            Paragraph::new("Loading properties...").block(properties_block);
-                        f.render_widget(properties_content, content_area);
                     }
                 }
             }
+            if self.show_plan {
+                self.draw_plan(f, area);
+            }
+            self.history.draw(f, area, focused);
+            self.saved_queries.draw(f, area, focused);
         } else {
             // No table selected
             let block = Block::default()
                 .title("Table View")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::White));
+                .border_style(self.theme.border(false));
 
             let content = Paragraph::new(
                 "No table selected\n\nSelect a table from the database structure on the left.",
@@ -656,3 +3603,363 @@ impl Component for TableComponent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(columns: &[&str], rows: &[&[&str]]) -> Records {
+        Records {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: rows.iter().map(|r| r.iter().map(|v| v.to_string()).collect()).collect(),
+            truncated: false,
+            blobs: HashMap::new(),
+            kinds: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn aggregates_numeric_columns_only() {
+        let recs = records(
+            &["id", "name", "price"],
+            &[&["1", "apple", "1.5"], &["2", "pear", ""], &["3", "fig", "2.5"]],
+        );
+        assert_eq!(aggregate_columns(&recs, Aggregate::Sum), vec![Some(6.0), None, Some(4.0)]);
+        assert_eq!(aggregate_columns(&recs, Aggregate::Avg), vec![Some(2.0), None, Some(2.0)]);
+        assert_eq!(aggregate_columns(&recs, Aggregate::Off), vec![None, None, None]);
+    }
+
+    #[test]
+    fn empty_columns_have_no_aggregate() {
+        let recs = records(&["note"], &[&[""], &[" "]]);
+        assert_eq!(aggregate_columns(&recs, Aggregate::Sum), vec![None]);
+    }
+
+    #[test]
+    fn non_finite_text_is_not_numeric() {
+        let recs = records(&["a", "b", "c"], &[&["1", "2", "3"], &["inf", "NaN", "-infinity"]]);
+        assert_eq!(aggregate_columns(&recs, Aggregate::Sum), vec![None, None, None]);
+    }
+
+    fn column(name: &str, nullable: bool, primary_key: bool) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            nullable,
+            default: None,
+            primary_key,
+            charset: None,
+            collation: None,
+            comment: None,
+        }
+    }
+
+    /// A table editing its `note` cell, set to NULL
+    fn editing_null(nullable: bool) -> TableComponent {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        table.records = Some(records(&["id", "note"], &[&["1", "hello"]]));
+        let columns = vec![column("id", false, true), column("note", nullable, false)];
+        table.properties = Some(TableProperties { columns });
+        table.table_info = Some(TableInfo { database: "main".to_string(), schema: None, table: "notes".to_string() });
+        table.edit = Some(CellEdit { row: 0, col: 1, input: "hello".to_string(), null: false });
+        let _ = table.update(TableMsg::EditSetNull);
+        table
+    }
+
+    fn connection() -> Connection {
+        serde_yaml::from_str("type: sqlite\nname: t\npath: /tmp/t.db").unwrap()
+    }
+
+    #[test]
+    fn null_is_written_to_a_nullable_column() {
+        let mut table = editing_null(true);
+        let update = table.update(TableMsg::CommitEdit(connection()));
+        assert!(matches!(update.cmd, Command::Spawn(_)));
+        assert!(table.edit.is_none());
+        assert!(table.records_status.is_none());
+    }
+
+    #[test]
+    fn null_is_refused_for_a_not_null_column() {
+        let mut table = editing_null(false);
+        let update = table.update(TableMsg::CommitEdit(connection()));
+        assert!(matches!(update.cmd, Command::None));
+        assert_eq!(table.records_status.as_deref(), Some("cannot set NULL: note is NOT NULL"));
+        // The editor stays open to type a value instead
+        assert!(table.edit.as_ref().is_some_and(|e| e.null));
+    }
+
+    #[test]
+    fn typing_replaces_a_null_edit() {
+        let mut table = editing_null(false);
+        let _ = table.update(TableMsg::EditPush('x'));
+        let edit = table.edit.as_ref().unwrap();
+        assert!(!edit.null);
+        assert_eq!(edit.input, "x");
+    }
+
+    /// Text of the cell popup drawn for the first cell, row by row
+    fn cell_popup_text(table: &TableComponent) -> String {
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(70, 15)).unwrap();
+        let recs = table.records.clone().unwrap();
+        terminal.draw(|f| table.draw_cell_popup(f, f.size(), &recs)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let width = usize::from(buffer.area.width);
+        let symbols: Vec<&str> = buffer.content.iter().map(|c| c.symbol()).collect();
+        symbols.chunks(width).map(|row| row.concat() + "\n").collect()
+    }
+
+    #[test]
+    fn cell_popup_shows_column_type_and_comment() {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        table.records = Some(records(&["sku"], &[&["A-1"]]));
+        let mut sku = column("sku", false, true);
+        sku.data_type = "varchar(20)".to_string();
+        sku.comment = Some("stock keeping unit".to_string());
+        table.properties = Some(TableProperties { columns: vec![sku] });
+        let text = cell_popup_text(&table);
+        assert!(text.contains("varchar(20) NOT NULL — stock keeping unit"), "{}", text);
+        assert!(text.contains("A-1"));
+    }
+
+    #[test]
+    fn cell_popup_omits_the_header_without_properties() {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        table.records = Some(records(&["sku"], &[&["A-1"]]));
+        let text = cell_popup_text(&table);
+        assert!(!text.contains("NOT NULL"));
+        assert!(text.contains("A-1"));
+    }
+
+    #[test]
+    fn capped_wrap_ends_in_an_ellipsis() {
+        let text = wrap_cell_capped("aaaa bbbb cccc dddd", 4, 2);
+        let lines: Vec<String> = text.lines.iter().map(|l| l.to_string()).collect();
+        assert_eq!(lines, ["aaaa", "bbb…"]);
+        assert_eq!(wrap_cell_capped("aa bb", 4, 2).height(), 2);
+        assert_eq!(wrap_cell_capped("aa", 4, 0).height(), 1);
+    }
+
+    #[test]
+    fn wrapped_rows_are_bounded_and_keep_the_current_row_in_view() {
+        let table = TableComponent::new(0, 3, 0, Theme::default(), Keymap::default());
+        let long = "word ".repeat(100);
+        let mut rows: Vec<Vec<&str>> = (0..10).map(|_| vec!["1", "short"]).collect();
+        rows[0][1] = &long;
+        rows[6][1] = &long;
+        let rows: Vec<&[&str]> = rows.iter().map(Vec::as_slice).collect();
+        let recs = records(&["id", "note"], &rows);
+        let heights = table.wrapped_heights(&recs, &[0, 1], &[3, 12]);
+        assert_eq!(heights, [3, 1, 1, 1, 1, 1, 3, 1, 1, 1]);
+        for scroll in 0..heights.len() {
+            let (start, end) = line_window(&heights, scroll, 5);
+            assert!(start <= scroll && scroll < end, "row {} outside {}..{}", scroll, start, end);
+            assert!(heights[start..end].iter().sum::<u16>() <= 5);
+        }
+    }
+
+    #[test]
+    fn wrapped_grid_cuts_long_values_and_shows_the_next_rows() {
+        let mut table = TableComponent::new(0, 2, 0, Theme::default(), Keymap::default());
+        table.table_info = Some(TableInfo { database: "main".to_string(), schema: None, table: "notes".to_string() });
+        let long = "word ".repeat(100);
+        table.records = Some(records(&["note"], &[&[&long], &["second"], &["third"]]));
+        let _ = table.update(TableMsg::ToggleWrap);
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(60, 14)).unwrap();
+        terminal.draw(|f| table.draw(f, f.size(), true)).unwrap();
+        let text: String = terminal.backend().buffer().content.iter().map(|c| c.symbol()).collect();
+        assert!(text.contains('…'));
+        assert!(text.contains("second") && text.contains("third"));
+        assert_eq!(table.records_visible.get(), 3);
+    }
+
+    #[test]
+    fn prev_page_returns_to_the_offsets_next_page_left() {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        table.table_info = Some(TableInfo { database: "main".to_string(), schema: None, table: "t".to_string() });
+        table.page_size = 3;
+        table.records = Some(records(&["id"], &[&["1"], &["2"], &["3"]]));
+        let _ = table.update(TableMsg::NextPage(connection()));
+        assert_eq!(table.page_offset, 3);
+        // The memory cap cut this page to one row
+        let mut capped = records(&["id"], &[&["4"]]);
+        capped.truncated = true;
+        table.records = Some(capped);
+        let _ = table.update(TableMsg::NextPage(connection()));
+        assert_eq!(table.page_offset, 4);
+        let _ = table.update(TableMsg::PrevPage(connection()));
+        assert_eq!(table.page_offset, 3);
+        let _ = table.update(TableMsg::PrevPage(connection()));
+        assert_eq!(table.page_offset, 0);
+        let update = table.update(TableMsg::PrevPage(connection()));
+        assert!(matches!(update.cmd, Command::None));
+        assert_eq!(table.page_offset, 0);
+    }
+
+    /// Five rows of a table keyed on `id`
+    fn keyed_table() -> TableComponent {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        table.table_info = Some(TableInfo { database: "main".to_string(), schema: None, table: "t".to_string() });
+        let columns = vec![column("id", false, true), column("note", true, false)];
+        table.properties = Some(TableProperties { columns });
+        let rows: [&[&str]; 5] = [&["1", "a"], &["2", "b"], &["3", "c"], &["4", "d"], &["5", "e"]];
+        table.records = Some(records(&["id", "note"], &rows));
+        table
+    }
+
+    fn bookmark_row(table: &mut TableComponent, row: usize) {
+        table.records_scroll = row;
+        let _ = table.update(TableMsg::ToggleBookmark);
+    }
+
+    fn bookmarked(table: &TableComponent) -> Vec<usize> {
+        let recs = table.records.as_ref().unwrap();
+        (0..recs.rows.len()).filter(|&i| table.is_bookmarked(&recs.rows[i])).collect()
+    }
+
+    #[test]
+    fn bookmarks_toggle_by_primary_key() {
+        let mut table = keyed_table();
+        bookmark_row(&mut table, 1);
+        bookmark_row(&mut table, 3);
+        assert_eq!(bookmarked(&table), [1, 3]);
+        // Edited values don't lose the mark; the key identifies the row
+        table.records.as_mut().unwrap().rows[1][1] = "changed".to_string();
+        assert_eq!(bookmarked(&table), [1, 3]);
+        bookmark_row(&mut table, 3);
+        assert_eq!(bookmarked(&table), [1]);
+    }
+
+    #[test]
+    fn bookmark_jumps_wrap_both_ways() {
+        let mut table = keyed_table();
+        bookmark_row(&mut table, 1);
+        bookmark_row(&mut table, 3);
+        table.records_scroll = 0;
+        let mut visits = Vec::new();
+        for msg in [TableMsg::NextBookmark, TableMsg::NextBookmark, TableMsg::NextBookmark] {
+            let _ = table.update(msg);
+            visits.push(table.records_scroll);
+        }
+        for msg in [TableMsg::PrevBookmark, TableMsg::PrevBookmark] {
+            let _ = table.update(msg);
+            visits.push(table.records_scroll);
+        }
+        assert_eq!(visits, [1, 3, 1, 3, 1]);
+    }
+
+    #[test]
+    fn rows_narrower_or_wider_than_the_header_draw() {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        table.table_info = Some(TableInfo { database: "main".to_string(), schema: None, table: "t".to_string() });
+        table.records = Some(records(&["a", "b", "c"], &[&["1"], &[], &["1", "2", "3", "4"]]));
+        table.records_col_scroll = 2;
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 20)).unwrap();
+        let mut draw = |table: &TableComponent| {
+            terminal.draw(|f| table.draw(f, f.size(), true)).unwrap();
+        };
+        draw(&table);
+        for msg in [TableMsg::ToggleFitColumns, TableMsg::ToggleWrap, TableMsg::CycleAggregate, TableMsg::InspectCell] {
+            let _ = table.update(msg);
+            draw(&table);
+        }
+        table.records_scroll = 1;
+        draw(&table);
+    }
+
+    /// 50 rows of three columns
+    fn scrolling_table() -> TableComponent {
+        let mut table = TableComponent::new(0, DEFAULT_WRAP_LINES, 0, Theme::default(), Keymap::default());
+        let mut recs = records(&["id", "name", "note"], &[]);
+        recs.rows = (0..50).map(|i| vec![i.to_string(), format!("n{i}"), String::new()]).collect();
+        table.records = Some(recs);
+        table
+    }
+
+    #[test]
+    fn scroll_bottom_and_cols_end_stop_at_the_last_row_and_column() {
+        let mut table = scrolling_table();
+        let _ = table.update(TableMsg::ScrollBottom);
+        let _ = table.update(TableMsg::ColsEnd);
+        assert_eq!((table.records_scroll, table.records_col_scroll), (49, 2));
+
+        // Further scrolling stays clamped
+        let _ = table.update(TableMsg::ScrollRecordsBy(10));
+        let _ = table.update(TableMsg::ScrollColsBy(10));
+        assert_eq!((table.records_scroll, table.records_col_scroll), (49, 2));
+    }
+
+    #[test]
+    fn scroll_bottom_without_records_stays_at_zero() {
+        let mut table = scrolling_table();
+        table.records = None;
+        let _ = table.update(TableMsg::ScrollBottom);
+        let _ = table.update(TableMsg::ColsEnd);
+        assert_eq!((table.records_scroll, table.records_col_scroll), (0, 0));
+    }
+
+    #[test]
+    fn records_keys_do_nothing_while_properties_has_focus() {
+        let mut table = scrolling_table();
+        table.focus = TableFocus::Properties;
+        let _ = table.update(TableMsg::ScrollBottom);
+        let _ = table.update(TableMsg::ColsEnd);
+        assert_eq!((table.records_scroll, table.records_col_scroll), (0, 0));
+    }
+
+    /// Ends of the Properties grid reached with `ScrollPropsBottom` and `PropsColsEnd`
+    fn properties_ends(table: &mut TableComponent, view: PropsView) -> (usize, usize) {
+        table.focus = TableFocus::Properties;
+        table.props_view = view;
+        let _ = table.update(TableMsg::ScrollPropsBottom);
+        let _ = table.update(TableMsg::PropsColsEnd);
+        (table.properties_scroll, table.properties_col_scroll)
+    }
+
+    #[test]
+    fn properties_ends_follow_each_views_columns() {
+        let mut table = scrolling_table();
+        let columns = vec![column("id", false, true), column("name", true, false), column("note", true, false)];
+        table.properties = Some(TableProperties { columns });
+        let index = |name: &str| IndexInfo { name: name.to_string(), columns: vec!["id".to_string()], unique: true };
+        table.indexes = Some(Ok(vec![index("a"), index("b")]));
+        let foreign_key = ForeignKeyInfo {
+            column: "id".to_string(),
+            referenced_database: None,
+            referenced_schema: None,
+            referenced_table: "other".to_string(),
+            referenced_column: "id".to_string(),
+            on_delete: "CASCADE".to_string(),
+            on_update: "NO ACTION".to_string(),
+        };
+        table.foreign_keys = Some(Ok(vec![foreign_key]));
+        table.ddl = Some(Ok("CREATE TABLE t (\n  id int\n);".to_string()));
+
+        // Name, type, nullable, default, key
+        assert_eq!(properties_ends(&mut table, PropsView::Columns), (2, 4));
+        // Name, columns, unique
+        assert_eq!(properties_ends(&mut table, PropsView::Indexes), (1, 2));
+        // Column, reference, on delete, on update
+        assert_eq!(properties_ends(&mut table, PropsView::ForeignKeys), (0, 3));
+        // By line, no columns
+        assert_eq!(properties_ends(&mut table, PropsView::Ddl), (2, 0));
+
+        // Charset and collation add two columns
+        if let Some(props) = table.properties.as_mut() {
+            props.columns[1].charset = Some("utf8mb4".to_string());
+        }
+        assert_eq!(properties_ends(&mut table, PropsView::Columns), (2, 6));
+
+        // Further scrolling stays clamped
+        let _ = table.update(TableMsg::ScrollPropsBy(10));
+        let _ = table.update(TableMsg::ScrollPropsColsBy(10));
+        assert_eq!((table.properties_scroll, table.properties_col_scroll), (2, 6));
+    }
+
+    #[test]
+    fn properties_ends_without_rows_stop_at_the_first_row() {
+        let mut table = scrolling_table();
+        assert_eq!(properties_ends(&mut table, PropsView::Columns), (0, 4));
+        assert_eq!(properties_ends(&mut table, PropsView::Indexes), (0, 2));
+    }
+}
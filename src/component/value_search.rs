@@ -0,0 +1,401 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use super::{Child, Component, Database, DashboardMsg, FinderEntry, TableKind};
+use crate::app::AppMsg;
+use crate::connection::Connection;
+use crate::db::{DB, DBBehavior};
+use crate::logger::error;
+use crate::theme::Theme;
+use crate::update::{Command, Update};
+
+/// A column holding the searched value in some rows
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub entry: FinderEntry,
+    pub column: String,
+    pub rows: u64,
+}
+
+/// Every table (not view) of the loaded tree, in tree order
+fn searchable_tables(databases: &[Database]) -> Vec<FinderEntry> {
+    let mut entries = Vec::new();
+    for db in databases {
+        for child in &db.children {
+            let (schema, tables) = match child {
+                Child::Table(t) => (None, std::slice::from_ref(t)),
+                Child::Schema(s) => (Some(&s.name), s.tables.as_slice()),
+            };
+            entries.extend(tables.iter().filter(|t| t.kind == TableKind::Table).map(|t| FinderEntry {
+                database: db.name.clone(),
+                schema: schema.cloned(),
+                table: t.name.clone(),
+            }));
+        }
+    }
+    entries
+}
+
+/// Text columns of a table containing `needle`, with their matching row counts
+fn scan_table(conn: &Connection, entry: &FinderEntry, needle: &str) -> Result<Vec<(String, u64)>> {
+    let (database, schema, table) = (&entry.database, entry.schema.as_deref(), &entry.table);
+    let props = DB::fetch_properties(conn, database, schema, table)?;
+    let columns: Vec<String> = props.columns.into_iter().filter(|c| c.is_text()).map(|c| c.name).collect();
+    if columns.is_empty() {
+        return Ok(Vec::new());
+    }
+    let counts = DB::count_matches(conn, database, schema, table, &columns, needle)?;
+    Ok(columns.into_iter().zip(counts).filter(|(_, rows)| *rows > 0).collect())
+}
+
+pub enum ValueSearchMsg {
+    Close,
+    Push(char),
+    Pop,
+    /// Switch between the value and the schema inputs, stopping a running search
+    SwitchField,
+    Start,
+    /// Stop the running search, keeping what it found so far
+    Stop,
+    MoveUp,
+    MoveDown,
+    /// Open the table of the chosen hit; handled by the parent
+    Confirm(FinderEntry),
+    /// Results of the search with this generation, streamed as tables are scanned
+    Found(u64, SearchHit),
+    Scanned(u64),
+    TableFailed(u64),
+    Finished(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Value,
+    Schemas,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Editing,
+    Running,
+    Stopped,
+    Done,
+}
+
+/// Overlay searching the text columns of every table for a value
+pub struct ValueSearchComponent {
+    open: bool,
+    connection: Option<Connection>,
+    tables: Vec<FinderEntry>,
+    value: String,
+    /// Comma-separated database or schema names the scan is limited to
+    schemas: String,
+    field: Field,
+    phase: Phase,
+    hits: Vec<SearchHit>,
+    selected: usize,
+    /// Tables to scan in the current run, scanned so far, and failed
+    total: usize,
+    scanned: usize,
+    failed: usize,
+    generation: u64,
+    /// Set to stop the running scan between tables
+    cancel: Arc<AtomicBool>,
+    theme: Theme,
+}
+
+impl ValueSearchComponent {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            open: false,
+            connection: None,
+            tables: Vec::new(),
+            value: String::new(),
+            schemas: String::new(),
+            field: Field::Value,
+            phase: Phase::Editing,
+            hits: Vec::new(),
+            selected: 0,
+            total: 0,
+            scanned: 0,
+            failed: 0,
+            generation: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+            theme,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open over the tables of `databases`. The last search is shown again,
+    /// still running if it was hidden to open a table.
+    pub fn open(&mut self, conn: Connection, databases: &[Database]) {
+        self.open = true;
+        self.connection = Some(conn);
+        self.tables = searchable_tables(databases);
+    }
+
+    /// Close, stopping the search
+    pub fn close(&mut self) {
+        self.stop();
+        self.open = false;
+    }
+
+    /// Close, leaving the search running in the background
+    pub fn hide(&mut self) {
+        self.open = false;
+    }
+
+    /// Forget the last search; its results belong to another connection
+    pub fn reset(&mut self) {
+        self.close();
+        self.generation += 1;
+        self.phase = Phase::Editing;
+        self.field = Field::Value;
+        self.hits.clear();
+        self.selected = 0;
+        self.connection = None;
+        self.tables.clear();
+    }
+
+    fn stop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        if self.phase == Phase::Running {
+            self.phase = Phase::Stopped;
+        }
+    }
+
+    /// Tables whose database or schema is listed in `schemas`; all when it is empty
+    fn scope(&self) -> Vec<FinderEntry> {
+        let names: Vec<&str> = self.schemas.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
+        self.tables
+            .iter()
+            .filter(|e| {
+                names.is_empty()
+                    || names.contains(&e.database.as_str())
+                    || e.schema.as_deref().is_some_and(|s| names.contains(&s))
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn start(&mut self) -> Update<ValueSearchMsg> {
+        let Some(conn) = self.connection.clone() else {
+            return Update::none();
+        };
+        if self.value.is_empty() {
+            return Update::none();
+        }
+        let tables = self.scope();
+        self.stop();
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.generation += 1;
+        self.phase = Phase::Running;
+        self.hits.clear();
+        self.selected = 0;
+        self.total = tables.len();
+        self.scanned = 0;
+        self.failed = 0;
+
+        let generation = self.generation;
+        let cancel = self.cancel.clone();
+        let needle = self.value.clone();
+        let task = move |tx: std::sync::mpsc::Sender<AppMsg>| {
+            let send = |msg: ValueSearchMsg| tx.send(DashboardMsg::from(msg).into()).is_ok();
+            for entry in tables {
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+                let sent = match scan_table(&conn, &entry, &needle) {
+                    Ok(hits) => hits.into_iter().all(|(column, rows)| {
+                        send(ValueSearchMsg::Found(generation, SearchHit { entry: entry.clone(), column, rows }))
+                    }),
+                    Err(e) => {
+                        error(&format!("ValueSearch: scanning {} failed: {}", entry.table, e));
+                        send(ValueSearchMsg::TableFailed(generation))
+                    }
+                };
+                if !sent || !send(ValueSearchMsg::Scanned(generation)) {
+                    return;
+                }
+            }
+            send(ValueSearchMsg::Finished(generation));
+        };
+        Command::Spawn(Box::new(task)).into()
+    }
+
+    fn input(&mut self) -> &mut String {
+        match self.field {
+            Field::Value => &mut self.value,
+            Field::Schemas => &mut self.schemas,
+        }
+    }
+
+    fn status(&self) -> String {
+        let failed = match self.failed {
+            0 => String::new(),
+            n => format!(", {} failed (see the log)", n),
+        };
+        let progress = format!("{}/{} tables{}", self.scanned, self.total, failed);
+        match self.phase {
+            Phase::Editing => format!("{} tables to scan", self.scope().len()),
+            Phase::Running => format!("Scanning… {}", progress),
+            Phase::Stopped => format!("Stopped after {}", progress),
+            Phase::Done => format!("Done: {} columns match in {}", self.hits.len(), progress),
+        }
+    }
+}
+
+impl Component for ValueSearchComponent {
+    type Msg = ValueSearchMsg;
+
+    fn update(&mut self, msg: Self::Msg) -> Update<Self::Msg> {
+        match msg {
+            ValueSearchMsg::Close => self.close(),
+            ValueSearchMsg::Push(c) => self.input().push(c),
+            ValueSearchMsg::Pop => {
+                self.input().pop();
+            }
+            ValueSearchMsg::SwitchField => {
+                self.stop();
+                self.phase = Phase::Editing;
+                self.field = match self.field {
+                    Field::Value => Field::Schemas,
+                    Field::Schemas => Field::Value,
+                };
+            }
+            ValueSearchMsg::Start => return self.start(),
+            ValueSearchMsg::Stop => self.stop(),
+            ValueSearchMsg::MoveUp => self.selected = self.selected.saturating_sub(1),
+            ValueSearchMsg::MoveDown => {
+                if self.selected + 1 < self.hits.len() {
+                    self.selected += 1;
+                }
+            }
+            ValueSearchMsg::Confirm(_) => {} // Handled by parent
+            // Results of an older (or stopped) run are dropped
+            ValueSearchMsg::Found(generation, hit) if generation == self.generation => self.hits.push(hit),
+            ValueSearchMsg::Scanned(generation) if generation == self.generation => self.scanned += 1,
+            ValueSearchMsg::TableFailed(generation) if generation == self.generation => self.failed += 1,
+            ValueSearchMsg::Finished(generation) if generation == self.generation && self.phase == Phase::Running => {
+                self.phase = Phase::Done;
+            }
+            ValueSearchMsg::Found(..)
+            | ValueSearchMsg::Scanned(_)
+            | ValueSearchMsg::TableFailed(_)
+            | ValueSearchMsg::Finished(_) => {}
+        }
+        Update::none()
+    }
+
+    fn handle_key(&self, key: KeyEvent) -> Update<Self::Msg> {
+        use crossterm::event::KeyCode::*;
+
+        if self.phase == Phase::Editing {
+            return match key.code {
+                Esc => ValueSearchMsg::Close.into(),
+                Enter => ValueSearchMsg::Start.into(),
+                Tab | BackTab => ValueSearchMsg::SwitchField.into(),
+                Backspace => ValueSearchMsg::Pop.into(),
+                Char(c) => ValueSearchMsg::Push(c).into(),
+                _ => Update::none(),
+            };
+        }
+        match key.code {
+            Esc if self.phase == Phase::Running => ValueSearchMsg::Stop.into(),
+            Esc => ValueSearchMsg::Close.into(),
+            Enter => match self.hits.get(self.selected) {
+                Some(hit) => ValueSearchMsg::Confirm(hit.entry.clone()).into(),
+                None => Update::none(),
+            },
+            Tab | BackTab => ValueSearchMsg::SwitchField.into(),
+            Up | Char('k') => ValueSearchMsg::MoveUp.into(),
+            Down | Char('j') => ValueSearchMsg::MoveDown.into(),
+            _ => Update::none(),
+        }
+    }
+
+    fn is_typing(&self) -> bool {
+        self.open
+    }
+
+    fn draw(&self, f: &mut Frame, area: Rect, _focused: bool) {
+        if !self.open {
+            return;
+        }
+        // Centered popup over the dashboard
+        let width = area.width.saturating_mul(3) / 4;
+        let height = area.height.saturating_mul(3) / 4;
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        f.render_widget(Clear, popup);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(2), Constraint::Min(0)])
+            .split(popup);
+
+        let editing = self.phase == Phase::Editing;
+        for (chunk, field, title, text) in [
+            (chunks[0], Field::Value, "Search values (Enter: scan, Tab: next field, Esc: close)", &self.value),
+            (chunks[1], Field::Schemas, "Only in databases / schemas (comma-separated; empty: all)", &self.schemas),
+        ] {
+            let active = editing && self.field == field;
+            let cursor = if active { "_" } else { "" };
+            let input = Paragraph::new(format!("{}{}", text, cursor)).block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border(active)),
+            );
+            f.render_widget(input, chunk);
+        }
+
+        let note = "Counts LIKE matches in every text column of each table, reading every row: \
+                    this can be slow on large databases, so limit the scan to the schemas you need.";
+        f.render_widget(Paragraph::new(Line::from(note)).wrap(Wrap { trim: true }), chunks[2]);
+
+        let items: Vec<ListItem> = if self.hits.is_empty() {
+            let empty = if editing { "" } else { "(no matches yet)" };
+            vec![ListItem::new(empty)]
+        } else {
+            self.hits
+                .iter()
+                .map(|hit| ListItem::new(format!("{}  {}  {} rows", hit.entry.label(), hit.column, hit.rows)))
+                .collect()
+        };
+        let keys = match self.phase {
+            Phase::Running => " (Enter: open, Tab: edit, Esc: stop)",
+            Phase::Stopped | Phase::Done => " (Enter: open, Tab: edit, Esc: close)",
+            Phase::Editing => "",
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(format!("{}{}", self.status(), keys))
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border(!editing)),
+            )
+            .highlight_style(Style::default().fg(self.theme.highlight).add_modifier(Modifier::BOLD))
+            .highlight_symbol("▶ ");
+        let mut state = ListState::default();
+        state.select((!editing && !self.hits.is_empty()).then_some(self.selected));
+        f.render_stateful_widget(list, chunks[3], &mut state);
+    }
+}
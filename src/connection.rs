@@ -1,21 +1,157 @@
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{config::Config, db::DatabaseType};
 
-#[derive(Debug, Deserialize, Clone)]
+pub mod secret;
+pub mod tunnel;
+
+pub use tunnel::SshConfig;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Connection {
     pub r#type: DatabaseType,
     pub name: Option<String>,
+    /// Heading the connection is listed under
+    pub group: Option<String>,
+    /// Complete connection URL / DSN, used instead of the individual fields
+    pub url: Option<String>,
     pub user: Option<String>,
     pub host: Option<String>,
     pub port: Option<u64>,
+    /// MySQL only: Unix socket of a local server, used instead of `host` and `port`
+    pub socket: Option<std::path::PathBuf>,
     pub path: Option<std::path::PathBuf>,
     pub password: Option<String>,
+    /// Environment variable holding the password; takes precedence over `password`
+    pub password_env: Option<String>,
+    /// OS keyring entry (`service/account` or `account`) holding the
+    /// password; takes precedence over `password_env` and `password`
+    pub password_keyring: Option<String>,
     pub database: Option<String>,
+    /// Soft cap on the bytes a single records fetch may hold in memory
+    pub max_records_bytes: Option<usize>,
+    /// Bytes of each BLOB value kept for the hex preview (default 4096)
+    pub blob_preview_bytes: Option<usize>,
+    /// Rows per records page; zero or values above `MAX_FETCH_LIMIT` fall back
+    /// to `DEFAULT_FETCH_LIMIT`
+    pub fetch_limit: Option<usize>,
+    /// Refuse to change table data from the Records view (default false)
+    pub read_only: Option<bool>,
+    /// Abort statements running longer than this (default 30; 0 disables)
+    pub query_timeout_secs: Option<u64>,
+    /// Postgres only: whether to use TLS (default `disable`)
+    pub sslmode: Option<SslMode>,
+    /// Reach `host:port` through an SSH port-forward from this bastion
+    /// (boxed: messages carry whole Connections)
+    pub ssh: Option<Box<SshConfig>>,
+}
+
+/// TLS negotiation for Postgres, with libpq's meaning: `prefer` and `require`
+/// encrypt but do not verify the server certificate
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+}
+
+impl SslMode {
+    /// Value of the `sslmode` URL parameter
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SslMode::Disable => "disable",
+            SslMode::Prefer => "prefer",
+            SslMode::Require => "require",
+        }
+    }
+}
+
+pub const DEFAULT_FETCH_LIMIT: usize = 200;
+pub const MAX_FETCH_LIMIT: usize = 100_000;
+pub const DEFAULT_QUERY_TIMEOUT_SECS: u64 = 30;
+
+impl Connection {
+    /// A connection of `type` with every optional setting unset
+    pub fn new(r#type: DatabaseType) -> Self {
+        Self {
+            r#type,
+            name: None,
+            group: None,
+            url: None,
+            user: None,
+            host: None,
+            port: None,
+            socket: None,
+            path: None,
+            password: None,
+            password_env: None,
+            password_keyring: None,
+            database: None,
+            max_records_bytes: None,
+            blob_preview_bytes: None,
+            fetch_limit: None,
+            read_only: None,
+            query_timeout_secs: None,
+            sslmode: None,
+            ssh: None,
+        }
+    }
+
+    pub fn fetch_limit(&self) -> usize {
+        match self.fetch_limit {
+            Some(n) if n > 0 && n <= MAX_FETCH_LIMIT => n,
+            _ => DEFAULT_FETCH_LIMIT,
+        }
+    }
+
+    /// How long a statement may run before the backend aborts it; `None`
+    /// when `query_timeout_secs` is 0
+    pub fn query_timeout(&self) -> Option<std::time::Duration> {
+        match self.query_timeout_secs.unwrap_or(DEFAULT_QUERY_TIMEOUT_SECS) {
+            0 => None,
+            secs => Some(std::time::Duration::from_secs(secs)),
+        }
+    }
+
+    /// The password to connect with, from the first of `password_keyring`,
+    /// `password_env` and `password` that is set
+    pub fn password(&self) -> Result<Option<String>> {
+        if let Some(key) = &self.password_keyring {
+            return secret::lookup(key).map(Some);
+        }
+        match &self.password_env {
+            Some(var) => std::env::var(var).map(Some).map_err(|e| {
+                anyhow::anyhow!("password_env {}: {}", var, e)
+            }),
+            None => Ok(self.password.clone()),
+        }
+    }
+
+    /// `url`, if set, after checking it starts with one of `schemes`
+    /// (given without `://`)
+    pub fn url_for(&self, schemes: &[&str]) -> Result<Option<&str>> {
+        let Some(url) = self.url.as_deref() else {
+            return Ok(None);
+        };
+        if schemes.iter().any(|s| url.strip_prefix(s).is_some_and(|rest| rest.starts_with("://"))) {
+            return Ok(Some(url));
+        }
+        let found = url.split_once("://").map_or(url, |(scheme, _)| scheme);
+        anyhow::bail!(
+            "url scheme {}:// does not match type {}; expected {}",
+            found,
+            self.r#type.as_str(),
+            schemes.iter().map(|s| format!("{}://", s)).collect::<Vec<_>>().join(" or ")
+        )
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.unwrap_or(false)
+    }
 }
 
 pub fn load_connections() -> Result<Vec<Connection>> {
-    let config = Config::new()?;
-    Ok(config.conn)
+    Config::load_all()
 }
@@ -0,0 +1,34 @@
+use anyhow::Result;
+
+/// Keyring service used when `password_keyring` names only an account
+const DEFAULT_SERVICE: &str = "clazydbm";
+
+/// Keyring entry for a `password_keyring` value: `service/account`, or just
+/// `account` under the `clazydbm` service
+fn entry(key: &str) -> Result<keyring::Entry> {
+    let (service, account) = key.split_once('/').unwrap_or((DEFAULT_SERVICE, key));
+    keyring::Entry::new(service, account).map_err(|e| describe(key, e))
+}
+
+/// Password stored in the OS keyring under `key`
+pub fn lookup(key: &str) -> Result<String> {
+    entry(key)?.get_password().map_err(|e| describe(key, e))
+}
+
+/// Store `password` in the OS keyring under `key`, replacing any previous one
+pub fn store(key: &str, password: &str) -> Result<()> {
+    entry(key)?.set_password(password).map_err(|e| describe(key, e))
+}
+
+fn describe(key: &str, e: keyring::Error) -> anyhow::Error {
+    match e {
+        keyring::Error::NoEntry => anyhow::anyhow!(
+            "password_keyring {}: no such keyring entry; store it from the connection editor",
+            key
+        ),
+        keyring::Error::NoStorageAccess(e) => {
+            anyhow::anyhow!("password_keyring {}: the keyring is locked or unavailable: {}", key, e)
+        }
+        e => anyhow::anyhow!("password_keyring {}: {}", key, e),
+    }
+}
@@ -0,0 +1,204 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Connection;
+use crate::logger::{debug, warn};
+
+/// How long to wait for the forwarded port to accept connections
+const OPEN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Lines of ssh's stderr kept to explain why it exited
+const STDERR_TAIL: usize = 5;
+
+/// `ssh` block of a connection: the bastion host to forward through
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct SshConfig {
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    /// Private key file; when unset ssh uses the agent and its default keys
+    pub key: Option<PathBuf>,
+}
+
+/// A running `ssh -N -L` forwarding a local port to the database host
+pub struct Tunnel {
+    child: Mutex<Child>,
+    local_port: u16,
+}
+
+impl Tunnel {
+    fn is_alive(&self) -> bool {
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        matches!(child.try_wait(), Ok(None))
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        let child = self.child.get_mut().unwrap_or_else(|e| e.into_inner());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Keeps a tunnel open while held
+pub type TunnelGuard = Arc<Tunnel>;
+
+/// Open tunnels, keyed by (ssh config, database host, database port) and
+/// shared by every fetch for that target
+#[allow(clippy::type_complexity)]
+static TUNNELS: Mutex<Vec<((SshConfig, String, u64), Arc<Tunnel>)>> = Mutex::new(Vec::new());
+
+/// The connection to hand to a backend. For connections with an `ssh` block
+/// this is a copy pointing at the local end of the tunnel, plus the guard
+/// that must outlive the fetch; otherwise `conn` itself.
+pub fn effective(conn: &Connection) -> Result<(Cow<'_, Connection>, Option<TunnelGuard>)> {
+    let Some(ssh) = &conn.ssh else {
+        return Ok((Cow::Borrowed(conn), None));
+    };
+    if conn.url.is_some() {
+        anyhow::bail!("ssh tunnel needs the host and port fields; it can't be combined with url");
+    }
+    if conn.socket.is_some() {
+        anyhow::bail!("ssh tunnel forwards a host and port; it can't be combined with socket");
+    }
+    let host = conn
+        .host
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("ssh tunnel needs the host field"))?;
+    let port = conn
+        .port
+        .ok_or_else(|| anyhow::anyhow!("ssh tunnel needs the port field"))?;
+    let tunnel = acquire(ssh, host, port)?;
+    let mut local = conn.clone();
+    local.host = Some("127.0.0.1".to_string());
+    local.port = Some(u64::from(tunnel.local_port));
+    Ok((Cow::Owned(local), Some(tunnel)))
+}
+
+/// Shut down every tunnel; called on exit so no ssh process outlives the app
+pub fn close_all() {
+    TUNNELS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn acquire(ssh: &SshConfig, host: String, port: u64) -> Result<Arc<Tunnel>> {
+    let key = (ssh.clone(), host, port);
+    // Held while opening so concurrent fetches don't start duplicate tunnels
+    let mut tunnels = TUNNELS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut previous_port = None;
+    if let Some(i) = tunnels.iter().position(|(k, _)| *k == key) {
+        if tunnels[i].1.is_alive() {
+            return Ok(tunnels[i].1.clone());
+        }
+        // Dropped mid-session (network blip, bastion restart): reopen, on the
+        // same local port when possible so pooled client URLs stay valid
+        warn(&format!("ssh tunnel via {} dropped, reopening", ssh.host));
+        previous_port = Some(tunnels[i].1.local_port);
+        tunnels.swap_remove(i);
+    }
+    let tunnel = Arc::new(open(ssh, &key.1, key.2, previous_port)?);
+    tunnels.push((key, tunnel.clone()));
+    Ok(tunnel)
+}
+
+fn open(ssh: &SshConfig, host: &str, port: u64, preferred_port: Option<u16>) -> Result<Tunnel> {
+    let local_port = free_local_port(preferred_port)?;
+    let mut cmd = Command::new("ssh");
+    cmd.arg("-N")
+        .arg("-L")
+        .arg(format!("127.0.0.1:{}:{}:{}", local_port, host, port))
+        // Fail instead of prompting: the TUI owns the terminal
+        .args(["-o", "BatchMode=yes", "-o", "ExitOnForwardFailure=yes"])
+        .args(["-o", "ServerAliveInterval=30"]);
+    if let Some(p) = ssh.port {
+        cmd.arg("-p").arg(p.to_string());
+    }
+    if let Some(key) = &ssh.key {
+        cmd.arg("-i").arg(key);
+    }
+    cmd.arg(match &ssh.user {
+        Some(user) => format!("{}@{}", user, ssh.host),
+        None => ssh.host.clone(),
+    });
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+
+    debug(&format!("ssh: forwarding 127.0.0.1:{} -> {}:{} via {}", local_port, host, port, ssh.host));
+    let mut child = cmd.spawn().context("failed to run ssh; is it installed?")?;
+    let stderr = child.stderr.take().map(drain_lines);
+    let deadline = Instant::now() + OPEN_TIMEOUT;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            let tail = stderr.and_then(|s| s.join().ok()).unwrap_or_default();
+            return Err(open_error(ssh, status, tail.join("\n").trim()));
+        }
+        if TcpStream::connect(("127.0.0.1", local_port)).is_ok() {
+            return Ok(Tunnel {
+                child: Mutex::new(child),
+                local_port,
+            });
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("ssh tunnel via {} not up after {}s", ssh.host, OPEN_TIMEOUT.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Read `pipe` to the end on its own thread, logging each line and keeping
+/// the last `STDERR_TAIL`. A tunnel's ssh runs for the whole session, and
+/// warnings left unread would fill the pipe and stall it.
+fn drain_lines(pipe: impl Read + Send + 'static) -> JoinHandle<Vec<String>> {
+    std::thread::spawn(move || {
+        let mut tail = VecDeque::with_capacity(STDERR_TAIL);
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            debug(&format!("ssh: {}", line));
+            if tail.len() == STDERR_TAIL {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+        tail.into()
+    })
+}
+
+fn free_local_port(preferred: Option<u16>) -> Result<u16> {
+    if let Some(port) = preferred {
+        if TcpListener::bind(("127.0.0.1", port)).is_ok() {
+            return Ok(port);
+        }
+    }
+    Ok(TcpListener::bind(("127.0.0.1", 0))?.local_addr()?.port())
+}
+
+fn open_error(ssh: &SshConfig, status: ExitStatus, stderr: &str) -> anyhow::Error {
+    let hint = if stderr.contains("Permission denied") && ssh.key.is_some() {
+        "\nif the key is passphrase-protected, load it into ssh-agent (`ssh-add`)"
+    } else {
+        ""
+    };
+    anyhow::anyhow!("ssh to {} exited ({}): {}{}", ssh.host, status, stderr, hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drained_output_keeps_the_last_lines() {
+        let output: String = (1..=8).map(|i| format!("line {}\n", i)).collect();
+        let tail = drain_lines(std::io::Cursor::new(output)).join().unwrap();
+        assert_eq!(tail, ["line 4", "line 5", "line 6", "line 7", "line 8"]);
+    }
+}
@@ -0,0 +1,522 @@
+use anyhow::Result;
+
+use crate::component::{Child, Database, Schema, Table, TableKind};
+use crate::{connection::Connection, db::DBBehavior};
+use crate::db::{contains_pattern, ddl_script, match_counts, plan_lines, CliTarget, ServerInfo, ForeignKeyInfo, IndexInfo, RecordQuery, Records, ColumnInfo, RowBudget, RowChunks, RowCount, TableProperties};
+use crate::db::sqlite::file_path;
+use crate::logger::debug;
+use duckdb::OptionalExt;
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Instant;
+
+pub struct DuckDb {}
+
+impl DuckDb {
+    fn path(conn: &Connection) -> Result<PathBuf> {
+        file_path(conn, "duckdb")
+    }
+
+    /// DuckDB allows a single read-write process per file; browsing read-only
+    /// leaves the file usable by whatever else has it open
+    fn open(conn: &Connection) -> Result<duckdb::Connection> {
+        let config = duckdb::Config::default().access_mode(duckdb::AccessMode::ReadOnly)?;
+        Ok(duckdb::Connection::open_with_flags(Self::path(conn)?, config)?)
+    }
+
+    /// Read-write handle for edits; fails while another process has the file open for writing
+    fn open_writable(conn: &Connection) -> Result<duckdb::Connection> {
+        Ok(duckdb::Connection::open(Self::path(conn)?)?)
+    }
+
+    /// Schema of `table`: the one given by the tree, else looked up
+    /// preferring `main` when the name is ambiguous
+    fn table_schema(dc: &duckdb::Connection, schema: Option<&str>, table: &str) -> Result<String> {
+        if let Some(schema) = schema {
+            return Ok(schema.to_string());
+        }
+        Ok(dc.query_row(
+            "SELECT table_schema FROM information_schema.tables WHERE table_name = ?
+             ORDER BY table_schema <> 'main', table_schema LIMIT 1",
+            duckdb::params![table],
+            |row| row.get(0),
+        )?)
+    }
+}
+
+/// Double-quote an identifier
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// `?` placeholders for VARCHAR parameters written to `columns` of
+/// `schema.table`, each cast to its column's declared type
+fn typed_params(dc: &duckdb::Connection, schema: &str, table: &str, columns: &[&str]) -> Result<Vec<String>> {
+    let mut stmt = dc.prepare(
+        "SELECT column_name, data_type FROM information_schema.columns
+         WHERE table_schema = ? AND table_name = ?",
+    )?;
+    let types: HashMap<String, String> = stmt
+        .query_map(duckdb::params![schema, table], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    columns
+        .iter()
+        .map(|col| {
+            let ty = types
+                .get(*col)
+                .ok_or_else(|| anyhow::anyhow!("column {} not found in {}.{}", col, schema, table))?;
+            Ok(format!("CAST(? AS {})", ty))
+        })
+        .collect()
+}
+
+/// `a = ? AND b = ? ...` matching `key` columns to `params`
+fn key_condition(key: &[(String, String)], params: &[String]) -> String {
+    key.iter()
+        .zip(params)
+        .map(|((col, _), p)| format!("{} = {}", quote(col), p))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Read rows of VARCHAR `columns` until the memory cap, passing them on
+/// through `chunks` as they're read
+fn read_text_rows(
+    conn: &Connection,
+    rows: &mut duckdb::Rows<'_>,
+    columns: &[String],
+    mut chunks: RowChunks,
+) -> Result<(Vec<Vec<String>>, bool)> {
+    let mut rows_vec = Vec::new();
+    // No binary values: every column is cast to VARCHAR
+    let mut blobs = HashMap::new();
+    let mut budget = RowBudget::for_connection(conn);
+    while let Some(row) = rows.next()? {
+        let mut v = Vec::with_capacity(columns.len());
+        for i in 0..columns.len() {
+            v.push(row.get::<_, Option<String>>(i)?.unwrap_or_default());
+        }
+        if !budget.admit(&v) {
+            return Ok((rows_vec, true));
+        }
+        rows_vec.push(v);
+        chunks.flush(columns, &mut rows_vec, &mut blobs);
+    }
+    Ok((rows_vec, false))
+}
+
+impl DBBehavior for DuckDb {
+    fn database_url(conn: &Connection) -> Result<String> {
+        let path = Self::path(conn)?;
+        let path = path.to_str().ok_or_else(|| anyhow::anyhow!("path {} is not valid UTF-8", path.display()))?;
+        Ok(format!("duckdb://{path}"))
+    }
+    fn quote_identifier(ident: &str) -> String {
+        quote(ident)
+    }
+    fn ping(conn: &Connection) -> Result<()> {
+        Self::open(conn)?.execute_batch("SELECT 1")?;
+        Ok(())
+    }
+    fn server_info(conn: &Connection) -> Result<ServerInfo> {
+        // The library linked into clazydbm, e.g. `v1.1.3`
+        let dc = Self::open(conn)?;
+        let version: String = dc.query_row("SELECT version()", [], |row| row.get(0))?;
+        let started = Instant::now();
+        dc.execute_batch("SELECT 1")?;
+        Ok(ServerInfo { version: format!("DuckDB {}", version), latency: started.elapsed() })
+    }
+    fn fetch_databases(conn: &Connection) -> Result<Vec<Database>> {
+        debug("duckdb: opening file");
+        let path = Self::path(conn)?;
+        let dbname = conn
+            .name
+            .clone()
+            .or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "duckdb".to_string());
+
+        let dc = Self::open(conn)?;
+        debug("duckdb: opened");
+        let mut stmt = dc.prepare(
+            "SELECT table_schema, table_name, table_type FROM information_schema.tables
+             WHERE table_type IN ('BASE TABLE', 'VIEW')
+             ORDER BY table_schema, table_name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+
+        let mut children: Vec<Child> = Vec::new();
+        for r in rows {
+            let (schema, name, table_type) = r?;
+            let table = Table {
+                name,
+                engine: None,
+                schema: Some(schema.clone()),
+                kind: TableKind::from_table_type(&table_type),
+            };
+            match children.last_mut() {
+                Some(Child::Schema(s)) if s.name == schema => s.tables.push(table),
+                _ => children.push(Child::Schema(Schema { name: schema, tables: vec![table], is_expanded: false })),
+            }
+        }
+
+        Ok(vec![Database::new(dbname, children)])
+    }
+
+    fn fetch_records(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        query: &RecordQuery,
+    ) -> Result<Records> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+
+        // columns
+        let mut col_stmt = dc.prepare(
+            "SELECT column_name FROM information_schema.columns
+             WHERE table_schema = ? AND table_name = ? ORDER BY ordinal_position",
+        )?;
+        let columns = col_stmt
+            .query_map(duckdb::params![schema, table], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        if columns.is_empty() {
+            anyhow::bail!("no columns found for {}.{}", schema, table);
+        }
+
+        // rows: cast every column to VARCHAR for consistent string output
+        let select_list = columns
+            .iter()
+            .map(|c| format!("CAST({} AS VARCHAR)", quote(c)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let q = format!(
+            "SELECT {} FROM {}.{}{}{} LIMIT {} OFFSET {}",
+            select_list, quote(&schema), quote(table),
+            query.where_clause(), query.order_clause(), query.limit, query.offset
+        );
+        let mut stmt = dc.prepare(&q)?;
+        let mut rows = stmt.query([])?;
+        let (rows_vec, truncated) = read_text_rows(conn, &mut rows, &columns, RowChunks::new(query))?;
+
+        Ok(Records { columns, rows: rows_vec, truncated, blobs: HashMap::new(), kinds: Vec::new() })
+    }
+
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records> {
+        let sql = sql.trim().trim_end_matches(';');
+        let first = sql.split_whitespace().next().unwrap_or_default().to_lowercase();
+        let returns_rows = matches!(
+            first.as_str(),
+            "select" | "with" | "values" | "from" | "table" | "describe" | "show" | "summarize" | "pivot" | "unpivot"
+        );
+        if !returns_rows {
+            // Writes need the read-write handle, as cell edits do
+            if conn.is_read_only() {
+                anyhow::bail!("the connection is read-only");
+            }
+            let n = Self::open_writable(conn)?.execute(sql, [])?;
+            return Ok(Records::message(format!("{} rows affected", n)));
+        }
+
+        // Cast every column to VARCHAR, keeping the names
+        let dc = Self::open(conn)?;
+        let mut stmt = dc.prepare(&format!("SELECT COLUMNS(*)::VARCHAR FROM ({}) AS q", sql))?;
+        let mut rows = stmt.query([])?;
+        let columns: Vec<String> = rows
+            .as_ref()
+            .map(|s| s.column_names())
+            .unwrap_or_default();
+        let (rows_vec, truncated) = read_text_rows(conn, &mut rows, &columns, RowChunks::default())?;
+
+        Ok(Records { columns, rows: rows_vec, truncated, blobs: HashMap::new(), kinds: Vec::new() })
+    }
+
+    fn explain(conn: &Connection, sql: &str, analyze: bool) -> Result<Vec<String>> {
+        // Opened read-only, so ANALYZE can't change anything
+        let dc = Self::open(conn)?;
+        let options = if analyze { "ANALYZE " } else { "" };
+        let mut stmt = dc.prepare(&format!("EXPLAIN {}{}", options, sql.trim().trim_end_matches(';')))?;
+        // (explain_key, explain_value): the value is the rendered plan
+        let plans = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<duckdb::Result<Vec<_>>>()?;
+        Ok(plan_lines(plans))
+    }
+
+    fn update_cell(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let dc = Self::open_writable(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        let names: Vec<&str> = std::iter::once(column).chain(key.iter().map(|(c, _)| c.as_str())).collect();
+        let params = typed_params(&dc, &schema, table, &names)?;
+        let q = format!(
+            "UPDATE {}.{} SET {} = {} WHERE {}",
+            quote(&schema),
+            quote(table),
+            quote(column),
+            params[0],
+            key_condition(key, &params[1..])
+        );
+        let values = std::iter::once(value).chain(key.iter().map(|(_, v)| Some(v.as_str())));
+        let n = dc.execute(&q, duckdb::params_from_iter(values))?;
+        Ok(n as u64)
+    }
+
+    fn delete_row(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let dc = Self::open_writable(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        let names: Vec<&str> = key.iter().map(|(c, _)| c.as_str()).collect();
+        let params = typed_params(&dc, &schema, table, &names)?;
+        let q = format!(
+            "DELETE FROM {}.{} WHERE {}",
+            quote(&schema),
+            quote(table),
+            key_condition(key, &params)
+        );
+        let n = dc.execute(&q, duckdb::params_from_iter(key.iter().map(|(_, v)| v.as_str())))?;
+        Ok(n as u64)
+    }
+
+    fn insert_row(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64> {
+        let dc = Self::open_writable(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        let target = format!("{}.{}", quote(&schema), quote(table));
+        let q = if values.is_empty() {
+            format!("INSERT INTO {} DEFAULT VALUES", target)
+        } else {
+            let names: Vec<&str> = values.iter().map(|(c, _)| c.as_str()).collect();
+            let params = typed_params(&dc, &schema, table, &names)?;
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                target,
+                names.iter().map(|c| quote(c)).collect::<Vec<_>>().join(", "),
+                params.join(", ")
+            )
+        };
+        let n = dc.execute(&q, duckdb::params_from_iter(values.iter().map(|(_, v)| v.as_str())))?;
+        Ok(n as u64)
+    }
+
+    fn fetch_properties(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<TableProperties> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        let mut stmt = dc.prepare(
+            "SELECT c.column_name, c.data_type, c.is_nullable, c.column_default,
+                    EXISTS (
+                        SELECT 1 FROM duckdb_constraints() k
+                        WHERE k.schema_name = c.table_schema AND k.table_name = c.table_name
+                          AND k.constraint_type = 'PRIMARY KEY'
+                          AND list_contains(k.constraint_column_names, c.column_name)
+                    )
+             FROM information_schema.columns c
+             WHERE c.table_schema = ? AND c.table_name = ?
+             ORDER BY c.ordinal_position",
+        )?;
+        let rows = stmt.query_map(duckdb::params![schema, table], |row| {
+            let is_nullable: String = row.get(2)?;
+            Ok(ColumnInfo {
+                name: row.get(0)?,
+                data_type: row.get(1)?,
+                nullable: is_nullable.eq_ignore_ascii_case("YES"),
+                default: row.get(3)?,
+                primary_key: row.get(4)?,
+                charset: None,
+                collation: None,
+                comment: None,
+            })
+        })?;
+        let mut columns = Vec::new();
+        for r in rows { columns.push(r?); }
+        Ok(TableProperties { columns })
+    }
+
+    fn fetch_indexes(conn: &Connection, _database: &str, schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        // Key and unique constraints are not listed by duckdb_indexes(), whose
+        // key columns are only available inside the CREATE INDEX text
+        let mut stmt = dc.prepare(
+            r"SELECT lower(constraint_type) AS name, true, array_to_string(constraint_column_names, ', ')
+             FROM duckdb_constraints()
+             WHERE schema_name = ? AND table_name = ? AND constraint_type IN ('PRIMARY KEY', 'UNIQUE')
+             UNION ALL
+             SELECT index_name, is_unique, regexp_extract(sql, '\((.*)\)', 1)
+             FROM duckdb_indexes()
+             WHERE schema_name = ? AND table_name = ?
+             ORDER BY name",
+        )?;
+        let rows = stmt.query_map(duckdb::params![schema, table, schema, table], |row| {
+            let columns: String = row.get(2)?;
+            Ok(IndexInfo {
+                name: row.get(0)?,
+                columns: columns.split(", ").map(str::to_string).collect(),
+                unique: row.get(1)?,
+            })
+        })?;
+        let mut indexes = Vec::new();
+        for r in rows { indexes.push(r?); }
+        Ok(indexes)
+    }
+
+    fn fetch_foreign_keys(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        // DuckDB has no referential actions; references stay within the schema
+        let mut stmt = dc.prepare(
+            "SELECT array_to_string(constraint_column_names, '\x1f'), referenced_table,
+                    array_to_string(referenced_column_names, '\x1f')
+             FROM duckdb_constraints()
+             WHERE schema_name = ? AND table_name = ? AND constraint_type = 'FOREIGN KEY'",
+        )?;
+        let rows = stmt.query_map(duckdb::params![schema, table], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        })?;
+        let mut keys = Vec::new();
+        for r in rows {
+            let (columns, referenced_table, referenced_columns) = r?;
+            for (column, referenced_column) in columns.split('\x1f').zip(referenced_columns.split('\x1f')) {
+                keys.push(ForeignKeyInfo {
+                    column: column.to_string(),
+                    referenced_database: None,
+                    referenced_schema: Some(schema.clone()),
+                    referenced_table: referenced_table.clone(),
+                    referenced_column: referenced_column.to_string(),
+                    on_delete: "NO ACTION".to_string(),
+                    on_update: "NO ACTION".to_string(),
+                });
+            }
+        }
+        Ok(keys)
+    }
+
+    fn fetch_ddl(conn: &Connection, _database: &str, schema: Option<&str>, table: &str) -> Result<String> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        let mut stmt = dc.prepare(
+            "SELECT 0 AS o, sql FROM duckdb_tables() WHERE schema_name = ? AND table_name = ?
+             UNION ALL
+             SELECT 0, sql FROM duckdb_views() WHERE schema_name = ? AND view_name = ?
+             UNION ALL
+             SELECT 1, sql FROM duckdb_indexes() WHERE schema_name = ? AND table_name = ? AND sql IS NOT NULL
+             ORDER BY o",
+        )?;
+        let statements = stmt
+            .query_map(duckdb::params![schema, table, schema, table, schema, table], |row| row.get::<_, String>(1))?
+            .collect::<duckdb::Result<Vec<_>>>()?;
+        if statements.is_empty() {
+            anyhow::bail!("table {}.{} not found", schema, table);
+        }
+        Ok(ddl_script(statements))
+    }
+
+    fn fetch_row_count(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        exact: bool,
+    ) -> Result<RowCount> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+
+        if !exact {
+            // Maintained by DuckDB itself; can lag behind uncheckpointed changes.
+            // Views have no row in duckdb_tables() and get counted below.
+            let est: Option<i64> = dc
+                .query_row(
+                    "SELECT estimated_size FROM duckdb_tables() WHERE schema_name = ? AND table_name = ?",
+                    duckdb::params![schema, table],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .flatten();
+            if let Some(n) = est {
+                return Ok(RowCount::Estimate(n as u64));
+            }
+        }
+
+        let q = format!("SELECT COUNT(*) FROM {}.{}", quote(&schema), quote(table));
+        let n: i64 = dc.query_row(&q, [], |row| row.get(0))?;
+        Ok(RowCount::Exact(n as u64))
+    }
+
+    fn count_matches(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>> {
+        let dc = Self::open(conn)?;
+        let schema = Self::table_schema(&dc, schema, table)?;
+        let q = format!(
+            "SELECT {} FROM {}.{}",
+            match_counts(columns.iter().map(|c| quote(c)), "ILIKE", "$1"),
+            quote(&schema),
+            quote(table)
+        );
+        let counts = dc.query_row(&q, duckdb::params![contains_pattern(needle)], |row| {
+            (0..columns.len()).map(|i| row.get::<_, i64>(i).map(|n| n as u64)).collect()
+        })?;
+        Ok(counts)
+    }
+
+    fn cli_tool_name() -> &'static str {
+        "duckdb"
+    }
+
+    fn is_cli_tool_available() -> bool {
+        Command::new("which")
+            .arg("duckdb")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn cli_command(conn: &Connection, _target: Option<&CliTarget>) -> Result<Command> {
+        let mut cmd = Command::new("duckdb");
+        cmd.arg(Self::path(conn)?);
+        Ok(cmd)
+    }
+
+    fn launch_cli_tool(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::ExitStatus> {
+        let mut cmd = Self::cli_command(conn, target)?;
+        debug(&format!("Launching duckdb with file: {:?}", cmd.get_args().next()));
+
+        cmd.status()
+            .map_err(|e| anyhow::anyhow!("Failed to launch duckdb: {}", e))
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+use super::{ColumnKind, Records};
+
+/// Field separator used until the delimiter becomes configurable
+pub const DEFAULT_CSV_DELIMITER: char = ',';
+
+/// How cell values are represented in exported JSON
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonValues {
+    /// Every value as a JSON string, exactly as loaded
+    Strings,
+    /// Numbers and booleans as JSON numbers and booleans, by `Records::kind`;
+    /// an empty value in such a column (how NULL is loaded) becomes `null`.
+    /// Numbers too long for a double stay strings so they aren't rounded.
+    Typed,
+}
+
+/// Digits a double holds without rounding
+const EXACT_DOUBLE_DIGITS: usize = 15;
+
+/// Write `records` (header row first) as RFC 4180 CSV to a new
+/// `<table>-<unix time>.csv` in `dir` and return its path
+pub fn write_csv(records: &Records, dir: &Path, table: &str, delimiter: char) -> Result<PathBuf> {
+    let path = export_path(dir, table, "csv");
+    let file = File::create(&path).with_context(|| format!("cannot create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    write_csv_record(&mut out, &records.columns, delimiter)?;
+    for row in &records.rows {
+        write_csv_record(&mut out, row, delimiter)?;
+    }
+    out.flush()?;
+    Ok(path)
+}
+
+/// Write `records` as a JSON array of objects keyed by column name to a new
+/// `<table>-<unix time>.json` in `dir` and return its path. An empty result
+/// is written as `[]`.
+pub fn write_json(records: &Records, dir: &Path, table: &str, values: JsonValues) -> Result<PathBuf> {
+    let path = export_path(dir, table, "json");
+    let file = File::create(&path).with_context(|| format!("cannot create {}", path.display()))?;
+    let mut out = BufWriter::new(file);
+    write_json_to(&mut out, records, values)?;
+    out.flush()?;
+    Ok(path)
+}
+
+/// `write_json` to any writer, e.g. stdout
+pub fn write_json_to(out: &mut impl Write, records: &Records, values: JsonValues) -> Result<()> {
+    let rows: Vec<serde_json::Value> = records
+        .rows
+        .iter()
+        .map(|row| {
+            let object = records
+                .columns
+                .iter()
+                .zip(row)
+                .enumerate()
+                .map(|(i, (col, cell))| (col.clone(), json_value(cell, records.kind(i), values)))
+                .collect::<serde_json::Map<_, _>>();
+            serde_json::Value::Object(object)
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *out, &rows)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Write `records` as one JSON object, `{"columns": [...], "rows": [[...], ...]}`;
+/// unlike an object per row, it names the columns of an empty result and
+/// keeps columns that share a name
+pub fn write_json_table(out: &mut impl Write, records: &Records, values: JsonValues) -> Result<()> {
+    let rows: Vec<Vec<serde_json::Value>> = records
+        .rows
+        .iter()
+        .map(|row| row.iter().enumerate().map(|(i, cell)| json_value(cell, records.kind(i), values)).collect())
+        .collect();
+    let table = serde_json::json!({ "columns": records.columns, "rows": rows });
+    serde_json::to_writer_pretty(&mut *out, &table)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn json_value(cell: &str, kind: ColumnKind, values: JsonValues) -> serde_json::Value {
+    use serde_json::Value;
+    if values == JsonValues::Strings {
+        return Value::String(cell.to_string());
+    }
+    match kind {
+        ColumnKind::Number | ColumnKind::Boolean if cell.is_empty() => Value::Null,
+        ColumnKind::Number => {
+            if let Ok(n) = cell.parse::<i64>() {
+                return n.into();
+            }
+            if let Ok(n) = cell.parse::<u64>() {
+                return n.into();
+            }
+            let digits = cell.chars().filter(char::is_ascii_digit).count();
+            match cell.parse::<f64>().ok().and_then(serde_json::Number::from_f64) {
+                Some(n) if digits <= EXACT_DOUBLE_DIGITS => Value::Number(n),
+                _ => Value::String(cell.to_string()),
+            }
+        }
+        ColumnKind::Boolean => match kind.format(cell).as_ref() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            other => Value::String(other.to_string()),
+        },
+        ColumnKind::Date | ColumnKind::Timestamp | ColumnKind::Text => Value::String(kind.format(cell).into_owned()),
+    }
+}
+
+/// One CSV line of `fields`; `write_csv` writes the header and each row this way
+pub fn write_csv_record(out: &mut impl Write, fields: &[String], delimiter: char) -> Result<()> {
+    let line = fields
+        .iter()
+        .map(|f| csv_field(f, delimiter))
+        .collect::<Vec<_>>()
+        .join(delimiter.encode_utf8(&mut [0; 4]));
+    // RFC 4180 records end with CRLF
+    write!(out, "{}\r\n", line)?;
+    Ok(())
+}
+
+/// Quote a field when it contains the delimiter, a quote or a line break,
+/// doubling embedded quotes
+fn csv_field(value: &str, delimiter: char) -> std::borrow::Cow<'_, str> {
+    if value.contains([delimiter, '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+/// `records` as a GitHub-flavored Markdown table, header row first and
+/// numeric columns right-aligned
+pub fn markdown_table(records: &Records) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut out = line(records.columns.iter().map(|c| markdown_cell(c)).collect());
+    let align = (0..records.columns.len())
+        .map(|i| if records.kind(i).right_aligned() { "---:" } else { "---" }.to_string())
+        .collect();
+    out.push_str(&line(align));
+    for row in &records.rows {
+        out.push_str(&line(row.iter().map(|cell| markdown_cell(cell)).collect()));
+    }
+    out
+}
+
+/// A value that can't break the table: `|` is escaped (along with `\`, so an
+/// existing escape survives) and line breaks become `<br>`
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
+
+/// `<dir>/<table>-<unix time>.<ext>`, with a `-N` suffix when that file
+/// already exists. The table name is sanitized so odd characters can't
+/// escape `dir`.
+fn export_path(dir: &Path, table: &str, ext: &str) -> PathBuf {
+    let name: String = table
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_') { c } else { '_' })
+        .collect();
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut path = dir.join(format!("{}-{}.{}", name, secs, ext));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}-{}.{}", name, secs, n, ext));
+        n += 1;
+    }
+    path
+}
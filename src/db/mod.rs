@@ -1,16 +1,30 @@
+#[cfg(feature = "duckdb")]
+mod duckdb;
+pub mod export;
+#[cfg(feature = "mssql")]
+mod mssql;
 mod mysql;
+mod pgpass;
+mod pool;
 mod postgres;
 mod sqlite;
 
-use crate::{component::Database, connection::Connection};
+use crate::{component::Database, connection::{tunnel, Connection}};
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "duckdb")]
+pub use duckdb::DuckDb;
+#[cfg(feature = "mssql")]
+pub use mssql::MsSql;
 pub use mysql::Mysql;
 pub use postgres::Postgres;
 pub use sqlite::Sqlite;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum DatabaseType {
     #[serde(rename = "mysql")]
     MySql,
@@ -18,28 +32,196 @@ pub enum DatabaseType {
     Postgres,
     #[serde(rename = "sqlite")]
     Sqlite,
+    #[serde(rename = "mssql")]
+    MsSql,
+    #[serde(rename = "duckdb")]
+    DuckDb,
 }
 
+impl DatabaseType {
+    pub const ALL: [DatabaseType; 5] = [
+        DatabaseType::MySql,
+        DatabaseType::Postgres,
+        DatabaseType::Sqlite,
+        DatabaseType::MsSql,
+        DatabaseType::DuckDb,
+    ];
+
+    /// Name used for `type` in the config
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DatabaseType::MySql => "mysql",
+            DatabaseType::Postgres => "postgres",
+            DatabaseType::Sqlite => "sqlite",
+            DatabaseType::MsSql => "mssql",
+            DatabaseType::DuckDb => "duckdb",
+        }
+    }
+
+    /// Whether the database is a local file (`path`) rather than a server
+    pub fn is_file(self) -> bool {
+        matches!(self, DatabaseType::Sqlite | DatabaseType::DuckDb)
+    }
+}
+
+/// Error for a backend left out of this build; its cargo feature is named
+/// like its `type`
+fn not_built(r#type: DatabaseType) -> anyhow::Error {
+    anyhow::anyhow!(
+        "this build of clazydbm has no {0} support (rebuild with the `{0}` cargo feature)",
+        r#type.as_str()
+    )
+}
+
+/// How long `DB::ping` waits before reporting a connection as unreachable
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub trait DBBehavior: Send + Sync {
     fn database_url(conn: &Connection) -> Result<String>;
+    /// `ident` quoted as a table or column name in this backend's SQL
+    fn quote_identifier(ident: &str) -> String;
+    /// Check that `conn` answers a trivial query, on a fresh (unpooled) connection
+    fn ping(conn: &Connection) -> Result<()>;
+    /// Server product and version, and the round trip of a trivial query
+    fn server_info(conn: &Connection) -> Result<ServerInfo>;
     fn fetch_databases(conn: &Connection) -> Result<Vec<Database>>;
+    /// Table-level methods get the table's `schema` from the tree where it has
+    /// one (Postgres, SQL Server, DuckDB); MySQL and SQLite ignore it
     fn fetch_records(
         conn: &Connection,
         database: &str,
+        schema: Option<&str>,
         table: &str,
-        limit: usize,
-        offset: usize,
+        query: &RecordQuery,
     ) -> Result<Records>;
+    /// Run a statement typed by the user. A row-returning statement yields its
+    /// first result set; any other a one-cell summary (see `Records::message`).
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records>;
+    /// Execution plan of a statement typed by the user, as lines of text. With
+    /// `analyze` the statement is run to report actual timings, inside a
+    /// transaction that is rolled back where the backend needs one.
+    fn explain(conn: &Connection, sql: &str, analyze: bool) -> Result<Vec<String>>;
+    /// Set `column` to `value` in the row whose primary-key columns hold the
+    /// `key` values, returning the number of rows changed. Values are sent as
+    /// text and converted to the column types by the server; `None` writes NULL.
+    fn update_cell(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64>;
+    /// Delete the row whose primary-key columns hold the `key` values,
+    /// returning the number of rows deleted
+    fn delete_row(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64>;
+    /// Insert a row setting the given (column, value) pairs, text converted
+    /// as in `update_cell`; other columns take their defaults
+    fn insert_row(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64>;
     fn fetch_properties(
         conn: &Connection,
         database: &str,
+        schema: Option<&str>,
         table: &str,
     ) -> Result<TableProperties>;
+    /// Indexes of a table (including those backing primary keys and unique
+    /// constraints), ordered by name
+    fn fetch_indexes(conn: &Connection, database: &str, schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>>;
+    /// Foreign keys of a table, one entry per referencing column
+    fn fetch_foreign_keys(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>>;
+    /// `CREATE` statements for a table or view, followed by those of its
+    /// indexes where the backend keeps them apart
+    fn fetch_ddl(conn: &Connection, database: &str, schema: Option<&str>, table: &str) -> Result<String>;
+    /// Row count of a table. With `exact == false` a backend may answer from
+    /// catalog statistics instead of running `COUNT(*)`.
+    fn fetch_row_count(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        exact: bool,
+    ) -> Result<RowCount>;
+    /// For each of `columns`, the number of rows whose value contains `needle`.
+    /// Case is ignored where the backend's `LIKE` allows it (`ILIKE` on Postgres
+    /// and DuckDB); every row is scanned, so this can be slow on large tables.
+    fn count_matches(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>>;
     
     // CLI tool related methods
     fn cli_tool_name() -> &'static str;
     fn is_cli_tool_available() -> bool;
-    fn launch_cli_tool(conn: &Connection) -> Result<std::process::ExitStatus>;
+    /// Build the CLI invocation, pre-focused on `target` as far as the tool allows.
+    fn cli_command(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::Command>;
+    fn launch_cli_tool(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::ExitStatus>;
+}
+
+/// Split a table given on the command line as `database.schema.table`,
+/// `database.table` or a bare `table` into its parts
+pub fn split_table_path(path: &str) -> (Option<&str>, Option<&str>, &str) {
+    match path.splitn(3, '.').collect::<Vec<_>>()[..] {
+        [database, schema, table] => (Some(database), Some(schema), table),
+        [database, table] => (Some(database), None, table),
+        _ => (None, None, path),
+    }
+}
+
+/// What copying a connection from the list gives: the file of SQLite and
+/// DuckDB, else `database_url` with its password masked unless `with_password`
+pub fn shareable_location(conn: &Connection, with_password: bool) -> Result<String> {
+    let url = match conn.r#type {
+        DatabaseType::Sqlite => return Ok(sqlite::file_path(conn, "sqlite")?.display().to_string()),
+        DatabaseType::DuckDb => return Ok(sqlite::file_path(conn, "duckdb")?.display().to_string()),
+        _ => DB::database_url(conn)?,
+    };
+    Ok(if with_password { url } else { mask_password(&url) })
+}
+
+/// `url` with the password in its `user:password@` part replaced by `****`.
+/// Passwords are written into URLs unescaped, so the user info runs up to the
+/// last `@`.
+fn mask_password(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let Some((user_info, host)) = rest.rsplit_once('@') else {
+        return url.to_string();
+    };
+    match user_info.split_once(':') {
+        Some((user, password)) if !password.is_empty() => format!("{}://{}:****@{}", scheme, user, host),
+        _ => url.to_string(),
+    }
+}
+
+/// Table the external CLI should open on, when launched from the tree.
+#[derive(Debug, Clone)]
+pub struct CliTarget {
+    pub database: String,
+    pub schema: Option<String>,
+    pub table: String,
 }
 
 pub struct DB;
@@ -50,22 +232,96 @@ impl DB {
             DatabaseType::MySql => Mysql::cli_tool_name(),
             DatabaseType::Postgres => Postgres::cli_tool_name(),
             DatabaseType::Sqlite => Sqlite::cli_tool_name(),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::cli_tool_name(),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::cli_tool_name(),
+            #[allow(unreachable_patterns)]
+            other => other.as_str(),
         }
     }
     
+    pub fn quote_identifier_for(conn: &Connection, ident: &str) -> String {
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::quote_identifier(ident),
+            DatabaseType::Postgres => Postgres::quote_identifier(ident),
+            DatabaseType::Sqlite => Sqlite::quote_identifier(ident),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::quote_identifier(ident),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::quote_identifier(ident),
+            // Never run: the backend's queries fail first
+            #[allow(unreachable_patterns)]
+            _ => format!("\"{}\"", ident.replace('"', "\"\"")),
+        }
+    }
+
     pub fn is_cli_tool_available_for(conn: &Connection) -> bool {
         match conn.r#type {
             DatabaseType::MySql => Mysql::is_cli_tool_available(),
             DatabaseType::Postgres => Postgres::is_cli_tool_available(),
             DatabaseType::Sqlite => Sqlite::is_cli_tool_available(),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::is_cli_tool_available(),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::is_cli_tool_available(),
+            #[allow(unreachable_patterns)]
+            _ => false,
         }
     }
     
-    pub fn launch_cli_tool_for(conn: &Connection) -> Result<std::process::ExitStatus> {
+    pub fn launch_cli_tool_for(
+        conn: &Connection,
+        target: Option<&CliTarget>,
+    ) -> Result<std::process::ExitStatus> {
+        // The tunnel stays up until the CLI exits
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
         match conn.r#type {
-            DatabaseType::MySql => Mysql::launch_cli_tool(conn),
-            DatabaseType::Postgres => Postgres::launch_cli_tool(conn),
-            DatabaseType::Sqlite => Sqlite::launch_cli_tool(conn),
+            DatabaseType::MySql => Mysql::launch_cli_tool(conn, target),
+            DatabaseType::Postgres => Postgres::launch_cli_tool(conn, target),
+            DatabaseType::Sqlite => Sqlite::launch_cli_tool(conn, target),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::launch_cli_tool(conn, target),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::launch_cli_tool(conn, target),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+}
+
+impl DB {
+    /// Call `probe` (e.g. a query on the connection) until it succeeds or
+    /// `timeout` elapses, doubling the pause between attempts (capped at 5s).
+    /// `on_retry` gets the attempt number, its error and the pause before the
+    /// next try.
+    pub fn wait_until_ready(
+        mut probe: impl FnMut() -> Result<()>,
+        timeout: Duration,
+        mut on_retry: impl FnMut(u32, &anyhow::Error, Duration),
+    ) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = Duration::from_millis(250);
+        let mut attempt = 1;
+        loop {
+            let err = match probe() {
+                Ok(_) => return Ok(()),
+                Err(e) => e,
+            };
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(err.context(format!(
+                    "not ready after {} attempts ({}s)",
+                    attempt,
+                    timeout.as_secs()
+                )));
+            }
+            let pause = delay.min(deadline - now);
+            on_retry(attempt, &err, pause);
+            std::thread::sleep(pause);
+            delay = (delay * 2).min(Duration::from_secs(5));
+            attempt += 1;
         }
     }
 }
@@ -76,37 +332,298 @@ impl DBBehavior for DB {
             DatabaseType::MySql => Mysql::database_url(conn),
             DatabaseType::Postgres => Postgres::database_url(conn),
             DatabaseType::Sqlite => Sqlite::database_url(conn),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::database_url(conn),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::database_url(conn),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn quote_identifier(_ident: &str) -> String {
+        unreachable!("Use type-specific implementations")
+    }
+    /// Gives up after `PING_TIMEOUT`; the attempt is left to finish in the background
+    fn ping(conn: &Connection) -> Result<()> {
+        let conn = conn.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let result = tunnel::effective(&conn).and_then(|(conn, _tunnel)| {
+                let conn = conn.as_ref();
+                match conn.r#type {
+                    DatabaseType::MySql => Mysql::ping(conn),
+                    DatabaseType::Postgres => Postgres::ping(conn),
+                    DatabaseType::Sqlite => Sqlite::ping(conn),
+                    #[cfg(feature = "mssql")]
+                    DatabaseType::MsSql => MsSql::ping(conn),
+                    #[cfg(feature = "duckdb")]
+                    DatabaseType::DuckDb => DuckDb::ping(conn),
+                    #[allow(unreachable_patterns)]
+                    other => Err(not_built(other)),
+                }
+            });
+            let _ = tx.send(result);
+        });
+        rx.recv_timeout(PING_TIMEOUT)
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("no answer within {}s", PING_TIMEOUT.as_secs())))
+    }
+    fn server_info(conn: &Connection) -> Result<ServerInfo> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::server_info(conn),
+            DatabaseType::Postgres => Postgres::server_info(conn),
+            DatabaseType::Sqlite => Sqlite::server_info(conn),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::server_info(conn),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::server_info(conn),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
         }
     }
     fn fetch_databases(conn: &Connection) -> Result<Vec<Database>> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
         match conn.r#type {
             DatabaseType::MySql => Mysql::fetch_databases(conn),
             DatabaseType::Postgres => Postgres::fetch_databases(conn),
             DatabaseType::Sqlite => Sqlite::fetch_databases(conn),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_databases(conn),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_databases(conn),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
         }
     }
     fn fetch_records(
         conn: &Connection,
         database: &str,
+        schema: Option<&str>,
         table: &str,
-        limit: usize,
-        offset: usize,
+        query: &RecordQuery,
     ) -> Result<Records> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::fetch_records(conn, database, schema, table, query),
+            DatabaseType::Postgres => Postgres::fetch_records(conn, database, schema, table, query),
+            DatabaseType::Sqlite => Sqlite::fetch_records(conn, database, schema, table, query),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_records(conn, database, schema, table, query),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_records(conn, database, schema, table, query),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
         match conn.r#type {
-            DatabaseType::MySql => Mysql::fetch_records(conn, database, table, limit, offset),
-            DatabaseType::Postgres => Postgres::fetch_records(conn, database, table, limit, offset),
-            DatabaseType::Sqlite => Sqlite::fetch_records(conn, database, table, limit, offset),
+            DatabaseType::MySql => Mysql::run_query(conn, sql),
+            DatabaseType::Postgres => Postgres::run_query(conn, sql),
+            DatabaseType::Sqlite => Sqlite::run_query(conn, sql),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::run_query(conn, sql),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::run_query(conn, sql),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn explain(conn: &Connection, sql: &str, analyze: bool) -> Result<Vec<String>> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::explain(conn, sql, analyze),
+            DatabaseType::Postgres => Postgres::explain(conn, sql, analyze),
+            DatabaseType::Sqlite => Sqlite::explain(conn, sql, analyze),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::explain(conn, sql, analyze),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::explain(conn, sql, analyze),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn update_cell(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::update_cell(conn, database, schema, table, column, value, key),
+            DatabaseType::Postgres => Postgres::update_cell(conn, database, schema, table, column, value, key),
+            DatabaseType::Sqlite => Sqlite::update_cell(conn, database, schema, table, column, value, key),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::update_cell(conn, database, schema, table, column, value, key),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::update_cell(conn, database, schema, table, column, value, key),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn delete_row(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::delete_row(conn, database, schema, table, key),
+            DatabaseType::Postgres => Postgres::delete_row(conn, database, schema, table, key),
+            DatabaseType::Sqlite => Sqlite::delete_row(conn, database, schema, table, key),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::delete_row(conn, database, schema, table, key),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::delete_row(conn, database, schema, table, key),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn insert_row(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::insert_row(conn, database, schema, table, values),
+            DatabaseType::Postgres => Postgres::insert_row(conn, database, schema, table, values),
+            DatabaseType::Sqlite => Sqlite::insert_row(conn, database, schema, table, values),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::insert_row(conn, database, schema, table, values),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::insert_row(conn, database, schema, table, values),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
         }
     }
     fn fetch_properties(
         conn: &Connection,
         database: &str,
+        schema: Option<&str>,
         table: &str,
     ) -> Result<TableProperties> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::fetch_properties(conn, database, schema, table),
+            DatabaseType::Postgres => Postgres::fetch_properties(conn, database, schema, table),
+            DatabaseType::Sqlite => Sqlite::fetch_properties(conn, database, schema, table),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_properties(conn, database, schema, table),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_properties(conn, database, schema, table),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn fetch_indexes(conn: &Connection, database: &str, schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::fetch_indexes(conn, database, schema, table),
+            DatabaseType::Postgres => Postgres::fetch_indexes(conn, database, schema, table),
+            DatabaseType::Sqlite => Sqlite::fetch_indexes(conn, database, schema, table),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_indexes(conn, database, schema, table),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_indexes(conn, database, schema, table),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn fetch_foreign_keys(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::fetch_foreign_keys(conn, database, schema, table),
+            DatabaseType::Postgres => Postgres::fetch_foreign_keys(conn, database, schema, table),
+            DatabaseType::Sqlite => Sqlite::fetch_foreign_keys(conn, database, schema, table),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_foreign_keys(conn, database, schema, table),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_foreign_keys(conn, database, schema, table),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn fetch_ddl(conn: &Connection, database: &str, schema: Option<&str>, table: &str) -> Result<String> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
         match conn.r#type {
-            DatabaseType::MySql => Mysql::fetch_properties(conn, database, table),
-            DatabaseType::Postgres => Postgres::fetch_properties(conn, database, table),
-            DatabaseType::Sqlite => Sqlite::fetch_properties(conn, database, table),
+            DatabaseType::MySql => Mysql::fetch_ddl(conn, database, schema, table),
+            DatabaseType::Postgres => Postgres::fetch_ddl(conn, database, schema, table),
+            DatabaseType::Sqlite => Sqlite::fetch_ddl(conn, database, schema, table),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_ddl(conn, database, schema, table),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_ddl(conn, database, schema, table),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn fetch_row_count(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        exact: bool,
+    ) -> Result<RowCount> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::fetch_row_count(conn, database, schema, table, exact),
+            DatabaseType::Postgres => Postgres::fetch_row_count(conn, database, schema, table, exact),
+            DatabaseType::Sqlite => Sqlite::fetch_row_count(conn, database, schema, table, exact),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::fetch_row_count(conn, database, schema, table, exact),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::fetch_row_count(conn, database, schema, table, exact),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
+        }
+    }
+    fn count_matches(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>> {
+        let (conn, _tunnel) = tunnel::effective(conn)?;
+        let conn = conn.as_ref();
+        match conn.r#type {
+            DatabaseType::MySql => Mysql::count_matches(conn, database, schema, table, columns, needle),
+            DatabaseType::Postgres => Postgres::count_matches(conn, database, schema, table, columns, needle),
+            DatabaseType::Sqlite => Sqlite::count_matches(conn, database, schema, table, columns, needle),
+            #[cfg(feature = "mssql")]
+            DatabaseType::MsSql => MsSql::count_matches(conn, database, schema, table, columns, needle),
+            #[cfg(feature = "duckdb")]
+            DatabaseType::DuckDb => DuckDb::count_matches(conn, database, schema, table, columns, needle),
+            #[allow(unreachable_patterns)]
+            other => Err(not_built(other)),
         }
     }
     
@@ -118,15 +635,338 @@ impl DBBehavior for DB {
         unreachable!("Use type-specific implementations")
     }
     
-    fn launch_cli_tool(_conn: &Connection) -> Result<std::process::ExitStatus> {
+    fn cli_command(_conn: &Connection, _target: Option<&CliTarget>) -> Result<std::process::Command> {
+        unreachable!("Use type-specific implementations")
+    }
+    
+    fn launch_cli_tool(_conn: &Connection, _target: Option<&CliTarget>) -> Result<std::process::ExitStatus> {
         unreachable!("Use type-specific implementations")
     }
 }
 
+/// Which rows of a table `fetch_records` returns
+#[derive(Debug, Clone, Default)]
+pub struct RecordQuery {
+    pub limit: usize,
+    pub offset: usize,
+    /// SQL predicate typed by the user, applied as `WHERE <filter>`
+    pub filter: Option<String>,
+    /// SQL sort expression, applied as `ORDER BY <order>`
+    pub order: Option<String>,
+    /// Gets the rows while the fetch still reads them; the returned `Records`
+    /// then holds only the rows after the last chunk
+    pub chunks: Option<ChunkSink>,
+}
+
+/// Rows passed on by a fetch as they are read, `RECORDS_CHUNK_ROWS` at a
+/// time. The flag marks a read's first chunk: a fetch retried after a lost
+/// connection starts over, and its first chunk replaces what came before.
+#[derive(Clone)]
+pub struct ChunkSink(pub Arc<dyn Fn(bool, Records) + Send + Sync>);
+
+impl std::fmt::Debug for ChunkSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ChunkSink")
+    }
+}
+
+/// Rows in each chunk a streaming fetch passes on
+pub const RECORDS_CHUNK_ROWS: usize = 500;
+
+/// A backend's side of `RecordQuery::chunks`: hands the rows read so far on
+/// once there are enough of them. Does nothing without a sink.
+#[derive(Default)]
+pub struct RowChunks<'a> {
+    sink: Option<&'a ChunkSink>,
+    sent: bool,
+}
+
+impl<'a> RowChunks<'a> {
+    pub fn new(query: &'a RecordQuery) -> Self {
+        Self { sink: query.chunks.as_ref(), sent: false }
+    }
+
+    /// Pass `rows` and their `blobs` (keyed by position in `rows`) on when
+    /// they fill a chunk, leaving both empty
+    pub fn flush(
+        &mut self,
+        columns: &[String],
+        rows: &mut Vec<Vec<String>>,
+        blobs: &mut HashMap<(usize, usize), Blob>,
+    ) {
+        let Some(sink) = self.sink.filter(|_| rows.len() >= RECORDS_CHUNK_ROWS) else {
+            return;
+        };
+        let chunk = Records {
+            columns: columns.to_vec(),
+            rows: std::mem::take(rows),
+            truncated: false,
+            blobs: std::mem::take(blobs),
+            kinds: Vec::new(),
+        };
+        (sink.0)(!self.sent, chunk);
+        self.sent = true;
+    }
+}
+
+impl RecordQuery {
+    /// ` WHERE (<filter>)`, or nothing without a filter
+    pub fn where_clause(&self) -> String {
+        match self.filter.as_deref().map(str::trim) {
+            Some(f) if !f.is_empty() => format!(" WHERE ({})", f),
+            _ => String::new(),
+        }
+    }
+
+    /// ` ORDER BY <order>`, or nothing without an order
+    pub fn order_clause(&self) -> String {
+        match self.order.as_deref().map(str::trim) {
+            Some(o) if !o.is_empty() => format!(" ORDER BY {}", o),
+            _ => String::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Records {
     pub columns: Vec<String>,
     pub rows: Vec<Vec<String>>, // each inner Vec is a row of stringified values
+    /// Fewer rows than requested were kept because the memory cap was reached
+    pub truncated: bool,
+    /// Raw bytes of binary cells by (row, column); the grid shows `blob_summary`
+    pub blobs: HashMap<(usize, usize), Blob>,
+    /// Display kind of each column, from the table's properties; empty until
+    /// `set_kinds` is called
+    pub kinds: Vec<ColumnKind>,
+}
+
+impl Records {
+    /// Single-cell result for a statement that returns no rows
+    pub fn message(text: impl Into<String>) -> Self {
+        Self {
+            columns: vec!["result".to_string()],
+            rows: vec![vec![text.into()]],
+            truncated: false,
+            blobs: HashMap::new(),
+            kinds: Vec::new(),
+        }
+    }
+
+    /// Match the columns by name against `properties` to learn their kinds
+    pub fn set_kinds(&mut self, properties: &[ColumnInfo]) {
+        self.kinds = self
+            .columns
+            .iter()
+            .map(|name| {
+                properties
+                    .iter()
+                    .find(|c| &c.name == name)
+                    .map_or(ColumnKind::Text, |c| ColumnKind::from_data_type(&c.data_type))
+            })
+            .collect();
+    }
+
+    pub fn kind(&self, col: usize) -> ColumnKind {
+        self.kinds.get(col).copied().unwrap_or_default()
+    }
+
+    /// Add the rows of `more`, read after these, taking on its `truncated`
+    pub fn append(&mut self, more: Records) {
+        let offset = self.rows.len();
+        self.blobs.extend(more.blobs.into_iter().map(|((r, c), b)| ((r + offset, c), b)));
+        self.rows.extend(more.rows);
+        self.truncated = more.truncated;
+    }
+}
+
+/// How a column's values are displayed, derived from its declared type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnKind {
+    #[default]
+    Text,
+    Number,
+    Boolean,
+    Date,
+    Timestamp,
+}
+
+impl ColumnKind {
+    /// Kind of a type as the backends spell it in `ColumnInfo::data_type`,
+    /// e.g. `bigint unsigned`, `numeric(10,2)`, `timestamp with time zone`
+    pub fn from_data_type(data_type: &str) -> Self {
+        let ty = data_type.trim().to_lowercase();
+        // MySQL's BOOLEAN is an alias of TINYINT(1)
+        if ty == "tinyint(1)" {
+            return ColumnKind::Boolean;
+        }
+        let base = ty.split(['(', ' ']).next().unwrap_or_default();
+        match base {
+            "bool" | "boolean" | "bit" => ColumnKind::Boolean,
+            "date" => ColumnKind::Date,
+            "datetime" | "datetime2" | "smalldatetime" | "datetimeoffset" => ColumnKind::Timestamp,
+            b if b.starts_with("timestamp") => ColumnKind::Timestamp,
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "int2" | "int4" | "int8"
+            | "hugeint" | "utinyint" | "usmallint" | "uinteger" | "ubigint" | "uhugeint" | "serial"
+            | "smallserial" | "bigserial" | "decimal" | "numeric" | "number" | "real" | "float" | "float4"
+            | "float8" | "double" | "money" | "smallmoney" => ColumnKind::Number,
+            _ => ColumnKind::Text,
+        }
+    }
+
+    /// Numbers line up on the right
+    pub fn right_aligned(self) -> bool {
+        self == ColumnKind::Number
+    }
+
+    /// `value` as the grid shows it: `true`/`false` for booleans and ISO 8601
+    /// (`2024-01-31`, `2024-01-31T12:00:00+09:00`) for dates and timestamps.
+    /// Values that don't look as expected are returned unchanged.
+    pub fn format(self, value: &str) -> std::borrow::Cow<'_, str> {
+        use std::borrow::Cow;
+        // Starts with `YYYY-MM-DD`
+        let is_date = |v: &str| {
+            v.len() >= 10
+                && v.bytes().take(10).enumerate().all(|(i, b)| match i {
+                    4 | 7 => b == b'-',
+                    _ => b.is_ascii_digit(),
+                })
+        };
+        match self {
+            ColumnKind::Boolean => match value.to_lowercase().as_str() {
+                "t" | "1" | "true" => Cow::Borrowed("true"),
+                "f" | "0" | "false" => Cow::Borrowed("false"),
+                _ => Cow::Borrowed(value),
+            },
+            // MySQL sends DATE values with a zero time of day
+            ColumnKind::Date if is_date(value) && matches!(&value[10..], " 00:00:00" | "T00:00:00") => {
+                Cow::Borrowed(&value[..10])
+            }
+            ColumnKind::Timestamp if is_date(value) && value[10..].starts_with(' ') => {
+                let mut iso = format!("{}T{}", &value[..10], &value[11..]);
+                // Postgres abbreviates whole-hour offsets: `+09` -> `+09:00`
+                let tail = iso.len().saturating_sub(3);
+                if iso.len() > 19
+                    && iso.is_char_boundary(tail)
+                    && iso[tail..].starts_with(['+', '-'])
+                    && iso[tail + 1..].bytes().all(|b| b.is_ascii_digit())
+                {
+                    iso.push_str(":00");
+                }
+                Cow::Owned(iso)
+            }
+            _ => Cow::Borrowed(value),
+        }
+    }
+}
+
+/// Leading bytes of a binary value, kept for the hex preview
+#[derive(Debug, Clone)]
+pub struct Blob {
+    /// Full length of the value
+    pub len: usize,
+    /// At most `Connection::blob_preview_bytes` bytes
+    pub bytes: Vec<u8>,
+}
+
+impl Blob {
+    pub fn new(bytes: &[u8], conn: &Connection) -> Self {
+        let cap = conn.blob_preview_bytes.unwrap_or(DEFAULT_BLOB_PREVIEW_BYTES);
+        Self {
+            len: bytes.len(),
+            bytes: bytes[..bytes.len().min(cap)].to_vec(),
+        }
+    }
+}
+
+/// Grid text for a binary value
+pub fn blob_summary(len: usize) -> String {
+    format!("<blob {} bytes>", len)
+}
+
+/// Default for `Connection::max_records_bytes`
+pub const DEFAULT_MAX_RECORDS_BYTES: usize = 64 * 1024 * 1024;
+/// Default for `Connection::blob_preview_bytes`
+pub const DEFAULT_BLOB_PREVIEW_BYTES: usize = 4096;
+
+/// Running estimate of the memory held by rows as a backend builds `Records`.
+pub struct RowBudget {
+    remaining: usize,
+    admitted: usize,
+}
+
+impl RowBudget {
+    pub fn for_connection(conn: &Connection) -> Self {
+        Self {
+            remaining: conn.max_records_bytes.unwrap_or(DEFAULT_MAX_RECORDS_BYTES),
+            admitted: 0,
+        }
+    }
+
+    /// Charge a row against the budget. Returns false once the cap would be
+    /// exceeded; the first row is always admitted so something is shown.
+    pub fn admit(&mut self, row: &[String]) -> bool {
+        self.admit_with_blobs(row, &[])
+    }
+
+    /// `admit` for a row whose binary cells also keep preview bytes
+    pub fn admit_with_blobs(&mut self, row: &[String], blobs: &[(usize, Blob)]) -> bool {
+        let size = std::mem::size_of::<Vec<String>>()
+            + row
+                .iter()
+                .map(|v| std::mem::size_of::<String>() + v.capacity())
+                .sum::<usize>()
+            + blobs.iter().map(|(_, b)| b.bytes.capacity()).sum::<usize>();
+        if size > self.remaining && self.admitted > 0 {
+            return false;
+        }
+        self.remaining = self.remaining.saturating_sub(size);
+        self.admitted += 1;
+        true
+    }
+}
+
+/// What is known about the server behind a connection
+#[derive(Debug, Clone)]
+pub struct ServerInfo {
+    /// Product and version, e.g. `PostgreSQL 16.2` or `MariaDB 10.11.6`
+    pub version: String,
+    /// Round trip of `SELECT 1` on an open connection
+    pub latency: Duration,
+}
+
+impl std::fmt::Display for ServerInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let micros = self.latency.as_micros();
+        if micros < 1000 {
+            write!(f, "{}, {} µs", self.version, micros)
+        } else {
+            write!(f, "{}, {} ms", self.version, self.latency.as_millis())
+        }
+    }
+}
+
+/// Number of rows in a table, tagged with how it was obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowCount {
+    /// Result of `SELECT COUNT(*)`
+    Exact(u64),
+    /// Catalog statistic (e.g. `reltuples`, `TABLE_ROWS`), may be stale
+    Estimate(u64),
+}
+
+impl RowCount {
+    pub fn is_estimate(&self) -> bool {
+        matches!(self, RowCount::Estimate(_))
+    }
+}
+
+impl std::fmt::Display for RowCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RowCount::Exact(n) => write!(f, "{}", n),
+            RowCount::Estimate(n) => write!(f, "≈{}", n),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -136,6 +976,20 @@ pub struct ColumnInfo {
     pub nullable: bool,
     pub default: Option<String>,
     pub primary_key: bool,
+    /// Character set and collation of a text column; MySQL only
+    pub charset: Option<String>,
+    pub collation: Option<String>,
+    /// Comment documenting the column; MySQL and PostgreSQL only
+    pub comment: Option<String>,
+}
+
+impl ColumnInfo {
+    /// Whether the column holds character strings (`varchar`, `text`,
+    /// `nchar`, ...), the ones a value search looks in
+    pub fn is_text(&self) -> bool {
+        let ty = self.data_type.to_lowercase();
+        ["char", "text", "string", "clob"].iter().any(|t| ty.contains(t))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -143,4 +997,194 @@ pub struct TableProperties {
     pub columns: Vec<ColumnInfo>,
 }
 
+#[derive(Debug, Clone)]
+pub struct IndexInfo {
+    pub name: String,
+    /// Key columns in index order; expressions are shown as written
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForeignKeyInfo {
+    pub column: String,
+    /// Database of the referenced table when it differs (MySQL), as named in the tree
+    pub referenced_database: Option<String>,
+    /// Schema of the referenced table, for backends whose tree has schemas
+    pub referenced_schema: Option<String>,
+    pub referenced_table: String,
+    pub referenced_column: String,
+    /// Referential actions, e.g. `CASCADE` or `NO ACTION`
+    pub on_delete: String,
+    pub on_update: String,
+}
+
 // end
+
+/// Statements as one script: each ends in exactly one `;`, separated by a blank line
+fn ddl_script(statements: impl IntoIterator<Item = String>) -> String {
+    statements
+        .into_iter()
+        .map(|s| format!("{};", s.trim().trim_end_matches(';').trim_end()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// `LIKE` pattern for values containing `needle`; its wildcards (and `[`,
+/// special on SQL Server) are escaped with `!`, so pair it with `ESCAPE '!'`
+fn contains_pattern(needle: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in needle.chars() {
+        if matches!(c, '!' | '%' | '_' | '[') {
+            pattern.push('!');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Select list counting, for each of the (quoted) `columns`, the rows where it
+/// matches the `contains_pattern` bound as `param`, compared with `like`
+fn match_counts(columns: impl IntoIterator<Item = String>, like: &str, param: &str) -> String {
+    columns
+        .into_iter()
+        .map(|c| format!("COUNT(CASE WHEN {} {} {} ESCAPE '!' THEN 1 END)", c, like, param))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Plan text returned as one value per row (or one per step), split into lines
+fn plan_lines<S: AsRef<str>>(texts: impl IntoIterator<Item = S>) -> Vec<String> {
+    texts
+        .into_iter()
+        .flat_map(|t| t.as_ref().lines().map(str::to_string).collect::<Vec<_>>())
+        .collect()
+}
+
+/// A plan returned as a result set, laid out as an aligned text table
+fn plan_table(columns: &[String], rows: &[Vec<String>]) -> Vec<String> {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let values = rows.iter().filter_map(|r| r.get(i)).map(|v| v.chars().count());
+            values.fold(c.chars().count(), usize::max)
+        })
+        .collect();
+    let line = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(v, &w)| format!("{:<w$}", v, w = w))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+    let rule = widths.iter().map(|&w| "-".repeat(w)).collect::<Vec<_>>().join("-+-");
+    std::iter::once(line(columns)).chain(std::iter::once(rule)).chain(rows.iter().map(|r| line(r))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "duckdb"))]
+    fn backends_left_out_of_the_build_fail_with_their_feature() {
+        let conn: Connection = serde_yaml::from_str("type: duckdb\nname: t\npath: /tmp/t.duckdb").unwrap();
+        let err = DB::fetch_databases(&conn).unwrap_err().to_string();
+        assert_eq!(err, "this build of clazydbm has no duckdb support (rebuild with the `duckdb` cargo feature)");
+        assert!(!DB::is_cli_tool_available_for(&conn));
+    }
+
+    #[test]
+    fn wait_until_ready_retries_until_the_probe_succeeds() {
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        let probe = || {
+            calls += 1;
+            if calls < 3 {
+                anyhow::bail!("connection refused");
+            }
+            Ok(())
+        };
+        let result = DB::wait_until_ready(probe, Duration::from_secs(10), |attempt, err, _| {
+            retries.push((attempt, err.to_string()));
+        });
+        assert!(result.is_ok());
+        assert_eq!(calls, 3);
+        assert_eq!(retries, [(1, "connection refused".to_string()), (2, "connection refused".to_string())]);
+    }
+
+    #[test]
+    fn wait_until_ready_gives_up_at_the_timeout() {
+        let mut calls = 0u32;
+        let mut retries = 0u32;
+        let started = Instant::now();
+        let probe = || {
+            calls += 1;
+            anyhow::bail!("connection refused")
+        };
+        let result = DB::wait_until_ready(probe, Duration::from_millis(300), |_, _, _| retries += 1);
+        let err = result.expect_err("the probe never succeeds");
+        assert!(format!("{:#}", err).contains(&format!("not ready after {} attempts", calls)));
+        assert!(format!("{:#}", err).contains("connection refused"));
+        assert_eq!(retries + 1, calls);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn estimated_row_count_is_marked() {
+        let count = RowCount::Estimate(1200);
+        assert!(count.is_estimate());
+        assert_eq!(count.to_string(), "≈1200");
+    }
+
+    #[test]
+    fn exact_row_count_has_no_marker() {
+        let count = RowCount::Exact(1200);
+        assert!(!count.is_estimate());
+        assert_eq!(count.to_string(), "1200");
+    }
+
+    fn budget(max_records_bytes: usize) -> RowBudget {
+        let conn: Connection = serde_yaml::from_str(&format!(
+            "type: sqlite\nname: t\npath: /tmp/t.db\nmax_records_bytes: {}",
+            max_records_bytes
+        ))
+        .unwrap();
+        RowBudget::for_connection(&conn)
+    }
+
+    /// Bytes `RowBudget` charges for a one-cell row of `len` bytes
+    fn row_size(len: usize) -> usize {
+        std::mem::size_of::<Vec<String>>() + std::mem::size_of::<String>() + len
+    }
+
+    #[test]
+    fn row_budget_admits_rows_until_the_cap() {
+        let mut budget = budget(3 * row_size(100));
+        let row = vec!["x".repeat(100)];
+        assert!((0..3).all(|_| budget.admit(&row)));
+        assert!(!budget.admit(&row));
+    }
+
+    #[test]
+    fn row_budget_always_admits_an_oversized_first_row() {
+        let mut budget = budget(1000);
+        assert!(budget.admit(&["x".repeat(5000)]));
+        assert!(!budget.admit(&["y".to_string()]));
+    }
+
+    #[test]
+    fn row_budget_charges_blob_previews() {
+        let mut budget = budget(row_size(0) + 100);
+        assert!(budget.admit(&[String::new()]));
+        let blob = Blob { len: 10_000, bytes: vec![0; 200] };
+        assert!(!budget.admit_with_blobs(&[String::new()], &[(0, blob)]));
+        // The refused row wasn't charged
+        assert!(budget.admit(&[String::new()]));
+    }
+}
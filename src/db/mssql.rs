@@ -0,0 +1,863 @@
+use anyhow::Result;
+use futures_util::TryStreamExt;
+use tiberius::{AuthMethod, Client, ColumnData, Config, QueryItem, SqlBrowser};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use crate::component::{Child, Database, Schema, Table, TableKind};
+use crate::{connection::Connection, db::DBBehavior};
+use crate::db::{blob_summary, contains_pattern, ddl_script, match_counts, plan_lines, Blob, CliTarget, ServerInfo, ForeignKeyInfo, IndexInfo, RecordQuery, Records, ColumnInfo, RowBudget, RowChunks, RowCount, TableProperties};
+use crate::logger::{debug, warn};
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::Instant;
+
+pub struct MsSql {}
+
+type MsClient = Client<Compat<TcpStream>>;
+
+impl MsSql {
+    /// `host` may name an instance (`server\instance`); its port is then
+    /// looked up through SQL Browser unless `port` is set. A `url` is a JDBC
+    /// string (`sqlserver://host:port;user=...;password=...;database=...`).
+    fn config(conn: &Connection) -> Result<Config> {
+        if let Some(url) = conn.url_for(&["sqlserver", "jdbc:sqlserver"])? {
+            let jdbc = match url.strip_prefix("jdbc:") {
+                Some(_) => url.to_string(),
+                None => format!("jdbc:{}", url),
+            };
+            let mut config = Config::from_jdbc_string(&jdbc)?;
+            if let Some(database) = &conn.database {
+                config.database(database);
+            }
+            config.trust_cert();
+            return Ok(config);
+        }
+        let host = conn
+            .host
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("type mssql needs the host field"))?;
+        if host.starts_with("np:") || host.starts_with(r"\\") {
+            anyhow::bail!("mssql named pipes are not supported; enable TCP/IP on the server and use host/port");
+        }
+        let user = conn
+            .user
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("type mssql needs the user field"))?;
+        let host = host.strip_prefix("tcp:").unwrap_or(host);
+
+        let mut config = Config::new();
+        match host.split_once('\\') {
+            Some((server, instance)) => {
+                config.host(server);
+                config.instance_name(instance);
+            }
+            None => config.host(host),
+        }
+        if let Some(port) = conn.port {
+            config.port(u16::try_from(port)?);
+        }
+        if let Some(database) = &conn.database {
+            config.database(database);
+        }
+        config.authentication(AuthMethod::sql_server(user, conn.password()?.unwrap_or_default()));
+        // Most servers use a self-signed certificate (as sqlcmd -C)
+        config.trust_cert();
+        Ok(config)
+    }
+
+    /// Run one async fetch to completion on a private single-threaded runtime,
+    /// giving up after the connection's query timeout (tiberius has no
+    /// statement timeout of its own; dropping the future closes the connection)
+    fn block_on<T>(conn: &Connection, fut: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        match conn.query_timeout() {
+            Some(timeout) => runtime.block_on(async {
+                tokio::time::timeout(timeout, fut)
+                    .await
+                    .map_err(|_| anyhow::anyhow!("query timed out after {}s", timeout.as_secs()))?
+            }),
+            None => runtime.block_on(fut),
+        }
+    }
+
+    async fn connect(conn: &Connection) -> Result<MsClient> {
+        let config = Self::config(conn)?;
+        // Resolves named instances via SQL Browser, plain TCP otherwise
+        let tcp = TcpStream::connect_named(&config).await?;
+        tcp.set_nodelay(true)?;
+        Ok(Client::connect(config, tcp.compat_write()).await?)
+    }
+
+    /// Schema of `table` in `database`: the one given by the tree, else looked
+    /// up preferring `dbo` when the name is ambiguous
+    async fn table_schema(client: &mut MsClient, database: &str, schema: Option<&str>, table: &str) -> Result<String> {
+        if let Some(schema) = schema {
+            return Ok(schema.to_string());
+        }
+        let q = format!(
+            "SELECT TOP 1 TABLE_SCHEMA FROM {}.INFORMATION_SCHEMA.TABLES WHERE TABLE_NAME = @P1 \
+             ORDER BY CASE WHEN TABLE_SCHEMA = 'dbo' THEN 0 ELSE 1 END",
+            quote(database)
+        );
+        let row = client.query(q, &[&table]).await?.into_row().await?;
+        row.and_then(|r| r.get::<&str, _>(0).map(str::to_string))
+            .ok_or_else(|| anyhow::anyhow!("table {} not found in {}", table, database))
+    }
+}
+
+/// Bracket-quote an identifier
+fn quote(ident: &str) -> String {
+    format!("[{}]", ident.replace(']', "]]"))
+}
+
+/// `a = @PN AND b = @PN+1 ...` over the `key` columns, numbering from `first`
+fn key_condition(key: &[(String, String)], first: usize) -> String {
+    key.iter()
+        .enumerate()
+        .map(|(i, (col, _))| format!("{} = @P{}", quote(col), first + i))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Expression rendering a column as NVARCHAR, so every value reads as text
+fn text_expr(column: &str, data_type: &str) -> String {
+    let col = quote(column);
+    match data_type {
+        "binary" | "varbinary" | "image" | "timestamp" | "rowversion" => {
+            format!("CONVERT(NVARCHAR(MAX), CONVERT(VARBINARY(MAX), {}), 1)", col)
+        }
+        // ISO 8601
+        "date" | "datetime" | "datetime2" | "smalldatetime" | "datetimeoffset" | "time" => {
+            format!("CONVERT(NVARCHAR(MAX), {}, 126)", col)
+        }
+        "geography" | "geometry" | "hierarchyid" => format!("{}.ToString()", col),
+        _ => format!("CAST({} AS NVARCHAR(MAX))", col),
+    }
+}
+
+/// Text of a value from an arbitrary query; binary values become blobs.
+/// Dates and times are ISO 8601, as `text_expr` renders them.
+fn cell_text(data: &ColumnData<'_>, conn: &Connection, blob: &mut Option<Blob>) -> String {
+    fn opt<T: ToString>(v: &Option<T>) -> String {
+        v.as_ref().map(T::to_string).unwrap_or_default()
+    }
+    match data {
+        ColumnData::U8(v) => opt(v),
+        ColumnData::I16(v) => opt(v),
+        ColumnData::I32(v) => opt(v),
+        ColumnData::I64(v) => opt(v),
+        ColumnData::F32(v) => opt(v),
+        ColumnData::F64(v) => opt(v),
+        ColumnData::Bit(v) => opt(v),
+        ColumnData::String(v) => opt(v),
+        ColumnData::Guid(v) => opt(v),
+        ColumnData::Numeric(v) => opt(v),
+        ColumnData::Xml(v) => opt(v),
+        ColumnData::Binary(v) => match v {
+            Some(b) => {
+                *blob = Some(Blob::new(b, conn));
+                blob_summary(b.len())
+            }
+            None => String::new(),
+        },
+        // Days since 1900-01-01 and 1/300 s (minutes for smalldatetime) since midnight
+        ColumnData::DateTime(v) => v
+            .map(|dt| {
+                let nanos = u64::from(dt.seconds_fragments()) * 10_000_000 / 3;
+                format!("{}T{}", civil_date(i64::from(dt.days()) - DAYS_1900), clock(nanos, 3))
+            })
+            .unwrap_or_default(),
+        ColumnData::SmallDateTime(v) => v
+            .map(|dt| {
+                let nanos = u64::from(dt.seconds_fragments()) * 60_000_000_000;
+                format!("{}T{}", civil_date(i64::from(dt.days()) - DAYS_1900), clock(nanos, 0))
+            })
+            .unwrap_or_default(),
+        ColumnData::Date(v) => v.map(|d| civil_date(i64::from(d.days()) - DAYS_0001)).unwrap_or_default(),
+        ColumnData::Time(v) => v.map(|t| clock(time_nanos(t), t.scale())).unwrap_or_default(),
+        ColumnData::DateTime2(v) => v
+            .map(|dt| {
+                let t = dt.time();
+                format!("{}T{}", civil_date(i64::from(dt.date().days()) - DAYS_0001), clock(time_nanos(t), t.scale()))
+            })
+            .unwrap_or_default(),
+        // Stored as UTC; shown in the value's own offset
+        ColumnData::DateTimeOffset(v) => v
+            .map(|dto| {
+                let dt = dto.datetime2();
+                let t = dt.time();
+                let day_nanos = 86_400 * 1_000_000_000i64;
+                let local = (i64::from(dt.date().days()) - DAYS_0001) * day_nanos
+                    + time_nanos(t) as i64
+                    + i64::from(dto.offset()) * 60_000_000_000;
+                let (days, nanos) = (local.div_euclid(day_nanos), local.rem_euclid(day_nanos) as u64);
+                let offset = dto.offset();
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                format!(
+                    "{}T{}{}{:02}:{:02}",
+                    civil_date(days),
+                    clock(nanos, t.scale()),
+                    sign,
+                    offset / 60,
+                    offset % 60
+                )
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Days from 1970-01-01 back to 1900-01-01 and to 0001-01-01
+const DAYS_1900: i64 = 25_567;
+const DAYS_0001: i64 = 719_162;
+
+fn time_nanos(t: tiberius::time::Time) -> u64 {
+    t.increments() * 10u64.pow(9 - u32::from(t.scale()))
+}
+
+/// `YYYY-MM-DD` for a day count relative to 1970-01-01 (proleptic Gregorian)
+fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// `HH:MM:SS` plus `digits` fractional digits, from nanoseconds since midnight
+fn clock(nanos: u64, digits: u8) -> String {
+    let secs = nanos / 1_000_000_000;
+    let base = format!("{:02}:{:02}:{:02}", secs / 3_600, secs / 60 % 60, secs % 60);
+    if digits == 0 {
+        return base;
+    }
+    let frac = format!("{:09}", nanos % 1_000_000_000);
+    format!("{}.{}", base, &frac[..usize::from(digits.min(9))])
+}
+
+impl DBBehavior for MsSql {
+    /// Identifies the connection (logs, client reuse); connecting goes through `config`
+    fn database_url(conn: &Connection) -> Result<String> {
+        let config = Self::config(conn)?;
+        let user = conn.user.as_deref().unwrap_or_default();
+        match conn.database.as_ref() {
+            Some(database) => Ok(format!("mssql://{}@{}/{}", user, config.get_addr(), database)),
+            None => Ok(format!("mssql://{}@{}", user, config.get_addr())),
+        }
+    }
+    fn quote_identifier(ident: &str) -> String {
+        quote(ident)
+    }
+    fn ping(conn: &Connection) -> Result<()> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            client.simple_query("SELECT 1").await?.into_results().await?;
+            Ok(())
+        })
+    }
+    fn server_info(conn: &Connection) -> Result<ServerInfo> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let row = client
+                .simple_query("SELECT CAST(SERVERPROPERTY('ProductVersion') AS nvarchar(128))")
+                .await?
+                .into_row()
+                .await?;
+            let version = row.and_then(|r| r.get::<&str, _>(0).map(str::to_string)).unwrap_or_default();
+            let started = Instant::now();
+            client.simple_query("SELECT 1").await?.into_results().await?;
+            Ok(ServerInfo { version: format!("SQL Server {}", version), latency: started.elapsed() })
+        })
+    }
+    fn fetch_databases(conn: &Connection) -> Result<Vec<Database>> {
+        debug("mssql: connecting");
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            debug("mssql: connected");
+
+            // Determine database list, skipping master/tempdb/model/msdb
+            let dbs: Vec<String> = match conn.database.as_ref() {
+                Some(db) => vec![db.clone()],
+                None => client
+                    .simple_query("SELECT name FROM sys.databases WHERE database_id > 4 AND state = 0 ORDER BY name")
+                    .await?
+                    .into_first_result()
+                    .await?
+                    .iter()
+                    .filter_map(|r| r.get::<&str, _>(0).map(str::to_string))
+                    .collect(),
+            };
+
+            let mut out = Vec::new();
+            for dbname in dbs {
+                let q = format!(
+                    "SELECT TABLE_SCHEMA, TABLE_NAME, TABLE_TYPE FROM {}.INFORMATION_SCHEMA.TABLES \
+                     WHERE TABLE_TYPE IN ('BASE TABLE', 'VIEW') ORDER BY TABLE_SCHEMA, TABLE_NAME",
+                    quote(&dbname)
+                );
+                // A login may see databases it cannot open
+                let rows = match client.simple_query(q).await {
+                    Ok(stream) => stream.into_first_result().await?,
+                    Err(e) => {
+                        warn(&format!("mssql: skipping {}: {}", dbname, e));
+                        continue;
+                    }
+                };
+
+                let mut children: Vec<Child> = Vec::new();
+                for row in rows {
+                    let schema = row.get::<&str, _>(0).unwrap_or_default().to_string();
+                    let table = Table {
+                        name: row.get::<&str, _>(1).unwrap_or_default().to_string(),
+                        engine: None,
+                        schema: Some(schema.clone()),
+                        kind: TableKind::from_table_type(row.get::<&str, _>(2).unwrap_or_default()),
+                    };
+                    match children.last_mut() {
+                        Some(Child::Schema(s)) if s.name == schema => s.tables.push(table),
+                        _ => children.push(Child::Schema(Schema { name: schema, tables: vec![table], is_expanded: false })),
+                    }
+                }
+                out.push(Database::new(dbname, children));
+            }
+
+            Ok(out)
+        })
+    }
+
+    fn fetch_records(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        query: &RecordQuery,
+    ) -> Result<Records> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+
+            // columns
+            let cols_q = format!(
+                "SELECT COLUMN_NAME, DATA_TYPE FROM {}.INFORMATION_SCHEMA.COLUMNS \
+                 WHERE TABLE_SCHEMA = @P1 AND TABLE_NAME = @P2 ORDER BY ORDINAL_POSITION",
+                quote(database)
+            );
+            let cols: Vec<(String, String)> = client
+                .query(cols_q, &[&schema.as_str(), &table])
+                .await?
+                .into_first_result()
+                .await?
+                .iter()
+                .map(|r| {
+                    let name = r.get::<&str, _>(0).unwrap_or_default().to_string();
+                    let data_type = r.get::<&str, _>(1).unwrap_or_default().to_string();
+                    (name, data_type)
+                })
+                .collect();
+            if cols.is_empty() {
+                anyhow::bail!("no columns found for {}.{}.{}", database, schema, table);
+            }
+
+            let select_list = cols
+                .iter()
+                .map(|(name, data_type)| text_expr(name, data_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let source = format!("{}.{}.{}", quote(database), quote(&schema), quote(table));
+            let filter = query.where_clause();
+            // TOP works on every version; OFFSET/FETCH (2012+) only for later pages
+            let q = if query.offset == 0 {
+                format!(
+                    "SELECT TOP ({}) {} FROM {}{}{}",
+                    query.limit, select_list, source, filter, query.order_clause()
+                )
+            } else {
+                // OFFSET needs an ORDER BY
+                let order = match query.order_clause() {
+                    o if o.is_empty() => " ORDER BY (SELECT NULL)".to_string(),
+                    o => o,
+                };
+                format!(
+                    "SELECT {} FROM {}{}{} OFFSET {} ROWS FETCH NEXT {} ROWS ONLY",
+                    select_list, source, filter, order, query.offset, query.limit
+                )
+            };
+
+            let mut stream = client.simple_query(q).await?.into_row_stream();
+            let columns: Vec<String> = cols.into_iter().map(|(name, _)| name).collect();
+            let mut rows_vec = Vec::new();
+            // Binary columns are converted to text by `text_expr`
+            let mut blobs = HashMap::new();
+            let mut budget = RowBudget::for_connection(conn);
+            let mut chunks = RowChunks::new(query);
+            let mut truncated = false;
+            while let Some(row) = stream.try_next().await? {
+                let out: Vec<String> = (0..row.len())
+                    .map(|i| row.get::<&str, _>(i).unwrap_or_default().to_string())
+                    .collect();
+                if !budget.admit(&out) {
+                    truncated = true;
+                    break;
+                }
+                rows_vec.push(out);
+                chunks.flush(&columns, &mut rows_vec, &mut blobs);
+            }
+
+            Ok(Records { columns, rows: rows_vec, truncated, blobs, kinds: Vec::new() })
+        })
+    }
+
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let mut stream = client.simple_query(sql).await?;
+            let mut columns: Option<Vec<String>> = None;
+            let mut rows_vec = Vec::new();
+            let mut blobs = HashMap::new();
+            let mut budget = RowBudget::for_connection(conn);
+            let mut truncated = false;
+            while let Some(item) = stream.try_next().await? {
+                match item {
+                    QueryItem::Metadata(meta) if meta.result_index() == 0 => {
+                        columns = Some(meta.columns().iter().map(|c| c.name().to_string()).collect());
+                    }
+                    // Only the first result set is shown
+                    QueryItem::Metadata(_) => break,
+                    QueryItem::Row(row) => {
+                        let mut out = Vec::with_capacity(row.len());
+                        let mut row_blobs = Vec::new();
+                        for (i, (_, data)) in row.cells().enumerate() {
+                            let mut blob = None;
+                            out.push(cell_text(data, conn, &mut blob));
+                            row_blobs.extend(blob.map(|b| (i, b)));
+                        }
+                        if !budget.admit_with_blobs(&out, &row_blobs) {
+                            truncated = true;
+                            break;
+                        }
+                        let r = rows_vec.len();
+                        blobs.extend(row_blobs.into_iter().map(|(c, b)| ((r, c), b)));
+                        rows_vec.push(out);
+                    }
+                }
+            }
+            // The simple protocol reports no affected-row count
+            Ok(match columns {
+                Some(columns) => Records { columns, rows: rows_vec, truncated, blobs, kinds: Vec::new() },
+                None => Records::message("OK"),
+            })
+        })
+    }
+
+    fn explain(conn: &Connection, sql: &str, analyze: bool) -> Result<Vec<String>> {
+        if analyze {
+            anyhow::bail!("SQL Server only shows the estimated plan here");
+        }
+        Self::block_on(conn, async {
+            // A fresh client, so SHOWPLAN doesn't outlive this call. It must be
+            // alone in its batch; after it, statements are compiled but not run.
+            let mut client = Self::connect(conn).await?;
+            client.simple_query("SET SHOWPLAN_TEXT ON").await?.into_results().await?;
+            let results = client.simple_query(sql).await?.into_results().await?;
+            // Each statement gives its own text, then its plan, in the StmtText column
+            let texts: Vec<String> = results
+                .iter()
+                .flatten()
+                .filter_map(|row| row.get::<&str, _>(0).map(str::to_string))
+                .collect();
+            Ok(plan_lines(texts))
+        })
+    }
+
+    fn update_cell(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            // NVARCHAR parameters, implicitly converted to each column's type
+            let q = format!(
+                "UPDATE {}.{}.{} SET {} = @P1 WHERE {}",
+                quote(database),
+                quote(&schema),
+                quote(table),
+                quote(column),
+                key_condition(key, 2)
+            );
+            let mut params: Vec<&dyn tiberius::ToSql> = vec![&value];
+            params.extend(key.iter().map(|(_, v)| v as &dyn tiberius::ToSql));
+            Ok(client.execute(q, &params).await?.total())
+        })
+    }
+
+    fn delete_row(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            let q = format!(
+                "DELETE FROM {}.{}.{} WHERE {}",
+                quote(database),
+                quote(&schema),
+                quote(table),
+                key_condition(key, 1)
+            );
+            let params: Vec<&dyn tiberius::ToSql> = key.iter().map(|(_, v)| v as &dyn tiberius::ToSql).collect();
+            Ok(client.execute(q, &params).await?.total())
+        })
+    }
+
+    fn insert_row(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            let target = format!("{}.{}.{}", quote(database), quote(&schema), quote(table));
+            let q = if values.is_empty() {
+                format!("INSERT INTO {} DEFAULT VALUES", target)
+            } else {
+                format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    target,
+                    values.iter().map(|(c, _)| quote(c)).collect::<Vec<_>>().join(", "),
+                    (1..=values.len()).map(|i| format!("@P{}", i)).collect::<Vec<_>>().join(", ")
+                )
+            };
+            let params: Vec<&dyn tiberius::ToSql> = values.iter().map(|(_, v)| v as &dyn tiberius::ToSql).collect();
+            Ok(client.execute(q, &params).await?.total())
+        })
+    }
+
+    fn fetch_properties(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<TableProperties> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+
+            let q = format!(
+                "SELECT c.COLUMN_NAME, c.DATA_TYPE, CAST(c.CHARACTER_MAXIMUM_LENGTH AS INT), c.IS_NULLABLE, c.COLUMN_DEFAULT,
+                        CASE WHEN k.COLUMN_NAME IS NULL THEN 0 ELSE 1 END
+                 FROM {db}.INFORMATION_SCHEMA.COLUMNS c
+                 LEFT JOIN (
+                     SELECT kcu.COLUMN_NAME
+                     FROM {db}.INFORMATION_SCHEMA.TABLE_CONSTRAINTS tc
+                     JOIN {db}.INFORMATION_SCHEMA.KEY_COLUMN_USAGE kcu
+                       ON tc.CONSTRAINT_NAME = kcu.CONSTRAINT_NAME
+                      AND tc.TABLE_SCHEMA = kcu.TABLE_SCHEMA
+                     WHERE tc.CONSTRAINT_TYPE = 'PRIMARY KEY'
+                       AND tc.TABLE_SCHEMA = @P1 AND tc.TABLE_NAME = @P2
+                 ) k ON k.COLUMN_NAME = c.COLUMN_NAME
+                 WHERE c.TABLE_SCHEMA = @P1 AND c.TABLE_NAME = @P2
+                 ORDER BY c.ORDINAL_POSITION",
+                db = quote(database)
+            );
+            let rows = client
+                .query(q, &[&schema.as_str(), &table])
+                .await?
+                .into_first_result()
+                .await?;
+            let columns = rows
+                .iter()
+                .map(|r| {
+                    let data_type = r.get::<&str, _>(1).unwrap_or_default();
+                    let data_type = match r.get::<i32, _>(2) {
+                        Some(-1) => format!("{}(max)", data_type),
+                        Some(len) => format!("{}({})", data_type, len),
+                        None => data_type.to_string(),
+                    };
+                    ColumnInfo {
+                        name: r.get::<&str, _>(0).unwrap_or_default().to_string(),
+                        data_type,
+                        nullable: r.get::<&str, _>(3).is_some_and(|s| s.eq_ignore_ascii_case("YES")),
+                        default: r.get::<&str, _>(4).map(str::to_string),
+                        primary_key: r.get::<i32, _>(5) == Some(1),
+                        charset: None,
+                        collation: None,
+                        comment: None,
+                    }
+                })
+                .collect();
+            Ok(TableProperties { columns })
+        })
+    }
+
+    fn fetch_indexes(conn: &Connection, database: &str, schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            let q = format!(
+                "SELECT i.name, CAST(i.is_unique AS INT), c.name
+                 FROM {db}.sys.indexes i
+                 JOIN {db}.sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id
+                 JOIN {db}.sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id
+                 WHERE i.object_id = OBJECT_ID(@P1) AND ic.is_included_column = 0
+                 ORDER BY i.name, ic.key_ordinal",
+                db = quote(database)
+            );
+            let object = format!("{}.{}.{}", quote(database), quote(&schema), quote(table));
+            let rows = client.query(q, &[&object.as_str()]).await?.into_first_result().await?;
+            let mut indexes: Vec<IndexInfo> = Vec::new();
+            for r in &rows {
+                let name = r.get::<&str, _>(0).unwrap_or_default();
+                let column = r.get::<&str, _>(2).unwrap_or_default().to_string();
+                match indexes.last_mut() {
+                    Some(last) if last.name == name => last.columns.push(column),
+                    _ => indexes.push(IndexInfo {
+                        name: name.to_string(),
+                        columns: vec![column],
+                        unique: r.get::<i32, _>(1) == Some(1),
+                    }),
+                }
+            }
+            Ok(indexes)
+        })
+    }
+
+    fn fetch_foreign_keys(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            let q = format!(
+                "SELECT pc.name, s.name, rt.name, rc.name,
+                        fk.delete_referential_action_desc, fk.update_referential_action_desc
+                 FROM {db}.sys.foreign_keys fk
+                 JOIN {db}.sys.foreign_key_columns fkc ON fkc.constraint_object_id = fk.object_id
+                 JOIN {db}.sys.columns pc ON pc.object_id = fkc.parent_object_id AND pc.column_id = fkc.parent_column_id
+                 JOIN {db}.sys.tables rt ON rt.object_id = fkc.referenced_object_id
+                 JOIN {db}.sys.schemas s ON s.schema_id = rt.schema_id
+                 JOIN {db}.sys.columns rc ON rc.object_id = fkc.referenced_object_id AND rc.column_id = fkc.referenced_column_id
+                 WHERE fk.parent_object_id = OBJECT_ID(@P1)
+                 ORDER BY fk.name, fkc.constraint_column_id",
+                db = quote(database)
+            );
+            let object = format!("{}.{}.{}", quote(database), quote(&schema), quote(table));
+            let rows = client.query(q, &[&object.as_str()]).await?.into_first_result().await?;
+            let text = |r: &tiberius::Row, i: usize| r.get::<&str, _>(i).unwrap_or_default().to_string();
+            Ok(rows
+                .iter()
+                .map(|r| ForeignKeyInfo {
+                    column: text(r, 0),
+                    referenced_database: None,
+                    referenced_schema: Some(text(r, 1)),
+                    referenced_table: text(r, 2),
+                    referenced_column: text(r, 3),
+                    // e.g. NO_ACTION, SET_NULL
+                    on_delete: text(r, 4).replace('_', " "),
+                    on_update: text(r, 5).replace('_', " "),
+                })
+                .collect())
+        })
+    }
+
+    fn fetch_ddl(conn: &Connection, database: &str, schema: Option<&str>, table: &str) -> Result<String> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            let object = format!("{}.{}.{}", quote(database), quote(&schema), quote(table));
+            let db = quote(database);
+            // Views keep their source text
+            let q = format!("SELECT definition FROM {}.sys.sql_modules WHERE object_id = OBJECT_ID(@P1)", db);
+            let row = client.query(q, &[&object.as_str()]).await?.into_row().await?;
+            if let Some(definition) = row.as_ref().and_then(|r| r.get::<&str, _>(0)) {
+                return Ok(ddl_script([definition.to_string()]));
+            }
+            // Tables don't; rebuild the statement from the catalog
+            let q = format!(
+                "SELECT c.name, t.name, CAST(c.max_length AS INT), CAST(c.precision AS INT), CAST(c.scale AS INT),
+                        CAST(c.is_nullable AS INT), CAST(c.is_identity AS INT), dc.definition
+                 FROM {db}.sys.columns c
+                 JOIN {db}.sys.types t ON t.user_type_id = c.user_type_id
+                 LEFT JOIN {db}.sys.default_constraints dc ON dc.object_id = c.default_object_id
+                 WHERE c.object_id = OBJECT_ID(@P1)
+                 ORDER BY c.column_id",
+                db = db
+            );
+            let columns = client.query(q, &[&object.as_str()]).await?.into_first_result().await?;
+            if columns.is_empty() {
+                anyhow::bail!("table {} not found in {}", table, database);
+            }
+            let mut lines = Vec::new();
+            for r in &columns {
+                let type_name = r.get::<&str, _>(1).unwrap_or_default();
+                let (length, precision, scale) =
+                    (r.get::<i32, _>(2).unwrap_or(0), r.get::<i32, _>(3).unwrap_or(0), r.get::<i32, _>(4).unwrap_or(0));
+                let size = |n: i32| if n < 0 { "max".to_string() } else { n.to_string() };
+                let data_type = match type_name {
+                    "varchar" | "char" | "varbinary" | "binary" => format!("{}({})", type_name, size(length)),
+                    // max_length counts bytes, two per character
+                    "nvarchar" | "nchar" => {
+                        format!("{}({})", type_name, size(if length < 0 { length } else { length / 2 }))
+                    }
+                    "decimal" | "numeric" => format!("{}({}, {})", type_name, precision, scale),
+                    "datetime2" | "datetimeoffset" | "time" => format!("{}({})", type_name, scale),
+                    _ => type_name.to_string(),
+                };
+                let mut line = format!("    {} {}", quote(r.get::<&str, _>(0).unwrap_or_default()), data_type);
+                if r.get::<i32, _>(6) == Some(1) {
+                    line.push_str(" IDENTITY");
+                }
+                if let Some(default) = r.get::<&str, _>(7) {
+                    line.push_str(&format!(" DEFAULT {}", default));
+                }
+                line.push_str(if r.get::<i32, _>(5) == Some(1) { " NULL" } else { " NOT NULL" });
+                lines.push(line);
+            }
+            let q = format!(
+                "SELECT k.name, c.name
+                 FROM {db}.sys.key_constraints k
+                 JOIN {db}.sys.index_columns ic ON ic.object_id = k.parent_object_id AND ic.index_id = k.unique_index_id
+                 JOIN {db}.sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id
+                 WHERE k.parent_object_id = OBJECT_ID(@P1) AND k.type = 'PK'
+                 ORDER BY ic.key_ordinal",
+                db = db
+            );
+            let key = client.query(q, &[&object.as_str()]).await?.into_first_result().await?;
+            if let Some(first) = key.first() {
+                let columns: Vec<String> =
+                    key.iter().map(|r| quote(r.get::<&str, _>(1).unwrap_or_default())).collect();
+                lines.push(format!(
+                    "    CONSTRAINT {} PRIMARY KEY ({})",
+                    quote(first.get::<&str, _>(0).unwrap_or_default()),
+                    columns.join(", ")
+                ));
+            }
+            let name = format!("{}.{}", quote(&schema), quote(table));
+            Ok(ddl_script([format!("CREATE TABLE {} (\n{}\n)", name, lines.join(",\n"))]))
+        })
+    }
+
+    fn fetch_row_count(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        exact: bool,
+    ) -> Result<RowCount> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            let source = format!("{}.{}.{}", quote(database), quote(&schema), quote(table));
+
+            if !exact {
+                // Row counts kept per heap/clustered-index partition
+                let q = format!(
+                    "SELECT CAST(SUM(p.rows) AS BIGINT) FROM {}.sys.partitions p \
+                     WHERE p.object_id = OBJECT_ID(@P1) AND p.index_id IN (0, 1)",
+                    quote(database)
+                );
+                let row = client.query(q, &[&source.as_str()]).await?.into_row().await?;
+                if let Some(n) = row.and_then(|r| r.get::<i64, _>(0)) {
+                    return Ok(RowCount::Estimate(n as u64));
+                }
+            }
+
+            let q = format!("SELECT COUNT_BIG(*) FROM {}", source);
+            let row = client.simple_query(q).await?.into_row().await?;
+            let n = row.and_then(|r| r.get::<i64, _>(0)).unwrap_or(0);
+            Ok(RowCount::Exact(n as u64))
+        })
+    }
+
+    fn count_matches(
+        conn: &Connection,
+        database: &str,
+        schema: Option<&str>,
+        table: &str,
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>> {
+        Self::block_on(conn, async {
+            let mut client = Self::connect(conn).await?;
+            let schema = Self::table_schema(&mut client, database, schema, table).await?;
+            // COUNT is an int here; case follows the column's collation
+            let q = format!(
+                "SELECT {} FROM {}.{}.{}",
+                match_counts(columns.iter().map(|c| quote(c)), "LIKE", "@P1"),
+                quote(database),
+                quote(&schema),
+                quote(table)
+            );
+            let pattern = contains_pattern(needle);
+            let row = client.query(q, &[&pattern.as_str()]).await?.into_row().await?;
+            Ok((0..columns.len())
+                .map(|i| row.as_ref().and_then(|r| r.get::<i32, _>(i)).unwrap_or(0) as u64)
+                .collect())
+        })
+    }
+
+    fn cli_tool_name() -> &'static str {
+        "sqlcmd"
+    }
+
+    fn is_cli_tool_available() -> bool {
+        Command::new("which")
+            .arg("sqlcmd")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    fn cli_command(conn: &Connection, target: Option<&CliTarget>) -> Result<Command> {
+        if conn.url.is_some() {
+            anyhow::bail!("sqlcmd can't be launched for a url connection; use host/user/password instead");
+        }
+        // sqlcmd addresses instances as host\instance and ports as host,port
+        let host = conn.host.as_deref().unwrap_or_default();
+        let host = host.strip_prefix("tcp:").unwrap_or(host);
+        let server = match conn.port {
+            Some(port) => format!("{},{}", host.split('\\').next().unwrap_or(host), port),
+            None => host.to_string(),
+        };
+        let mut cmd = Command::new("sqlcmd");
+        cmd.arg("-S").arg(server).arg("-C");
+        cmd.arg("-U").arg(conn.user.as_deref().unwrap_or_default());
+        if let Some(password) = conn.password()? {
+            // Kept off the command line
+            cmd.env("SQLCMDPASSWORD", password);
+        }
+        let database = target.map(|t| t.database.as_str()).or(conn.database.as_deref());
+        if let Some(database) = database {
+            cmd.arg("-d").arg(database);
+        }
+        Ok(cmd)
+    }
+
+    fn launch_cli_tool(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::ExitStatus> {
+        let mut cmd = Self::cli_command(conn, target)?;
+        debug("Launching sqlcmd");
+
+        cmd.status()
+            .map_err(|e| anyhow::anyhow!("Failed to launch sqlcmd: {}", e))
+    }
+}
@@ -1,19 +1,148 @@
 use anyhow::Result;
 
-use crate::component::{Child, Database, Table};
+use crate::component::{Child, Database, Table, TableKind};
 use crate::{connection::Connection, db::DBBehavior};
-use crate::db::{Records, ColumnInfo, TableProperties};
+use crate::db::{blob_summary, contains_pattern, ddl_script, match_counts, plan_lines, plan_table, pool::ClientPool, Blob, CliTarget, ServerInfo, ForeignKeyInfo, IndexInfo, RecordQuery, Records, ColumnInfo, RowBudget, RowChunks, RowCount, TableProperties};
 use crate::logger::debug;
+use std::collections::HashMap;
 use std::process::Command;
+use std::time::Instant;
 
 pub struct Mysql {}
 
+/// Backtick-quote an identifier
+fn quote(ident: &str) -> String {
+    format!("`{}`", ident.replace('`', "``"))
+}
+
+/// `a = ? AND b = ? ...` over the `key` columns
+fn key_condition(key: &[(String, String)]) -> String {
+    key.iter()
+        .map(|(col, _)| format!("{} = ?", quote(col)))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// Whether `e` means the client's connection is gone: dropped at the socket
+/// ("server has gone away"), disconnected for inactivity (4031) or by a
+/// shutdown (1053). Read timeouts are the query timeout, not a lost connection.
+fn connection_lost(e: &anyhow::Error) -> bool {
+    use std::io::ErrorKind;
+    match e.downcast_ref::<mysql::Error>() {
+        Some(mysql::Error::IoError(e)) => !matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock),
+        Some(mysql::Error::CodecError(_)) => true,
+        Some(mysql::Error::MySqlError(e)) => matches!(e.code, 1053 | 4031),
+        _ => false,
+    }
+}
+
+static POOL: ClientPool<mysql::Conn> = ClientPool::new(connection_lost);
+
+/// Server-internal schemas left out when listing every database
+const SYSTEM_SCHEMAS: &[&str] = &["information_schema", "mysql", "performance_schema", "sys"];
+
+impl Mysql {
+    /// Run `f` on a pooled client for `conn`, connecting if none is idle
+    fn with_conn<T>(conn: &Connection, f: impl FnMut(&mut mysql::Conn) -> Result<T>) -> Result<T> {
+        let url = Mysql::database_url(conn)?;
+        POOL.with_client(&url, || Self::connect(&url, conn), f)
+    }
+
+    /// Connect, bounding statements by the connection's query timeout: the
+    /// server aborts SELECTs past MAX_EXECUTION_TIME, and the socket timeouts
+    /// catch everything else (and servers without it, such as MariaDB)
+    fn connect(url: &str, conn: &Connection) -> Result<mysql::Conn> {
+        use mysql::prelude::*;
+        let timeout = conn.query_timeout();
+        let mut opts = mysql::OptsBuilder::from_opts(mysql::Opts::from_url(url)?)
+            .read_timeout(timeout)
+            .write_timeout(timeout);
+        // Set directly: the path is in the URL only as the pool key, unescaped
+        if let Some(socket) = &conn.socket {
+            opts = opts.socket(Some(socket.to_string_lossy()));
+        }
+        let mut c = mysql::Conn::new(opts)?;
+        if let Some(timeout) = timeout {
+            let _ = c.query_drop(format!("SET SESSION MAX_EXECUTION_TIME = {}", timeout.as_millis()));
+        }
+        Ok(c)
+    }
+}
+
+/// Stringify `rows` until the memory cap, passing them on through `chunks`
+/// as they're read; values that are not UTF-8 are kept as blobs (the text
+/// protocol sends every string as bytes)
+fn collect_records(
+    conn: &Connection,
+    columns: Vec<String>,
+    rows: impl Iterator<Item = mysql::Result<mysql::Row>>,
+    mut chunks: RowChunks,
+) -> Result<Records> {
+    use mysql::Value;
+    let mut rows_vec = Vec::new();
+    let mut budget = RowBudget::for_connection(conn);
+    let mut truncated = false;
+    let mut blobs = HashMap::new();
+    for row in rows {
+        let row: mysql::Row = row?;
+        let mut out = Vec::new();
+        let mut row_blobs = Vec::new();
+        for (i, v) in row.unwrap().into_iter().enumerate() {
+            let s = match v {
+                Value::NULL => String::new(),
+                Value::Bytes(b) => match String::from_utf8(b) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        let b = e.into_bytes();
+                        row_blobs.push((i, Blob::new(&b, conn)));
+                        blob_summary(b.len())
+                    }
+                },
+                Value::Int(i) => i.to_string(),
+                Value::UInt(u) => u.to_string(),
+                Value::Float(f) => f.to_string(),
+                Value::Double(d) => d.to_string(),
+                Value::Date(y,m,d,h,mi,s, _us) => format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y,m,d,h,mi,s),
+                Value::Time(neg, d, h, mi, s, _us) => {
+                    let hours = d * 24 + u32::from(h);
+                    format!("{}{:02}:{:02}:{:02}", if neg {"-"} else {""}, hours, mi, s)
+                }
+            };
+            out.push(s);
+        }
+        if !budget.admit_with_blobs(&out, &row_blobs) {
+            truncated = true;
+            break;
+        }
+        let r = rows_vec.len();
+        blobs.extend(row_blobs.into_iter().map(|(c, b)| ((r, c), b)));
+        rows_vec.push(out);
+        chunks.flush(&columns, &mut rows_vec, &mut blobs);
+    }
+
+    Ok(Records { columns, rows: rows_vec, truncated, blobs, kinds: Vec::new() })
+}
+
 impl DBBehavior for Mysql {
     fn database_url(conn: &Connection) -> Result<String> {
+        if let Some(url) = conn.url_for(&["mysql"])? {
+            if conn.socket.is_some() {
+                anyhow::bail!("socket can't be combined with url; add ?socket=<path> to the url instead");
+            }
+            return Ok(url.to_string());
+        }
         let user = conn
             .user
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("type mysql needs the user field"))?;
+        if let Some(socket) = &conn.socket {
+            if conn.host.is_some() || conn.port.is_some() {
+                anyhow::bail!("give either socket or host and port, not both");
+            }
+            let password = conn.password()?.unwrap_or_default();
+            let database = conn.database.as_deref().unwrap_or_default();
+            return Ok(format!("mysql://{}:{}@localhost/{}?socket={}", user, password, database, socket.display()));
+        }
         let host = conn
             .host
             .as_ref()
@@ -22,10 +151,7 @@ impl DBBehavior for Mysql {
             .port
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("type mysql needs the port field"))?;
-        let password = conn
-            .password
-            .as_ref()
-            .map_or(String::new(), |p| p.to_string());
+        let password = conn.password()?.unwrap_or_default();
 
         match conn.database.as_ref() {
             Some(database) => Ok(format!(
@@ -45,128 +171,388 @@ impl DBBehavior for Mysql {
             )),
         }
     }
+    fn quote_identifier(ident: &str) -> String {
+        quote(ident)
+    }
+    fn ping(conn: &Connection) -> Result<()> {
+        use mysql::prelude::*;
+        let mut c = Self::connect(&Self::database_url(conn)?, conn)?;
+        c.query_drop("SELECT 1")?;
+        Ok(())
+    }
+    fn server_info(conn: &Connection) -> Result<ServerInfo> {
+        use mysql::prelude::*;
+        Self::with_conn(conn, |c| {
+            let version: String = c.query_first("SELECT VERSION()")?.unwrap_or_default();
+            let started = Instant::now();
+            c.query_drop("SELECT 1")?;
+            let latency = started.elapsed();
+            // MariaDB tags its version, e.g. `10.11.6-MariaDB-log`
+            let version = match version.split_once("-MariaDB") {
+                Some((number, _)) => format!("MariaDB {}", number),
+                None => format!("MySQL {}", version),
+            };
+            Ok(ServerInfo { version, latency })
+        })
+    }
     fn fetch_databases(conn: &Connection) -> Result<Vec<Database>> {
         debug("mysql: connecting");
         use mysql::prelude::*;
         use mysql::params;
 
+        Self::with_conn(conn, |c| {
+            debug("mysql: connected");
+
+            // Determine database list; a configured one is shown even if it's a system schema
+            let dbs: Vec<String> = match conn.database.as_ref() {
+                Some(db) => vec![db.clone()],
+                // A database named in `url` is the session default
+                None => match c.query_first::<Option<String>, _>("SELECT DATABASE()")?.flatten() {
+                    Some(db) => vec![db],
+                    None => c
+                        .query::<String, _>("SHOW DATABASES")?
+                        .into_iter()
+                        .filter(|db| !SYSTEM_SCHEMAS.contains(&db.to_lowercase().as_str()))
+                        .collect(),
+                },
+            };
+
+            // One node per database (MySQL's schemas), its tables as children;
+            // the node's name is what fetch_records and friends get as `database`
+            let mut out = Vec::new();
+            for dbname in dbs {
+                let q = r#"
+                    SELECT TABLE_NAME, ENGINE, TABLE_TYPE
+                    FROM information_schema.TABLES
+                    WHERE TABLE_SCHEMA = :schema
+                    ORDER BY TABLE_NAME
+                "#;
+                let rows: Vec<(String, Option<String>, String)> = c.exec(q, params! { "schema" => &dbname })?;
+                
+                let children = rows
+                    .into_iter()
+                    .map(|(name, engine, table_type)| {
+                        let kind = TableKind::from_table_type(&table_type);
+                        let t = Table { name, engine, schema: None, kind };
+                        Child::Table(t)
+                    })
+                    .collect();
+
+                out.push(Database::new(dbname, children));
+            }
+
+            Ok(out)
+        })
+    }
+
+    fn fetch_records(
+        conn: &Connection,
+        database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        query: &RecordQuery,
+    ) -> Result<Records> {
+        use mysql::prelude::*;
+        Self::with_conn(conn, |c| {
+            let q = format!(
+                "SELECT * FROM {}.{}{}{} LIMIT {} OFFSET {}",
+                quote(database), quote(table), query.where_clause(), query.order_clause(), query.limit, query.offset
+            );
+            let mut result = c.query_iter(q)?;
+            let Some(set) = result.iter() else {
+                anyhow::bail!("no result set for {}.{}", database, table);
+            };
+            // Names come from the result set itself: information_schema can
+            // hide columns the user lacks privileges on, or lag behind a DDL
+            let columns: Vec<String> = set
+                .columns()
+                .as_ref()
+                .iter()
+                .map(|col| col.name_str().into_owned())
+                .collect();
+            let columns = if columns.is_empty() { vec!["(no columns)".to_string()] } else { columns };
+            collect_records(conn, columns, set, RowChunks::new(query))
+        })
+    }
+
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records> {
+        use mysql::prelude::*;
+        // A fresh client: unlike pooled fetches, a failed statement is never retried
         let url = Mysql::database_url(conn)?;
-        let opts = mysql::Opts::from_url(&url)?;
-        let mut c = mysql::Conn::new(opts)?;
-        debug("mysql: connected");
+        let mut c = Self::connect(&url, conn)?;
+        let mut result = c.query_iter(sql)?;
+        let Some(set) = result.iter() else {
+            return Ok(Records::message("OK"));
+        };
+        let columns: Vec<String> = set
+            .columns()
+            .as_ref()
+            .iter()
+            .map(|col| col.name_str().into_owned())
+            .collect();
+        if columns.is_empty() {
+            return Ok(Records::message(format!("{} rows affected", set.affected_rows())));
+        }
+        collect_records(conn, columns, set, RowChunks::default())
+    }
 
-        // Determine database list
-        let dbs: Vec<String> = match conn.database.as_ref() {
-            Some(db) => vec![db.clone()],
-            None => c.query::<String, _>("SHOW DATABASES")?,
+    fn explain(conn: &Connection, sql: &str, analyze: bool) -> Result<Vec<String>> {
+        use mysql::prelude::*;
+        let url = Mysql::database_url(conn)?;
+        let mut c = Self::connect(&url, conn)?;
+        let sql = sql.trim().trim_end_matches(';');
+        if analyze {
+            // EXPLAIN ANALYZE (MySQL 8.0.18+) runs the statement; roll back whatever it changed
+            let mut tx = c.start_transaction(mysql::TxOpts::default())?;
+            let plans: Vec<String> = tx.query(format!("EXPLAIN ANALYZE {}", sql))?;
+            tx.rollback()?;
+            return Ok(plan_lines(plans));
+        }
+        // The tree (MySQL 8.0.16+) reads best; older servers and MariaDB only have the table
+        if let Ok(plans) = c.query::<String, _>(format!("EXPLAIN FORMAT=TREE {}", sql)) {
+            return Ok(plan_lines(plans));
+        }
+        let mut result = c.query_iter(format!("EXPLAIN {}", sql))?;
+        let Some(set) = result.iter() else {
+            return Ok(Vec::new());
         };
+        let columns: Vec<String> = set.columns().as_ref().iter().map(|col| col.name_str().into_owned()).collect();
+        let recs = collect_records(conn, columns, set, RowChunks::default())?;
+        Ok(plan_table(&recs.columns, &recs.rows))
+    }
 
-        // For each database, list tables via information_schema
-        let mut out = Vec::new();
-        for dbname in dbs {
-            // Skip internal schemas
-            if dbname == "information_schema" || dbname == "mysql" || dbname == "performance_schema" || dbname == "sys" {
-                continue;
-            }
+    fn update_cell(
+        conn: &Connection,
+        database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        use mysql::prelude::*;
+        // A fresh client, as for run_query: a write is never retried
+        let url = Mysql::database_url(conn)?;
+        let mut c = Self::connect(&url, conn)?;
+        let q = format!(
+            "UPDATE {}.{} SET {} = ? WHERE {}",
+            quote(database),
+            quote(table),
+            quote(column),
+            key_condition(key)
+        );
+        let params: Vec<mysql::Value> = std::iter::once(value.map_or(mysql::Value::NULL, mysql::Value::from))
+            .chain(key.iter().map(|(_, v)| mysql::Value::from(v.as_str())))
+            .collect();
+        c.exec_drop(q, params)?;
+        // Rows actually changed: writing a cell's current value counts zero
+        Ok(c.affected_rows())
+    }
+
+    fn delete_row(
+        conn: &Connection,
+        database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        use mysql::prelude::*;
+        let url = Mysql::database_url(conn)?;
+        let mut c = Self::connect(&url, conn)?;
+        let q = format!("DELETE FROM {}.{} WHERE {}", quote(database), quote(table), key_condition(key));
+        let params: Vec<mysql::Value> = key.iter().map(|(_, v)| mysql::Value::from(v.as_str())).collect();
+        c.exec_drop(q, params)?;
+        Ok(c.affected_rows())
+    }
+
+    fn insert_row(
+        conn: &Connection,
+        database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64> {
+        use mysql::prelude::*;
+        let url = Mysql::database_url(conn)?;
+        let mut c = Self::connect(&url, conn)?;
+        // `() VALUES ()` inserts a row of defaults
+        let q = format!(
+            "INSERT INTO {}.{} ({}) VALUES ({})",
+            quote(database),
+            quote(table),
+            values.iter().map(|(c, _)| quote(c)).collect::<Vec<_>>().join(", "),
+            vec!["?"; values.len()].join(", ")
+        );
+        let params: Vec<mysql::Value> = values.iter().map(|(_, v)| mysql::Value::from(v.as_str())).collect();
+        c.exec_drop(q, params)?;
+        Ok(c.affected_rows())
+    }
+
+    fn fetch_properties(
+        conn: &Connection,
+        database: &str,
+        _schema: Option<&str>,
+        table: &str,
+    ) -> Result<TableProperties> {
+        use mysql::prelude::*;
+        use mysql::params;
+        Self::with_conn(conn, |c| {
 
             let q = r#"
-                SELECT TABLE_NAME, ENGINE
-                FROM information_schema.TABLES
-                WHERE TABLE_SCHEMA = :schema
-                ORDER BY TABLE_NAME
+                SELECT COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_KEY,
+                       CHARACTER_SET_NAME, COLLATION_NAME, COLUMN_COMMENT
+                FROM information_schema.COLUMNS
+                WHERE TABLE_SCHEMA = :schema AND TABLE_NAME = :table
+                ORDER BY ORDINAL_POSITION
             "#;
-            let rows: Vec<(String, Option<String>)> = c.exec(q, params! { "schema" => &dbname })?;
-            
-            let children = rows
+            // The character set and collation are NULL for non-text columns;
+            // a column without a comment has an empty one
+            type Row = (String, String, String, Option<String>, Option<String>, Option<String>, Option<String>, String);
+            let rows: Vec<Row> = c.exec(q, params! { "schema" => database, "table" => table })?;
+            let columns = rows
                 .into_iter()
-                .map(|(name, engine)| {
-                    let t = Table { name, engine, schema: None };
-                    Child::Table(t)
+                .map(|(name, coltype, is_nullable, default, colkey, charset, collation, comment)| ColumnInfo {
+                    name,
+                    data_type: coltype,
+                    nullable: is_nullable.eq_ignore_ascii_case("YES"),
+                    default,
+                    primary_key: colkey.as_deref() == Some("PRI"),
+                    charset,
+                    collation,
+                    comment: Some(comment).filter(|c| !c.is_empty()),
                 })
                 .collect();
+            Ok(TableProperties { columns })
+        })
+    }
 
-            out.push(Database::new(dbname, children));
-        }
-
-        Ok(out)
+    fn fetch_indexes(conn: &Connection, database: &str, _schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>> {
+        use mysql::prelude::*;
+        use mysql::params;
+        Self::with_conn(conn, |c| {
+            // COLUMN_NAME is NULL for functional key parts
+            let q = r#"
+                SELECT INDEX_NAME, NON_UNIQUE, COALESCE(COLUMN_NAME, EXPRESSION)
+                FROM information_schema.STATISTICS
+                WHERE TABLE_SCHEMA = :schema AND TABLE_NAME = :table
+                ORDER BY INDEX_NAME, SEQ_IN_INDEX
+            "#;
+            let rows: Vec<(String, i64, Option<String>)> =
+                c.exec(q, params! { "schema" => database, "table" => table })?;
+            let mut indexes: Vec<IndexInfo> = Vec::new();
+            for (name, non_unique, column) in rows {
+                let column = column.unwrap_or_default();
+                match indexes.last_mut() {
+                    Some(last) if last.name == name => last.columns.push(column),
+                    _ => indexes.push(IndexInfo { name, columns: vec![column], unique: non_unique == 0 }),
+                }
+            }
+            Ok(indexes)
+        })
     }
 
-    fn fetch_records(
+    fn fetch_foreign_keys(
         conn: &Connection,
         database: &str,
+        _schema: Option<&str>,
         table: &str,
-        limit: usize,
-        offset: usize,
-    ) -> Result<Records> {
+    ) -> Result<Vec<ForeignKeyInfo>> {
         use mysql::prelude::*;
-        use mysql::{params, Value};
-        let url = Mysql::database_url(conn)?;
-        let opts = mysql::Opts::from_url(&url)?;
-        let mut c = mysql::Conn::new(opts)?;
+        use mysql::params;
+        Self::with_conn(conn, |c| {
+            let q = r#"
+                SELECT kcu.COLUMN_NAME, kcu.REFERENCED_TABLE_SCHEMA, kcu.REFERENCED_TABLE_NAME,
+                       kcu.REFERENCED_COLUMN_NAME, rc.DELETE_RULE, rc.UPDATE_RULE
+                FROM information_schema.KEY_COLUMN_USAGE kcu
+                JOIN information_schema.REFERENTIAL_CONSTRAINTS rc
+                  ON rc.CONSTRAINT_SCHEMA = kcu.CONSTRAINT_SCHEMA
+                 AND rc.CONSTRAINT_NAME = kcu.CONSTRAINT_NAME
+                 AND rc.TABLE_NAME = kcu.TABLE_NAME
+                WHERE kcu.TABLE_SCHEMA = :schema AND kcu.TABLE_NAME = :table
+                  AND kcu.REFERENCED_TABLE_NAME IS NOT NULL
+                ORDER BY kcu.CONSTRAINT_NAME, kcu.ORDINAL_POSITION
+            "#;
+            let rows: Vec<(String, String, String, String, String, String)> =
+                c.exec(q, params! { "schema" => database, "table" => table })?;
+            Ok(rows
+                .into_iter()
+                .map(|(column, ref_db, ref_table, ref_column, on_delete, on_update)| ForeignKeyInfo {
+                    column,
+                    referenced_database: Some(ref_db).filter(|db| db != database),
+                    referenced_schema: None,
+                    referenced_table: ref_table,
+                    referenced_column: ref_column,
+                    on_delete,
+                    on_update,
+                })
+                .collect())
+        })
+    }
 
-        // columns
-        let cols_q = r#"SELECT COLUMN_NAME FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = :schema AND TABLE_NAME = :table ORDER BY ORDINAL_POSITION"#;
-        let columns: Vec<String> = c.exec(cols_q, params! { "schema" => database, "table" => table })?;
+    fn fetch_ddl(conn: &Connection, database: &str, _schema: Option<&str>, table: &str) -> Result<String> {
+        use mysql::prelude::*;
+        Self::with_conn(conn, |c| {
+            // Views answer too, with the CREATE VIEW in the same column
+            let sql = format!("SHOW CREATE TABLE {}.{}", quote(database), quote(table));
+            let row: Option<mysql::Row> = c.query_first(sql)?;
+            let ddl = row
+                .and_then(|mut r| r.take_opt::<String, _>(1))
+                .transpose()?
+                .ok_or_else(|| anyhow::anyhow!("table {} not found in {}", table, database))?;
+            Ok(ddl_script([ddl]))
+        })
+    }
 
-        // rows
-        let q = format!("SELECT * FROM `{}`.`{}` LIMIT {} OFFSET {}", database, table, limit, offset);
-        let result = c.query_iter(q)?;
-        let mut rows_vec = Vec::new();
-        for row in result {
-            let row: mysql::Row = row?;
-            let mut out = Vec::new();
-            for v in row.unwrap() {
-                let s = match v {
-                    Value::NULL => String::new(),
-                    Value::Bytes(b) => String::from_utf8_lossy(&b).into_owned(),
-                    Value::Int(i) => i.to_string(),
-                    Value::UInt(u) => u.to_string(),
-                    Value::Float(f) => f.to_string(),
-                    Value::Double(d) => d.to_string(),
-                    Value::Date(y,m,d,h,mi,s, _us) => format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y,m,d,h,mi,s),
-                    Value::Time(neg, d, h, mi, s, _us) => {
-                        let hours = d * 24 + u32::from(h);
-                        format!("{}{:02}:{:02}:{:02}", if neg {"-"} else {""}, hours, mi, s)
-                    }
-                };
-                out.push(s);
+    fn fetch_row_count(
+        conn: &Connection,
+        database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        exact: bool,
+    ) -> Result<RowCount> {
+        use mysql::prelude::*;
+        use mysql::params;
+        Self::with_conn(conn, |c| {
+
+            if !exact {
+                // TABLE_ROWS is an InnoDB estimate; NULL for views
+                let q = r#"SELECT TABLE_ROWS FROM information_schema.TABLES WHERE TABLE_SCHEMA = :schema AND TABLE_NAME = :table"#;
+                let est: Option<Option<u64>> = c.exec_first(q, params! { "schema" => database, "table" => table })?;
+                if let Some(Some(n)) = est {
+                    return Ok(RowCount::Estimate(n));
+                }
             }
-            rows_vec.push(out);
-        }
 
-        Ok(Records { columns, rows: rows_vec })
+            let q = format!("SELECT COUNT(*) FROM {}.{}", quote(database), quote(table));
+            let n: Option<u64> = c.query_first(q)?;
+            Ok(RowCount::Exact(n.unwrap_or(0)))
+        })
     }
 
-    fn fetch_properties(
+    fn count_matches(
         conn: &Connection,
         database: &str,
+        _schema: Option<&str>,
         table: &str,
-    ) -> Result<TableProperties> {
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>> {
         use mysql::prelude::*;
         use mysql::params;
-        let url = Mysql::database_url(conn)?;
-        let opts = mysql::Opts::from_url(&url)?;
-        let mut c = mysql::Conn::new(opts)?;
-
-        let q = r#"
-            SELECT COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_KEY
-            FROM information_schema.COLUMNS
-            WHERE TABLE_SCHEMA = :schema AND TABLE_NAME = :table
-            ORDER BY ORDINAL_POSITION
-        "#;
-        let rows: Vec<(String, String, String, Option<String>, Option<String>)> =
-            c.exec(q, params! { "schema" => database, "table" => table })?;
-        let columns = rows
-            .into_iter()
-            .map(|(name, coltype, is_nullable, default, colkey)| ColumnInfo {
-                name,
-                data_type: coltype,
-                nullable: is_nullable.eq_ignore_ascii_case("YES"),
-                default,
-                primary_key: colkey.as_deref() == Some("PRI"),
+        Self::with_conn(conn, |c| {
+            // Named, so the one pattern serves every column
+            let q = format!(
+                "SELECT {} FROM {}.{}",
+                match_counts(columns.iter().map(|col| quote(col)), "LIKE", ":needle"),
+                quote(database),
+                quote(table)
+            );
+            let row: Option<mysql::Row> = c.exec_first(q, params! { "needle" => contains_pattern(needle) })?;
+            Ok(match row {
+                Some(row) => (0..columns.len()).map(|i| row.get::<u64, _>(i).unwrap_or(0)).collect(),
+                None => vec![0; columns.len()],
             })
-            .collect();
-        Ok(TableProperties { columns })
+        })
     }
     
     fn cli_tool_name() -> &'static str {
@@ -181,13 +567,30 @@ impl DBBehavior for Mysql {
             .unwrap_or(false)
     }
     
-    fn launch_cli_tool(conn: &Connection) -> Result<std::process::ExitStatus> {
-        let db_url = Self::database_url(conn)?;
-        debug(&format!("Launching mycli with URL: {}", db_url));
+    fn cli_command(conn: &Connection, target: Option<&CliTarget>) -> Result<Command> {
+        // mycli cannot open on a table, but it can start in the table's database
+        let db_url = match target {
+            Some(t) => {
+                let mut conn = conn.clone();
+                conn.database = Some(t.database.clone());
+                Self::database_url(&conn)?
+            }
+            None => Self::database_url(conn)?,
+        };
+        let mut cmd = Command::new("mycli");
+        // mycli takes the socket as an option rather than from the URL
+        match (&conn.socket, db_url.split_once('?')) {
+            (Some(socket), Some((url, _))) => cmd.arg("--socket").arg(socket).arg(url),
+            _ => cmd.arg(db_url),
+        };
+        Ok(cmd)
+    }
+    
+    fn launch_cli_tool(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::ExitStatus> {
+        let mut cmd = Self::cli_command(conn, target)?;
+        debug("Launching mycli");
         
-        Command::new("mycli")
-            .arg(&db_url)
-            .status()
+        cmd.status()
             .map_err(|e| anyhow::anyhow!("Failed to launch mycli: {}", e))
     }
 }
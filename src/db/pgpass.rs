@@ -0,0 +1,59 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::logger::warn;
+
+/// Where libpq looks for passwords: `PGPASSFILE`, else `~/.pgpass`
+fn path() -> Option<PathBuf> {
+    match std::env::var_os("PGPASSFILE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs_next::home_dir().map(|home| home.join(".pgpass")),
+    }
+}
+
+/// The password of the first `.pgpass` line matching the connection, as
+/// libpq finds it. The file is skipped when others can read it.
+pub fn lookup(host: &str, port: u64, database: &str, user: &str) -> Option<String> {
+    let path = path()?;
+    let data = fs::read_to_string(&path).ok()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).ok()?.permissions().mode();
+        if mode & 0o077 != 0 {
+            warn(&format!(
+                "Postgres: ignoring {}: it has group or world access; permissions should be 0600 or less",
+                path.display()
+            ));
+            return None;
+        }
+    }
+    let port = port.to_string();
+    let wanted = [host, port.as_str(), database, user];
+    data.lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let fields = split_fields(line);
+            let [h, p, d, u, password]: [String; 5] = fields.try_into().ok()?;
+            let matches = [h, p, d, u].iter().zip(wanted).all(|(field, value)| field == "*" || field == value);
+            matches.then_some(password)
+        })
+        .next()
+}
+
+/// Fields of a `hostname:port:database:username:password` line; `\` escapes
+/// a `:` or `\` within a field
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => field.extend(chars.next()),
+            ':' => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
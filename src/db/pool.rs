@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::logger::{debug, info};
+
+/// Idle clients kept per connection key
+const MAX_IDLE_PER_KEY: usize = 4;
+/// Wait before reconnecting after a lost connection, doubled after every
+/// reconnect that fails in a row, up to `MAX_RECONNECT_DELAY`
+const RECONNECT_DELAY: Duration = Duration::from_millis(250);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(8);
+
+/// Idle database clients keyed by connection URL, shared by the fetches that
+/// run in `Command::Spawn` tasks. A fetch checks a client out, so concurrent
+/// tasks never share one, and hands it back when it succeeds. A client whose
+/// fetch fails is dropped. When a pooled client failed because its connection
+/// was lost (closed by the server while idle, a server restart), the fetch is
+/// retried once on a fresh connection after a short, growing delay, so a
+/// server that stays down isn't hammered.
+pub struct ClientPool<C> {
+    idle: Mutex<Vec<(String, C)>>,
+    /// Whether an error means the client's connection is gone
+    is_lost: fn(&anyhow::Error) -> bool,
+    /// Reconnects that failed in a row, per key
+    failed_reconnects: Mutex<Vec<(String, u32)>>,
+}
+
+impl<C> ClientPool<C> {
+    pub const fn new(is_lost: fn(&anyhow::Error) -> bool) -> Self {
+        Self {
+            idle: Mutex::new(Vec::new()),
+            is_lost,
+            failed_reconnects: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_client<T>(
+        &self,
+        key: &str,
+        connect: impl FnOnce() -> Result<C>,
+        mut f: impl FnMut(&mut C) -> Result<T>,
+    ) -> Result<T> {
+        let mut reconnecting = false;
+        if let Some(mut client) = self.checkout(key) {
+            match f(&mut client) {
+                Ok(v) => {
+                    self.checkin(key, client);
+                    return Ok(v);
+                }
+                Err(e) if (self.is_lost)(&e) => {
+                    let delay = self.reconnect_delay(key);
+                    info(&format!("pool: connection lost ({}), reconnecting in {:?}", e, delay));
+                    std::thread::sleep(delay);
+                    reconnecting = true;
+                }
+                // The statement failed, not the connection; retrying won't help
+                Err(e) => {
+                    debug(&format!("pool: dropping client after a failed fetch: {}", e));
+                    return Err(e);
+                }
+            }
+        }
+        let mut client = match connect() {
+            Ok(client) => client,
+            Err(e) => {
+                if reconnecting {
+                    self.note_reconnect(key, false);
+                }
+                return Err(e);
+            }
+        };
+        if reconnecting {
+            info("pool: reconnected");
+            self.note_reconnect(key, true);
+        }
+        let v = f(&mut client)?;
+        self.checkin(key, client);
+        Ok(v)
+    }
+
+    fn checkout(&self, key: &str) -> Option<C> {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        let i = idle.iter().position(|(k, _)| k == key)?;
+        Some(idle.swap_remove(i).1)
+    }
+
+    fn checkin(&self, key: &str, client: C) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.iter().filter(|(k, _)| k == key).count() < MAX_IDLE_PER_KEY {
+            idle.push((key.to_string(), client));
+        }
+    }
+
+    fn reconnect_delay(&self, key: &str) -> Duration {
+        let failed = self.failed_reconnects.lock().unwrap_or_else(|e| e.into_inner());
+        let n = failed.iter().find(|(k, _)| k == key).map_or(0, |(_, n)| *n);
+        RECONNECT_DELAY.saturating_mul(1 << n.min(6)).min(MAX_RECONNECT_DELAY)
+    }
+
+    fn note_reconnect(&self, key: &str, ok: bool) {
+        let mut failed = self.failed_reconnects.lock().unwrap_or_else(|e| e.into_inner());
+        if ok {
+            failed.retain(|(k, _)| k != key);
+            return;
+        }
+        match failed.iter_mut().find(|(k, _)| k == key) {
+            Some((_, n)) => *n += 1,
+            None => failed.push((key.to_string(), 1)),
+        }
+    }
+}
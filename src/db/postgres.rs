@@ -1,185 +1,656 @@
 use anyhow::Result;
 
-use crate::component::{Child, Database, Schema, Table};
+use crate::component::{Child, Database, Schema, Table, TableKind};
 use crate::{connection::Connection, db::DBBehavior};
-use crate::db::{Records, ColumnInfo, TableProperties};
+use crate::db::{contains_pattern, ddl_script, match_counts, pgpass, plan_lines, pool::ClientPool, CliTarget, ServerInfo, ForeignKeyInfo, IndexInfo, RecordQuery, Records, ColumnInfo, RowBudget, RowChunks, RowCount, TableProperties};
 use crate::logger::debug;
+use std::collections::HashMap;
 use std::process::Command;
+use std::time::Instant;
 
 pub struct Postgres {}
 
+/// Double-quote an identifier
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// `"schema"."table"`, or the bare quoted table (resolved through
+/// `search_path`) when no schema is known
+fn qualified(schema: Option<&str>, table: &str) -> String {
+    match schema {
+        Some(schema) => format!("{}.{}", quote(schema), quote(table)),
+        None => quote(table),
+    }
+}
+
+/// Condition on information_schema's `table_schema`, bound to a `$n` holding
+/// an optional schema; without one it is the current schema
+fn schema_condition(column: &str, param: usize) -> String {
+    format!("{} = COALESCE(${}::text, current_schema())", column, param)
+}
+
+/// `select` from information_schema's row for each column of one table, in
+/// order; `$1` is the optional schema and `$2` the table
+fn columns_sql(select: &str) -> String {
+    format!(
+        "SELECT {} FROM information_schema.columns WHERE {} AND table_name = $2 ORDER BY ordinal_position",
+        select,
+        schema_condition("table_schema", 1)
+    )
+}
+
+/// Planner estimate of a table's rows, looked up by its `qualified` name in `$1`.
+/// reltuples is -1 (or 0 on old servers) until the table has been analyzed.
+const ESTIMATE_SQL: &str =
+    "SELECT reltuples::bigint FROM pg_class WHERE oid = to_regclass($1) AND relkind IN ('r', 'p')";
+
+/// Whether `e` means the client's connection is gone: closed by the server
+/// (idle timeout, restart) or broken at the socket
+fn connection_lost(e: &anyhow::Error) -> bool {
+    use postgres::error::SqlState;
+    let Some(e) = e.downcast_ref::<postgres::Error>() else {
+        return false;
+    };
+    e.is_closed()
+        || e.code().is_some_and(|code| {
+            [SqlState::ADMIN_SHUTDOWN, SqlState::CRASH_SHUTDOWN, SqlState::IDLE_SESSION_TIMEOUT].contains(code)
+        })
+        || std::error::Error::source(e).is_some_and(|source| source.is::<std::io::Error>())
+}
+
+static POOL: ClientPool<postgres::Client> = ClientPool::new(connection_lost);
+
+impl Postgres {
+    /// Run `f` on a pooled client for `conn`, connecting if none is idle
+    fn with_client<T>(conn: &Connection, f: impl FnMut(&mut postgres::Client) -> Result<T>) -> Result<T> {
+        let url = Postgres::database_url(conn)?;
+        POOL.with_client(&url, || Self::connect(&url, conn), f)
+    }
+
+    /// Connect honoring the URL's `sslmode` and the connection's query timeout
+    fn connect(url: &str, conn: &Connection) -> Result<postgres::Client> {
+        use postgres::config::SslMode;
+
+        let config: postgres::Config = url.parse()?;
+        let mode = config.get_ssl_mode();
+        let res = match mode {
+            SslMode::Disable => config.connect(postgres::NoTls),
+            _ => {
+                // Like libpq's prefer/require: encrypt, don't verify the certificate
+                let tls = native_tls::TlsConnector::builder()
+                    .danger_accept_invalid_certs(true)
+                    .danger_accept_invalid_hostnames(true)
+                    .build()?;
+                config.connect(postgres_native_tls::MakeTlsConnector::new(tls))
+            }
+        };
+        let mut client = res.map_err(|e| {
+            let msg = e.to_string();
+            if mode == SslMode::Disable && ["SSL off", "no encryption", "SSL required"].iter().any(|s| msg.contains(s)) {
+                anyhow::anyhow!("{}\nthe server requires TLS; set `sslmode: require` on this connection", msg)
+            } else if msg.contains("does not support TLS") {
+                anyhow::anyhow!("{}\nthe server does not accept TLS; use `sslmode: prefer` or `disable`", msg)
+            } else {
+                e.into()
+            }
+        })?;
+        if let Some(timeout) = conn.query_timeout() {
+            client.batch_execute(&format!("SET statement_timeout = {}", timeout.as_millis()))?;
+        }
+        Ok(client)
+    }
+
+    /// Placeholders `$1`, `$2`, ... for text parameters written to `columns`
+    /// of `table`, each cast to its column's type (without typmod, so an
+    /// over-long varchar errors instead of being cut short)
+    fn text_params(
+        client: &mut postgres::Client,
+        schema: Option<&str>,
+        table: &str,
+        columns: &[&str],
+    ) -> Result<Vec<String>> {
+        let types: HashMap<String, String> = client
+            .query(
+                "SELECT attname::text, format_type(atttypid, NULL) FROM pg_attribute
+                 WHERE attrelid = to_regclass($1) AND attname::text = ANY($2) AND NOT attisdropped",
+                &[&qualified(schema, table), &columns],
+            )?
+            .into_iter()
+            .map(|r| (r.get(0), r.get(1)))
+            .collect();
+        columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                let ty = types
+                    .get(*col)
+                    .ok_or_else(|| anyhow::anyhow!("column {} not found in {}", col, table))?;
+                Ok(format!("${}::text::{}", i + 1, ty))
+            })
+            .collect()
+    }
+}
+
+/// `a = $1 AND b = $2 ...` matching `key` columns to `params`
+fn key_condition(key: &[(String, String)], params: &[String]) -> String {
+    key.iter()
+        .zip(params)
+        .map(|((col, _), p)| format!("{} = {}", quote(col), p))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
 impl DBBehavior for Postgres {
     fn database_url(conn: &Connection) -> Result<String> {
+        if let Some(url) = conn.url_for(&["postgres", "postgresql"])? {
+            return Ok(url.to_string());
+        }
+        // Fields left out fall back to the PG* variables and defaults psql uses
+        let env = |var: &str| std::env::var(var).ok().filter(|v| !v.is_empty());
         let user = conn
             .user
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("type postgres needs the user field"))?;
-        let host = conn
-            .host
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("type postgres needs the host field"))?;
-        let port = conn
-            .port
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("type postgres needs the port field"))?;
-        let password = conn
-            .password
-            .as_ref()
-            .map_or(String::new(), |p| p.to_string());
-
-        match conn.database.as_ref() {
-            Some(database) => Ok(format!(
+            .clone()
+            .or_else(|| env("PGUSER"))
+            .or_else(|| env("USER"))
+            .or_else(|| env("USERNAME"))
+            .ok_or_else(|| anyhow::anyhow!("type postgres needs the user field (or PGUSER)"))?;
+        let host = conn.host.clone().or_else(|| env("PGHOST")).unwrap_or_else(|| "localhost".to_string());
+        let port = match (conn.port, env("PGPORT")) {
+            (Some(port), _) => port,
+            (None, Some(port)) => port.parse().map_err(|_| anyhow::anyhow!("PGPORT {:?} is not a port", port))?,
+            (None, None) => 5432,
+        };
+        let database = conn.database.clone().or_else(|| env("PGDATABASE"));
+        let password = match conn.password()?.or_else(|| env("PGPASSWORD")) {
+            Some(password) => password,
+            // The server takes the user name when no database is given
+            None => pgpass::lookup(&host, port, database.as_deref().unwrap_or(&user), &user).unwrap_or_default(),
+        };
+
+        let url = match database.as_ref() {
+            Some(database) => format!(
                 "postgres://{user}:{password}@{host}:{port}/{database}",
                 user = user,
                 password = password,
                 host = host,
                 port = port,
                 database = database
-            )),
-            None => Ok(format!(
+            ),
+            None => format!(
                 "postgres://{user}:{password}@{host}:{port}",
                 user = user,
                 password = password,
                 host = host,
                 port = port,
-            )),
-        }
+            ),
+        };
+        // Also understood by pgcli (libpq)
+        Ok(match conn.sslmode {
+            Some(mode) => format!("{}?sslmode={}", url, mode.as_str()),
+            None => url,
+        })
+    }
+    fn quote_identifier(ident: &str) -> String {
+        quote(ident)
+    }
+    fn ping(conn: &Connection) -> Result<()> {
+        let mut client = Self::connect(&Self::database_url(conn)?, conn)?;
+        client.simple_query("SELECT 1")?;
+        Ok(())
+    }
+    fn server_info(conn: &Connection) -> Result<ServerInfo> {
+        Self::with_client(conn, |client| {
+            let version: String = client.query_one("SHOW server_version", &[])?.get(0);
+            let started = Instant::now();
+            client.simple_query("SELECT 1")?;
+            Ok(ServerInfo { version: format!("PostgreSQL {}", version), latency: started.elapsed() })
+        })
     }
     fn fetch_databases(conn: &Connection) -> Result<Vec<Database>> {
         debug("postgres: connecting");
-        let url = Postgres::database_url(conn)?;
-        let mut client = postgres::Client::connect(&url, postgres::NoTls)?;
-        debug("postgres: connected");
-
-        // Collect schema -> tables
-        let rows = client.query(
-            "SELECT table_schema, table_name
-             FROM information_schema.tables
-             WHERE table_type = 'BASE TABLE'
-               AND table_schema NOT IN ('pg_catalog','information_schema')
-             ORDER BY table_schema, table_name",
-            &[],
-        )?;
-
-        use std::collections::BTreeMap;
-        let mut by_schema: BTreeMap<String, Vec<Table>> = BTreeMap::new();
-        for row in rows {
-            let schema: String = row.get(0);
-            let table: String = row.get(1);
-            by_schema.entry(schema.clone()).or_default().push(Table {
-                name: table,
-                engine: None,
-                schema: Some(schema),
-            });
-        }
+        Self::with_client(conn, |client| {
+            debug("postgres: connected");
 
-        // Database name from connection
-        let dbname = conn
-            .database
-            .clone()
-            .unwrap_or_else(|| "postgres".to_string());
+            // Collect schema -> tables
+            let rows = client.query(
+                "SELECT table_schema, table_name, table_type
+                 FROM information_schema.tables
+                 WHERE table_type IN ('BASE TABLE', 'VIEW')
+                   AND table_schema NOT IN ('pg_catalog','information_schema')
+                 ORDER BY table_schema, table_name",
+                &[],
+            )?;
 
-        let mut children = Vec::new();
-        for (schema, tables) in by_schema {
-            children.push(Child::Schema(Schema { name: schema, tables, is_expanded: false }));
-        }
+            use std::collections::BTreeMap;
+            let mut by_schema: BTreeMap<String, Vec<Table>> = BTreeMap::new();
+            for row in rows {
+                let schema: String = row.get(0);
+                let table: String = row.get(1);
+                let table_type: String = row.get(2);
+                by_schema.entry(schema.clone()).or_default().push(Table {
+                    name: table,
+                    engine: None,
+                    schema: Some(schema),
+                    kind: TableKind::from_table_type(&table_type),
+                });
+            }
+
+            // Database name from connection, or the one the server picked (or `url` named)
+            let dbname = match &conn.database {
+                Some(db) => db.clone(),
+                None => client.query_one("SELECT current_database()::text", &[])?.get(0),
+            };
 
-        Ok(vec![Database::new(dbname, children)])
+            let mut children = Vec::new();
+            for (schema, tables) in by_schema {
+                children.push(Child::Schema(Schema { name: schema, tables, is_expanded: false }));
+            }
+
+            Ok(vec![Database::new(dbname, children)])
+        })
     }
 
     fn fetch_records(
         conn: &Connection,
         _database: &str,
+        schema: Option<&str>,
         table: &str,
-        limit: usize,
-        offset: usize,
+        query: &RecordQuery,
     ) -> Result<Records> {
         // columns
-        let url = Postgres::database_url(conn)?;
-        let mut client = postgres::Client::connect(&url, postgres::NoTls)?;
-        let cols_rows = client.query(
-            "SELECT column_name FROM information_schema.columns WHERE table_name = $1 ORDER BY ordinal_position",
-            &[&table],
-        )?;
-        let columns: Vec<String> = cols_rows.into_iter().map(|r| r.get::<_, String>(0)).collect();
-
-        // Build SELECT casting each column to text for consistent string output
-        let select_list = if columns.is_empty() {
-            "*".to_string()
-        } else {
-            columns
-                .iter()
-                .map(|c| format!("\"{}\"::text", c.replace('"', "\"\"")))
-                .collect::<Vec<_>>()
-                .join(", ")
-        };
-        let q = format!("SELECT {} FROM \"{}\" LIMIT $1 OFFSET $2", select_list, table.replace('"', "\"\""));
-        let rows = client.query(&q, &[&(limit as i64), &(offset as i64)])?;
+        Self::with_client(conn, |client| {
+            let cols_rows = client.query(&columns_sql("column_name"), &[&schema, &table])?;
+            let columns: Vec<String> = cols_rows.into_iter().map(|r| r.get::<_, String>(0)).collect();
+
+            // Build SELECT casting each column to text for consistent string output
+            let select_list = if columns.is_empty() {
+                "*".to_string()
+            } else {
+                columns
+                    .iter()
+                    .map(|c| format!("\"{}\"::text", c.replace('"', "\"\"")))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let q = format!(
+                "SELECT {} FROM {}{}{} LIMIT $1 OFFSET $2",
+                select_list,
+                qualified(schema, table),
+                query.where_clause(),
+                query.order_clause()
+            );
+            // Iterate rows as they arrive so the memory cap can stop the fetch early
+            use postgres::fallible_iterator::FallibleIterator;
+            let mut rows = client.query_raw(&q, [query.limit as i64, query.offset as i64])?;
+            let columns = if columns.is_empty() { vec!["(no columns)".to_string()] } else { columns };
+            let mut rows_vec = Vec::new();
+            let mut blobs = HashMap::new();
+            let mut budget = RowBudget::for_connection(conn);
+            let mut chunks = RowChunks::new(query);
+            let mut truncated = false;
+            while let Some(r) = rows.next()? {
+                let mut row_vec = Vec::new();
+                let cols = r.len();
+                for i in 0..cols {
+                    let v: Option<String> = r.get(i);
+                    row_vec.push(v.unwrap_or_default());
+                }
+                if !budget.admit(&row_vec) {
+                    truncated = true;
+                    break;
+                }
+                rows_vec.push(row_vec);
+                chunks.flush(&columns, &mut rows_vec, &mut blobs);
+            }
+
+            Ok(Records { columns, rows: rows_vec, truncated, blobs, kinds: Vec::new() })
+        })
+    }
+
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records> {
+        use postgres::SimpleQueryMessage;
+        // A fresh client: unlike pooled fetches, a failed statement is never retried.
+        // The simple protocol returns every value as text.
+        let mut client = Self::connect(&Postgres::database_url(conn)?, conn)?;
+        let mut columns: Option<Vec<String>> = None;
         let mut rows_vec = Vec::new();
-        for r in rows {
-            let mut row_vec = Vec::new();
-            let cols = r.len();
-            for i in 0..cols {
-                let v: Option<String> = r.get(i);
-                row_vec.push(v.unwrap_or_default());
+        let mut budget = RowBudget::for_connection(conn);
+        let mut truncated = false;
+        for msg in client.simple_query(sql)? {
+            match msg {
+                SimpleQueryMessage::RowDescription(cols) => {
+                    columns = Some(cols.iter().map(|c| c.name().to_string()).collect());
+                }
+                SimpleQueryMessage::Row(r) if !truncated => {
+                    let row_vec: Vec<String> =
+                        (0..r.len()).map(|i| r.get(i).unwrap_or_default().to_string()).collect();
+                    if !budget.admit(&row_vec) {
+                        truncated = true;
+                        continue;
+                    }
+                    rows_vec.push(row_vec);
+                }
+                // End of the first statement's result
+                SimpleQueryMessage::CommandComplete(n) => {
+                    return Ok(match columns {
+                        Some(columns) => {
+                            Records { columns, rows: rows_vec, truncated, blobs: HashMap::new(), kinds: Vec::new() }
+                        }
+                        None => Records::message(format!("{} rows affected", n)),
+                    });
+                }
+                _ => {}
             }
-            rows_vec.push(row_vec);
         }
+        Ok(Records::message("OK"))
+    }
+
+    fn explain(conn: &Connection, sql: &str, analyze: bool) -> Result<Vec<String>> {
+        use postgres::SimpleQueryMessage;
+        let mut client = Self::connect(&Postgres::database_url(conn)?, conn)?;
+        let options = if analyze { "(ANALYZE, BUFFERS) " } else { "" };
+        // EXPLAIN ANALYZE runs the statement; roll back whatever it changed
+        let mut tx = client.transaction()?;
+        let messages = tx.simple_query(&format!("EXPLAIN {}{}", options, sql.trim().trim_end_matches(';')))?;
+        tx.rollback()?;
+        Ok(plan_lines(messages.iter().filter_map(|m| match m {
+            SimpleQueryMessage::Row(r) => r.get(0),
+            _ => None,
+        })))
+    }
 
-        let columns = if columns.is_empty() { vec!["(no columns)".to_string()] } else { columns };
-        Ok(Records { columns, rows: rows_vec })
+    fn update_cell(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        // A fresh client, as for run_query: a write is never retried
+        let mut client = Self::connect(&Postgres::database_url(conn)?, conn)?;
+        let names: Vec<&str> = std::iter::once(column).chain(key.iter().map(|(c, _)| c.as_str())).collect();
+        let params = Self::text_params(&mut client, schema, table, &names)?;
+        let q = format!(
+            "UPDATE {} SET {} = {} WHERE {}",
+            qualified(schema, table),
+            quote(column),
+            params[0],
+            key_condition(key, &params[1..])
+        );
+        let mut values: Vec<&(dyn postgres::types::ToSql + Sync)> = vec![&value];
+        values.extend(key.iter().map(|(_, v)| v as &(dyn postgres::types::ToSql + Sync)));
+        Ok(client.execute(&q, &values)?)
+    }
+
+    fn delete_row(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let mut client = Self::connect(&Postgres::database_url(conn)?, conn)?;
+        let names: Vec<&str> = key.iter().map(|(c, _)| c.as_str()).collect();
+        let params = Self::text_params(&mut client, schema, table, &names)?;
+        let q = format!("DELETE FROM {} WHERE {}", qualified(schema, table), key_condition(key, &params));
+        let values: Vec<&(dyn postgres::types::ToSql + Sync)> =
+            key.iter().map(|(_, v)| v as &(dyn postgres::types::ToSql + Sync)).collect();
+        Ok(client.execute(&q, &values)?)
+    }
+
+    fn insert_row(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64> {
+        let mut client = Self::connect(&Postgres::database_url(conn)?, conn)?;
+        if values.is_empty() {
+            return Ok(client.execute(&format!("INSERT INTO {} DEFAULT VALUES", qualified(schema, table)), &[])?);
+        }
+        let names: Vec<&str> = values.iter().map(|(c, _)| c.as_str()).collect();
+        let params = Self::text_params(&mut client, schema, table, &names)?;
+        let q = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            qualified(schema, table),
+            names.iter().map(|c| quote(c)).collect::<Vec<_>>().join(", "),
+            params.join(", ")
+        );
+        let values: Vec<&(dyn postgres::types::ToSql + Sync)> =
+            values.iter().map(|(_, v)| v as &(dyn postgres::types::ToSql + Sync)).collect();
+        Ok(client.execute(&q, &values)?)
     }
 
     fn fetch_properties(
         conn: &Connection,
         _database: &str,
+        schema: Option<&str>,
         table: &str,
     ) -> Result<TableProperties> {
-        let url = Postgres::database_url(conn)?;
-        let mut client = postgres::Client::connect(&url, postgres::NoTls)?;
+        Self::with_client(conn, |client| {
 
-        // columns
-        let cols_rows = client.query(
-            "SELECT column_name, data_type, is_nullable, column_default
-             FROM information_schema.columns
-             WHERE table_name = $1
-             ORDER BY ordinal_position",
-            &[&table],
-        )?;
-        let mut columns: Vec<ColumnInfo> = cols_rows
-            .into_iter()
-            .map(|r| ColumnInfo {
-                name: r.get::<_, String>(0),
-                data_type: r.get::<_, String>(1),
-                nullable: {
-                    let s: String = r.get(2);
-                    s.eq_ignore_ascii_case("YES")
-                },
-                default: r.get::<_, Option<String>>(3),
-                primary_key: false, // fill below
-            })
-            .collect();
+            // columns
+            let cols_rows = client.query(
+                &columns_sql(
+                    "column_name, data_type, is_nullable, column_default,
+                     col_description(
+                         (quote_ident(table_schema) || '.' || quote_ident(table_name))::regclass,
+                         ordinal_position::int
+                     )",
+                ),
+                &[&schema, &table],
+            )?;
+            let mut columns: Vec<ColumnInfo> = cols_rows
+                .into_iter()
+                .map(|r| ColumnInfo {
+                    name: r.get::<_, String>(0),
+                    data_type: r.get::<_, String>(1),
+                    nullable: {
+                        let s: String = r.get(2);
+                        s.eq_ignore_ascii_case("YES")
+                    },
+                    default: r.get::<_, Option<String>>(3),
+                    primary_key: false, // fill below
+                    charset: None,
+                    collation: None,
+                    comment: r.get::<_, Option<String>>(4),
+                })
+                .collect();
 
-        // primary key columns (use information_schema to avoid regclass parameter typing issues)
-        let pk_rows = client.query(
-            "SELECT kcu.column_name
-             FROM information_schema.table_constraints tc
-             JOIN information_schema.key_column_usage kcu
-               ON tc.constraint_name = kcu.constraint_name
-              AND tc.table_schema = kcu.table_schema
-             WHERE tc.constraint_type = 'PRIMARY KEY'
-               AND tc.table_name = $1",
-            &[&table],
-        )?;
-        let pk: std::collections::HashSet<String> =
-            pk_rows.into_iter().map(|r| r.get::<_, String>(0)).collect();
-        for c in &mut columns {
-            if pk.contains(&c.name) {
-                c.primary_key = true;
+            // primary key columns (use information_schema to avoid regclass parameter typing issues)
+            let pk_rows = client.query(
+                &format!(
+                    "SELECT kcu.column_name
+                     FROM information_schema.table_constraints tc
+                     JOIN information_schema.key_column_usage kcu
+                       ON tc.constraint_name = kcu.constraint_name
+                      AND tc.table_schema = kcu.table_schema
+                     WHERE tc.constraint_type = 'PRIMARY KEY'
+                       AND {} AND tc.table_name = $2",
+                    schema_condition("tc.table_schema", 1)
+                ),
+                &[&schema, &table],
+            )?;
+            let pk: std::collections::HashSet<String> =
+                pk_rows.into_iter().map(|r| r.get::<_, String>(0)).collect();
+            for c in &mut columns {
+                if pk.contains(&c.name) {
+                    c.primary_key = true;
+                }
             }
-        }
 
-        Ok(TableProperties { columns })
+            Ok(TableProperties { columns })
+        })
+    }
+
+    fn fetch_indexes(conn: &Connection, _database: &str, schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>> {
+        Self::with_client(conn, |client| {
+            // pg_get_indexdef renders each key column, expressions included
+            let rows = client.query(
+                "SELECT i.relname::text, ix.indisunique,
+                        array(SELECT pg_get_indexdef(ix.indexrelid, k, true)
+                              FROM generate_series(1, ix.indnatts) k)
+                 FROM pg_index ix JOIN pg_class i ON i.oid = ix.indexrelid
+                 WHERE ix.indrelid = to_regclass($1)
+                 ORDER BY i.relname",
+                &[&qualified(schema, table)],
+            )?;
+            Ok(rows
+                .into_iter()
+                .map(|r| IndexInfo { name: r.get(0), unique: r.get(1), columns: r.get(2) })
+                .collect())
+        })
+    }
+
+    fn fetch_foreign_keys(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        Self::with_client(conn, |client| {
+            let action = |col: &str| {
+                format!(
+                    "CASE {} WHEN 'a' THEN 'NO ACTION' WHEN 'r' THEN 'RESTRICT' WHEN 'c' THEN 'CASCADE'
+                     WHEN 'n' THEN 'SET NULL' ELSE 'SET DEFAULT' END",
+                    col
+                )
+            };
+            let q = format!(
+                "SELECT a.attname::text, n.nspname::text, c.relname::text, fa.attname::text, {}, {}
+                 FROM pg_constraint con
+                 CROSS JOIN LATERAL unnest(con.conkey, con.confkey) WITH ORDINALITY AS k(col, fcol, pos)
+                 JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.col
+                 JOIN pg_attribute fa ON fa.attrelid = con.confrelid AND fa.attnum = k.fcol
+                 JOIN pg_class c ON c.oid = con.confrelid
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 WHERE con.contype = 'f' AND con.conrelid = to_regclass($1)
+                 ORDER BY con.conname, k.pos",
+                action("con.confdeltype"),
+                action("con.confupdtype")
+            );
+            Ok(client
+                .query(&q, &[&qualified(schema, table)])?
+                .into_iter()
+                .map(|r| ForeignKeyInfo {
+                    column: r.get(0),
+                    referenced_database: None,
+                    referenced_schema: Some(r.get(1)),
+                    referenced_table: r.get(2),
+                    referenced_column: r.get(3),
+                    on_delete: r.get(4),
+                    on_update: r.get(5),
+                })
+                .collect())
+        })
+    }
+
+    fn fetch_ddl(conn: &Connection, _database: &str, schema: Option<&str>, table: &str) -> Result<String> {
+        let name = qualified(schema, table);
+        Self::with_client(conn, |client| {
+            let rel = client
+                .query_opt("SELECT oid, relkind::text FROM pg_class WHERE oid = to_regclass($1)", &[&name])?
+                .ok_or_else(|| anyhow::anyhow!("table {} not found", name))?;
+            let (oid, kind): (u32, String) = (rel.get(0), rel.get(1));
+            if kind == "v" || kind == "m" {
+                let body: String = client.query_one("SELECT pg_get_viewdef($1, true)", &[&oid])?.get(0);
+                let what = if kind == "m" { "MATERIALIZED VIEW" } else { "VIEW" };
+                return Ok(ddl_script([format!("CREATE {} {} AS\n{}", what, name, body)]));
+            }
+            // Postgres keeps no CREATE TABLE text; rebuild it from the catalog
+            let mut lines = Vec::new();
+            let columns = client.query(
+                "SELECT quote_ident(a.attname), format_type(a.atttypid, a.atttypmod), a.attnotnull,
+                        a.attidentity::text, a.attgenerated::text, pg_get_expr(d.adbin, d.adrelid)
+                 FROM pg_attribute a
+                 LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+                 WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped
+                 ORDER BY a.attnum",
+                &[&oid],
+            )?;
+            for r in &columns {
+                let mut line = format!("    {} {}", r.get::<_, String>(0), r.get::<_, String>(1));
+                let expr: Option<String> = r.get(5);
+                match (r.get::<_, String>(3).as_str(), r.get::<_, String>(4).as_str(), expr) {
+                    ("a", _, _) => line.push_str(" GENERATED ALWAYS AS IDENTITY"),
+                    ("d", _, _) => line.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+                    (_, "s", Some(expr)) => line.push_str(&format!(" GENERATED ALWAYS AS ({}) STORED", expr)),
+                    (_, _, Some(expr)) => line.push_str(&format!(" DEFAULT {}", expr)),
+                    _ => {}
+                }
+                if r.get::<_, bool>(2) {
+                    line.push_str(" NOT NULL");
+                }
+                lines.push(line);
+            }
+            // Primary key first; NOT NULL constraints (Postgres 18) are already on the columns
+            let constraints = client.query(
+                "SELECT quote_ident(conname), pg_get_constraintdef(oid, true) FROM pg_constraint
+                 WHERE conrelid = $1 AND contype <> 'n'
+                 ORDER BY contype <> 'p', conname",
+                &[&oid],
+            )?;
+            for r in &constraints {
+                lines.push(format!("    CONSTRAINT {} {}", r.get::<_, String>(0), r.get::<_, String>(1)));
+            }
+            let mut statements = vec![format!("CREATE TABLE {} (\n{}\n)", name, lines.join(",\n"))];
+            // Indexes backing constraints are created by them
+            let indexes = client.query(
+                "SELECT pg_get_indexdef(i.indexrelid) FROM pg_index i
+                 WHERE i.indrelid = $1
+                   AND NOT EXISTS (SELECT 1 FROM pg_constraint c
+                                   WHERE c.conrelid = i.indrelid AND c.conindid = i.indexrelid)
+                 ORDER BY i.indexrelid::regclass::text",
+                &[&oid],
+            )?;
+            statements.extend(indexes.iter().map(|r| r.get::<_, String>(0)));
+            Ok(ddl_script(statements))
+        })
+    }
+
+    fn fetch_row_count(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        exact: bool,
+    ) -> Result<RowCount> {
+        Self::with_client(conn, |client| {
+
+            if !exact {
+                let row = client.query_opt(ESTIMATE_SQL, &[&qualified(schema, table)])?;
+                if let Some(n) = row.map(|r| r.get::<_, i64>(0)).filter(|n| *n > 0) {
+                    return Ok(RowCount::Estimate(n as u64));
+                }
+            }
+
+            let q = format!("SELECT COUNT(*) FROM {}", qualified(schema, table));
+            let n: i64 = client.query_one(&q, &[])?.get(0);
+            Ok(RowCount::Exact(n as u64))
+        })
+    }
+
+    fn count_matches(
+        conn: &Connection,
+        _database: &str,
+        schema: Option<&str>,
+        table: &str,
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>> {
+        Self::with_client(conn, |client| {
+            let q = format!(
+                "SELECT {} FROM {}",
+                match_counts(columns.iter().map(|c| quote(c)), "ILIKE", "$1"),
+                qualified(schema, table)
+            );
+            let row = client.query_one(&q, &[&contains_pattern(needle)])?;
+            Ok((0..columns.len()).map(|i| row.get::<_, i64>(i) as u64).collect())
+        })
     }
     
     fn cli_tool_name() -> &'static str {
@@ -194,13 +665,125 @@ impl DBBehavior for Postgres {
             .unwrap_or(false)
     }
     
-    fn launch_cli_tool(conn: &Connection) -> Result<std::process::ExitStatus> {
+    fn cli_command(conn: &Connection, target: Option<&CliTarget>) -> Result<Command> {
         let db_url = Self::database_url(conn)?;
-        debug(&format!("Launching pgcli with URL: {}", db_url));
+        let mut cmd = Command::new("pgcli");
+        cmd.arg(db_url);
+        // pgcli has no "open table" flag; putting the table's schema first on the
+        // search_path (honored by libpq via PGOPTIONS) lets `\d table` resolve unqualified.
+        // libpq splits PGOPTIONS at spaces unless backslash-escaped
+        if let Some(schema) = target.and_then(|t| t.schema.as_deref()) {
+            let schema = quote(schema).replace('\\', "\\\\").replace(' ', "\\ ");
+            cmd.env("PGOPTIONS", format!("-c search_path={},public", schema));
+        }
+        Ok(cmd)
+    }
+    
+    fn launch_cli_tool(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::ExitStatus> {
+        let mut cmd = Self::cli_command(conn, target)?;
+        debug("Launching pgcli");
         
-        Command::new("pgcli")
-            .arg(&db_url)
-            .status()
+        cmd.status()
             .map_err(|e| anyhow::anyhow!("Failed to launch pgcli: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn cli_for(schema: Option<&str>) -> Command {
+        let conn: Connection = serde_yaml::from_str(
+            "type: postgres\nname: t\nhost: db.local\nport: 5433\nuser: app\npassword: pw\ndatabase: shop",
+        )
+        .unwrap();
+        let target = CliTarget {
+            database: "shop".to_string(),
+            schema: schema.map(str::to_string),
+            table: "t".to_string(),
+        };
+        Postgres::cli_command(&conn, Some(&target)).unwrap()
+    }
+
+    fn pgoptions(cmd: &Command) -> Option<&OsStr> {
+        cmd.get_envs().find(|(k, _)| *k == "PGOPTIONS").and_then(|(_, v)| v)
+    }
+
+    #[test]
+    fn same_named_tables_in_two_schemas_stay_apart() {
+        assert_eq!(qualified(Some("public"), "users"), r#""public"."users""#);
+        assert_eq!(qualified(Some("reporting"), "users"), r#""reporting"."users""#);
+        // Without a schema the name resolves through search_path
+        assert_eq!(qualified(None, "users"), r#""users""#);
+        assert_eq!(qualified(Some("Odd\"s"), "t"), r#""Odd""s"."t""#);
+        // Column and estimate lookups bind the schema beside the table name
+        let sql = columns_sql("column_name");
+        assert!(sql.contains("table_schema = COALESCE($1::text, current_schema()) AND table_name = $2"), "{}", sql);
+        assert!(ESTIMATE_SQL.contains("oid = to_regclass($1)"));
+    }
+
+    /// Run with the dev server up: `docker compose -f dev/docker-compose.yml up -d postgres`,
+    /// then `cargo test -- --ignored`. Works in a scratch database it creates and drops.
+    #[test]
+    #[ignore = "needs the Postgres from dev/docker-compose.yml"]
+    fn same_named_tables_in_two_schemas_read_their_own_data() {
+        const DATABASE: &str = "clazydbm_schema_test";
+        let admin = |database: &str| {
+            let url = format!("host=127.0.0.1 port=5432 user=postgres password=postgres dbname={}", database);
+            postgres::Client::connect(&url, postgres::NoTls).unwrap()
+        };
+        let mut server = admin("demo");
+        // One statement each: a batch would run them in a transaction
+        server.batch_execute(&format!("DROP DATABASE IF EXISTS {}", DATABASE)).unwrap();
+        server.batch_execute(&format!("CREATE DATABASE {}", DATABASE)).unwrap();
+        admin(DATABASE)
+            .batch_execute(
+                "CREATE TABLE public.users (id int PRIMARY KEY, name text);
+                 INSERT INTO public.users VALUES (1, 'alice'), (2, 'bob');
+                 CREATE SCHEMA reporting;
+                 CREATE TABLE reporting.users (day date, signups int);
+                 INSERT INTO reporting.users VALUES ('2024-01-01', 7);",
+            )
+            .unwrap();
+
+        let conn: Connection = serde_yaml::from_str(&format!(
+            "type: postgres\nname: t\nhost: 127.0.0.1\nport: 5432\nuser: postgres\npassword: postgres\ndatabase: {}",
+            DATABASE
+        ))
+        .unwrap();
+        let query = RecordQuery { limit: 10, order: Some("1".to_string()), ..Default::default() };
+        let read = |schema: &str| {
+            let properties = Postgres::fetch_properties(&conn, DATABASE, Some(schema), "users").unwrap();
+            let records = Postgres::fetch_records(&conn, DATABASE, Some(schema), "users", &query).unwrap();
+            (properties.columns.into_iter().map(|c| c.name).collect::<Vec<_>>(), records.columns, records.rows)
+        };
+        let public = read("public");
+        let reporting = read("reporting");
+        server.batch_execute(&format!("DROP DATABASE {} WITH (FORCE)", DATABASE)).unwrap();
+
+        assert_eq!(public.0, ["id", "name"]);
+        assert_eq!(public.1, ["id", "name"]);
+        assert_eq!(public.2, [["1", "alice"], ["2", "bob"]]);
+        assert_eq!(reporting.0, ["day", "signups"]);
+        assert_eq!(reporting.1, ["day", "signups"]);
+        assert_eq!(reporting.2, [["2024-01-01", "7"]]);
+    }
+
+    #[test]
+    fn cli_command_connects_to_the_database_url() {
+        let cmd = cli_for(None);
+        assert_eq!(cmd.get_program(), "pgcli");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["postgres://app:pw@db.local:5433/shop"]);
+        assert_eq!(pgoptions(&cmd), None);
+    }
+
+    #[test]
+    fn cli_search_path_quotes_the_schema() {
+        assert_eq!(pgoptions(&cli_for(Some("sales"))).unwrap(), r#"-c search_path="sales",public"#);
+        assert_eq!(
+            pgoptions(&cli_for(Some(r#"Q1 "plan" \ x"#))).unwrap(),
+            r#"-c search_path="Q1\ ""plan""\ \\\ x",public"#
+        );
+    }
+}
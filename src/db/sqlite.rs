@@ -1,33 +1,75 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
-use crate::component::{Child, Database, Table};
+use crate::component::{Child, Database, Table, TableKind};
 use crate::{connection::Connection, db::DBBehavior};
-use crate::db::{Records, ColumnInfo, TableProperties};
+use crate::db::{blob_summary, contains_pattern, ddl_script, match_counts, Blob, CliTarget, ServerInfo, ForeignKeyInfo, IndexInfo, RecordQuery, Records, ColumnInfo, RowBudget, RowChunks, RowCount, TableProperties};
 use crate::logger::debug;
 use std::process::Command;
+use std::time::Instant;
 
 pub struct Sqlite {}
 
+impl Sqlite {
+    /// Open the database file, bounding each statement by the connection's
+    /// query timeout: lock waits give up after it, and running statements
+    /// are interrupted once it has passed since opening
+    fn open(conn: &Connection) -> Result<rusqlite::Connection> {
+        let sc = rusqlite::Connection::open(file_path(conn, "sqlite")?)?;
+        if let Some(timeout) = conn.query_timeout() {
+            sc.busy_timeout(timeout)?;
+            let deadline = std::time::Instant::now() + timeout;
+            sc.progress_handler(10_000, Some(move || std::time::Instant::now() > deadline));
+        }
+        Ok(sc)
+    }
+}
+
+/// Double-quote an identifier
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// `a = ?N AND b = ?N+1 ...` over the `key` columns, numbering from `first`
+fn key_condition(key: &[(String, String)], first: usize) -> String {
+    key.iter()
+        .enumerate()
+        .map(|(i, (col, _))| format!("{} = ?{}", quote(col), first + i))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
 impl DBBehavior for Sqlite {
     fn database_url(conn: &Connection) -> Result<String> {
-        let path = conn.path.as_ref().map_or(
-            Err(anyhow::anyhow!("type sqlite needs the path field")),
-            |path| expand_path(path).ok_or_else(|| anyhow::anyhow!("cannot expand file path")),
-        )?;
-
-        Ok(format!("sqlite://{path}", path = path.to_str().unwrap()))
+        let path = file_path(conn, "sqlite")?;
+        let path = path.to_str().ok_or_else(|| anyhow::anyhow!("path {} is not valid UTF-8", path.display()))?;
+        Ok(format!("sqlite://{path}"))
+    }
+    fn quote_identifier(ident: &str) -> String {
+        quote(ident)
+    }
+    fn ping(conn: &Connection) -> Result<()> {
+        use rusqlite::{Connection as SqliteConn, OpenFlags};
+        let path = file_path(conn, "sqlite")?;
+        // Read-only so a mistyped path is reported instead of created
+        let sc = SqliteConn::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        // Opening is lazy; reading the schema fails on a file that isn't a database
+        sc.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+        Ok(())
+    }
+    fn server_info(conn: &Connection) -> Result<ServerInfo> {
+        // The library linked into clazydbm, not a server
+        let sc = Self::open(conn)?;
+        let version: String = sc.query_row("SELECT sqlite_version()", [], |row| row.get(0))?;
+        let started = Instant::now();
+        sc.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))?;
+        Ok(ServerInfo { version: format!("SQLite {}", version), latency: started.elapsed() })
     }
     fn fetch_databases(conn: &Connection) -> Result<Vec<Database>> {
         debug("sqlite: opening file");
-        use rusqlite::Connection as SqliteConn;
-
-        let path = conn
-            .path
-            .as_ref()
-            .and_then(|p| expand_path(p))
-            .ok_or_else(|| anyhow::anyhow!("invalid sqlite path"))?;
+        let path = file_path(conn, "sqlite")?;
 
         let dbname = conn
             .name
@@ -35,20 +77,21 @@ impl DBBehavior for Sqlite {
             .or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
             .unwrap_or_else(|| "sqlite".to_string());
 
-        let sc = SqliteConn::open(path)?;
+        let sc = Self::open(conn)?;
         debug("sqlite: opened");
         let mut stmt = sc.prepare(
-            "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
         )?;
-        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
 
         let mut children = Vec::new();
         for r in rows {
-            let name = r?;
+            let (name, table_type) = r?;
             children.push(Child::Table(Table {
                 name,
                 engine: None,
                 schema: None,
+                kind: TableKind::from_table_type(&table_type),
             }));
         }
 
@@ -58,64 +101,121 @@ impl DBBehavior for Sqlite {
     fn fetch_records(
         conn: &Connection,
         database: &str,
+        _schema: Option<&str>,
         table: &str,
-        limit: usize,
-        offset: usize,
+        query: &RecordQuery,
     ) -> Result<Records> {
-        use rusqlite::Connection as SqliteConn;
         let _ = database; // not used for sqlite
-        let path = conn
-            .path
-            .as_ref()
-            .and_then(|p| expand_path(p))
-            .ok_or_else(|| anyhow::anyhow!("invalid sqlite path"))?;
-        let sc = SqliteConn::open(path)?;
+        let sc = Self::open(conn)?;
 
         // columns
-        let mut col_stmt = sc.prepare(&format!("PRAGMA table_info({});", table))?;
+        let mut col_stmt = sc.prepare(&format!("PRAGMA table_info({});", quote(table)))?;
         let col_iter = col_stmt.query_map([], |row| row.get::<_, String>(1))?; // name is col 1
         let mut columns = Vec::new();
         for c in col_iter { columns.push(c?); }
 
-        // rows: read ValueRef per column and stringify conservatively
-        use rusqlite::types::ValueRef;
-        let mut rows_vec: Vec<Vec<String>> = Vec::new();
-        let q = format!("SELECT * FROM {} LIMIT {} OFFSET {}", table, limit, offset);
+        let q = format!(
+            "SELECT * FROM {}{}{} LIMIT {} OFFSET {}",
+            quote(table), query.where_clause(), query.order_clause(), query.limit, query.offset
+        );
         let mut stmt = sc.prepare(&q)?;
-        let col_count = stmt.column_count();
-        let mut rows = stmt.query([])?;
-        while let Some(row) = rows.next()? {
-            let mut v = Vec::with_capacity(col_count);
-            for i in 0..col_count {
-                let cell = row.get_ref(i)?;
-                let s = match cell {
-                    ValueRef::Null => String::new(),
-                    ValueRef::Integer(i) => i.to_string(),
-                    ValueRef::Real(f) => f.to_string(),
-                    ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
-                    ValueRef::Blob(b) => format!("<blob {} bytes>", b.len()),
-                };
-                v.push(s);
-            }
-            rows_vec.push(v);
+        collect_records(conn, columns, &mut stmt, RowChunks::new(query))
+    }
+
+    fn run_query(conn: &Connection, sql: &str) -> Result<Records> {
+        let sc = Self::open(conn)?;
+        let mut stmt = sc.prepare(sql.trim())?;
+        if stmt.column_count() == 0 {
+            let n = stmt.execute([])?;
+            return Ok(Records::message(format!("{} rows affected", n)));
         }
+        let columns = stmt.column_names().into_iter().map(String::from).collect();
+        collect_records(conn, columns, &mut stmt, RowChunks::default())
+    }
 
-        Ok(Records { columns, rows: rows_vec })
+    fn explain(conn: &Connection, sql: &str, _analyze: bool) -> Result<Vec<String>> {
+        // SQLite has no EXPLAIN ANALYZE; the query plan never runs the statement
+        let sc = Self::open(conn)?;
+        let mut stmt = sc.prepare(&format!("EXPLAIN QUERY PLAN {}", sql.trim().trim_end_matches(';')))?;
+        let steps = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(3)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        // Indent each step under its parent, as the sqlite3 shell does
+        let mut depths: HashMap<i64, usize> = HashMap::new();
+        let mut lines = Vec::with_capacity(steps.len());
+        for (id, parent, detail) in steps {
+            let depth = depths.get(&parent).map_or(0, |d| d + 1);
+            depths.insert(id, depth);
+            lines.push(format!("{}{}", "  ".repeat(depth), detail));
+        }
+        Ok(lines)
+    }
+
+    fn update_cell(
+        conn: &Connection,
+        _database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: Option<&str>,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let sc = Self::open(conn)?;
+        // Column affinity turns the text back into a number where the column declares one
+        let q = format!(
+            "UPDATE {} SET {} = ?1 WHERE {}",
+            quote(table),
+            quote(column),
+            key_condition(key, 2)
+        );
+        let params = std::iter::once(value).chain(key.iter().map(|(_, v)| Some(v.as_str())));
+        let n = sc.execute(&q, rusqlite::params_from_iter(params))?;
+        Ok(n as u64)
+    }
+
+    fn delete_row(
+        conn: &Connection,
+        _database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        key: &[(String, String)],
+    ) -> Result<u64> {
+        let sc = Self::open(conn)?;
+        let q = format!("DELETE FROM {} WHERE {}", quote(table), key_condition(key, 1));
+        let n = sc.execute(&q, rusqlite::params_from_iter(key.iter().map(|(_, v)| v.as_str())))?;
+        Ok(n as u64)
+    }
+
+    fn insert_row(
+        conn: &Connection,
+        _database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        values: &[(String, String)],
+    ) -> Result<u64> {
+        let sc = Self::open(conn)?;
+        let q = if values.is_empty() {
+            format!("INSERT INTO {} DEFAULT VALUES", quote(table))
+        } else {
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                quote(table),
+                values.iter().map(|(c, _)| quote(c)).collect::<Vec<_>>().join(", "),
+                (1..=values.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ")
+            )
+        };
+        let n = sc.execute(&q, rusqlite::params_from_iter(values.iter().map(|(_, v)| v.as_str())))?;
+        Ok(n as u64)
     }
 
     fn fetch_properties(
         conn: &Connection,
         _database: &str,
+        _schema: Option<&str>,
         table: &str,
     ) -> Result<TableProperties> {
-        use rusqlite::Connection as SqliteConn;
-        let path = conn
-            .path
-            .as_ref()
-            .and_then(|p| expand_path(p))
-            .ok_or_else(|| anyhow::anyhow!("invalid sqlite path"))?;
-        let sc = SqliteConn::open(path)?;
-        let mut stmt = sc.prepare(&format!("PRAGMA table_info({});", table))?;
+        let sc = Self::open(conn)?;
+        let mut stmt = sc.prepare(&format!("PRAGMA table_info({});", quote(table)))?;
         let rows = stmt.query_map([], |row| {
             let name: String = row.get(1)?;
             let data_type: String = row.get(2)?;
@@ -129,12 +229,111 @@ impl DBBehavior for Sqlite {
                 nullable: if is_primary_key { false } else { notnull == 0 },
                 default: dflt,
                 primary_key: is_primary_key,
+                charset: None,
+                collation: None,
+                comment: None,
             })
         })?;
         let mut columns = Vec::new();
         for r in rows { columns.push(r?); }
         Ok(TableProperties { columns })
     }
+
+    fn fetch_indexes(conn: &Connection, _database: &str, _schema: Option<&str>, table: &str) -> Result<Vec<IndexInfo>> {
+        let sc = Self::open(conn)?;
+        let mut list = sc.prepare(&format!("PRAGMA index_list({})", quote(table)))?;
+        let entries = list
+            .query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, i64>(2)? != 0)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut indexes = Vec::new();
+        for (name, unique) in entries {
+            // name is NULL for expression columns
+            let mut info = sc.prepare(&format!("PRAGMA index_info({})", quote(&name)))?;
+            let columns = info
+                .query_map([], |row| row.get::<_, Option<String>>(2))?
+                .map(|c| c.map(|c| c.unwrap_or_else(|| "(expression)".to_string())))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            indexes.push(IndexInfo { name, columns, unique });
+        }
+        indexes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(indexes)
+    }
+
+    fn fetch_foreign_keys(
+        conn: &Connection,
+        _database: &str,
+        _schema: Option<&str>,
+        table: &str,
+    ) -> Result<Vec<ForeignKeyInfo>> {
+        let sc = Self::open(conn)?;
+        let mut stmt = sc.prepare(&format!("PRAGMA foreign_key_list({})", quote(table)))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ForeignKeyInfo {
+                column: row.get(3)?,
+                referenced_database: None,
+                referenced_schema: None,
+                referenced_table: row.get(2)?,
+                // NULL when the key references the parent's primary key implicitly
+                referenced_column: row.get::<_, Option<String>>(4)?.unwrap_or_else(|| "(primary key)".to_string()),
+                on_delete: row.get(6)?,
+                on_update: row.get(5)?,
+            })
+        })?;
+        let mut keys = Vec::new();
+        for r in rows { keys.push(r?); }
+        Ok(keys)
+    }
+
+    fn fetch_ddl(conn: &Connection, _database: &str, _schema: Option<&str>, table: &str) -> Result<String> {
+        let sc = Self::open(conn)?;
+        // The table or view first, then its indexes and triggers; indexes
+        // backing constraints have no SQL of their own
+        let mut stmt = sc.prepare(
+            "SELECT sql FROM sqlite_master WHERE tbl_name = ?1 AND sql IS NOT NULL
+             ORDER BY type NOT IN ('table', 'view'), type, name",
+        )?;
+        let statements = stmt
+            .query_map([table], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if statements.is_empty() {
+            anyhow::bail!("table {} not found", table);
+        }
+        Ok(ddl_script(statements))
+    }
+
+    fn fetch_row_count(
+        conn: &Connection,
+        _database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        _exact: bool,
+    ) -> Result<RowCount> {
+        // SQLite keeps no row statistics, so the count is always exact
+        let sc = Self::open(conn)?;
+        let n: i64 = sc.query_row(&format!("SELECT COUNT(*) FROM {}", quote(table)), [], |row| row.get(0))?;
+        Ok(RowCount::Exact(n as u64))
+    }
+
+    fn count_matches(
+        conn: &Connection,
+        _database: &str,
+        _schema: Option<&str>,
+        table: &str,
+        columns: &[String],
+        needle: &str,
+    ) -> Result<Vec<u64>> {
+        // LIKE is case-insensitive for ASCII letters
+        let sc = Self::open(conn)?;
+        let q = format!(
+            "SELECT {} FROM {}",
+            match_counts(columns.iter().map(|c| quote(c)), "LIKE", "?1"),
+            quote(table)
+        );
+        let counts = sc.query_row(&q, [contains_pattern(needle)], |row| {
+            (0..columns.len()).map(|i| row.get::<_, i64>(i).map(|n| n as u64)).collect()
+        })?;
+        Ok(counts)
+    }
     
     fn cli_tool_name() -> &'static str {
         "litecli"
@@ -148,19 +347,82 @@ impl DBBehavior for Sqlite {
             .unwrap_or(false)
     }
     
-    fn launch_cli_tool(conn: &Connection) -> Result<std::process::ExitStatus> {
-        let path = conn.path.as_ref()
-            .ok_or_else(|| anyhow::anyhow!("SQLite connection requires a path"))?;
-        debug(&format!("Launching litecli with file: {:?}", path));
+    fn cli_command(conn: &Connection, _target: Option<&CliTarget>) -> Result<Command> {
+        // A SQLite file has a single namespace, so there is nothing to pre-focus
+        let path = file_path(conn, "sqlite")?;
+        let mut cmd = Command::new("litecli");
+        cmd.arg(path);
+        Ok(cmd)
+    }
+    
+    fn launch_cli_tool(conn: &Connection, target: Option<&CliTarget>) -> Result<std::process::ExitStatus> {
+        let mut cmd = Self::cli_command(conn, target)?;
+        debug(&format!("Launching litecli with file: {:?}", cmd.get_args().next()));
         
-        Command::new("litecli")
-            .arg(path)
-            .status()
+        cmd.status()
             .map_err(|e| anyhow::anyhow!("Failed to launch litecli: {}", e))
     }
 }
 
-fn expand_path(path: &Path) -> Option<PathBuf> {
+/// Run `stmt` and read its rows, stringifying each ValueRef conservatively,
+/// until the memory cap, passing them on through `chunks` as they're read
+fn collect_records(
+    conn: &Connection,
+    columns: Vec<String>,
+    stmt: &mut rusqlite::Statement<'_>,
+    mut chunks: RowChunks,
+) -> Result<Records> {
+    use rusqlite::types::ValueRef;
+    let col_count = stmt.column_count();
+    let mut rows = stmt.query([])?;
+    let mut rows_vec: Vec<Vec<String>> = Vec::new();
+    let mut budget = RowBudget::for_connection(conn);
+    let mut truncated = false;
+    let mut blobs = HashMap::new();
+    while let Some(row) = rows.next()? {
+        let mut v = Vec::with_capacity(col_count);
+        let mut row_blobs = Vec::new();
+        for i in 0..col_count {
+            let cell = row.get_ref(i)?;
+            let s = match cell {
+                ValueRef::Null => String::new(),
+                ValueRef::Integer(i) => i.to_string(),
+                ValueRef::Real(f) => f.to_string(),
+                ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+                ValueRef::Blob(b) => {
+                    row_blobs.push((i, Blob::new(b, conn)));
+                    blob_summary(b.len())
+                }
+            };
+            v.push(s);
+        }
+        if !budget.admit_with_blobs(&v, &row_blobs) {
+            truncated = true;
+            break;
+        }
+        let r = rows_vec.len();
+        blobs.extend(row_blobs.into_iter().map(|(c, b)| ((r, c), b)));
+        rows_vec.push(v);
+        chunks.flush(&columns, &mut rows_vec, &mut blobs);
+    }
+
+    Ok(Records { columns, rows: rows_vec, truncated, blobs, kinds: Vec::new() })
+}
+
+/// Database file of a file-backed connection: the part of `url` after
+/// `<scheme>://` when set, `path` otherwise, with `~` and variables expanded
+pub(super) fn file_path(conn: &Connection, scheme: &str) -> Result<PathBuf> {
+    let path = match conn.url_for(&[scheme])? {
+        Some(url) => PathBuf::from(&url[scheme.len() + "://".len()..]),
+        None => conn
+            .path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("type {} needs the path or url field", scheme))?,
+    };
+    expand_path(&path).ok_or_else(|| anyhow::anyhow!("cannot expand file path {}", path.display()))
+}
+
+pub(super) fn expand_path(path: &Path) -> Option<PathBuf> {
     let mut expanded_path = PathBuf::new();
     let mut path_iter = path.iter();
     if path.starts_with("~") {
@@ -182,3 +444,37 @@ fn expand_path(path: &Path) -> Option<PathBuf> {
 }
 
 // (fetch_databases moved into trait impl above)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_named_like_keywords_or_with_spaces_are_quoted() {
+        let path = std::env::temp_dir().join(format!("clazydbm-quote-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let sc = rusqlite::Connection::open(&path).unwrap();
+        sc.execute_batch(
+            "CREATE TABLE \"order\" (id INTEGER PRIMARY KEY, total REAL);
+             INSERT INTO \"order\" (total) VALUES (1.5), (2.5);
+             CREATE TABLE \"my table\" (name TEXT);
+             INSERT INTO \"my table\" VALUES ('a');",
+        )
+        .unwrap();
+        drop(sc);
+        let yaml = format!("type: sqlite\nname: t\npath: {}", path.display());
+        let conn: Connection = serde_yaml::from_str(&yaml).unwrap();
+
+        for (table, rows, columns) in [("order", 2, vec!["id", "total"]), ("my table", 1, vec!["name"])] {
+            let count = Sqlite::fetch_row_count(&conn, "main", None, table, true).unwrap();
+            assert!(matches!(count, RowCount::Exact(n) if n == rows), "{table}");
+            let query = RecordQuery { limit: 10, ..Default::default() };
+            let records = Sqlite::fetch_records(&conn, "main", None, table, &query).unwrap();
+            assert_eq!(records.columns, columns, "{table}");
+            assert_eq!(records.rows.len() as u64, rows, "{table}");
+            let properties = Sqlite::fetch_properties(&conn, "main", None, table).unwrap();
+            assert_eq!(properties.columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), columns, "{table}");
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
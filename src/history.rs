@@ -0,0 +1,70 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// File in the app config dir holding one JSON entry per line, oldest first
+const HISTORY_FILENAME: &str = "history";
+/// Entries kept unless the config's `history_size` says otherwise
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// A statement run from the SQL tab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Local time it ran, as `YYYY-MM-DD HH:MM:SS`
+    pub time: String,
+    pub connection: String,
+    pub sql: String,
+}
+
+fn history_path() -> Result<PathBuf> {
+    Ok(Config::app_config_dir()?.join(HISTORY_FILENAME))
+}
+
+/// Every recorded statement, oldest first; lines that don't parse are skipped
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(data.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// Append `sql` to the history, then drop the oldest entries beyond `cap`.
+/// A cap of 0 turns the history off.
+pub fn record(connection: &str, sql: &str, cap: usize) -> Result<()> {
+    if cap == 0 {
+        return Ok(());
+    }
+    let entry = HistoryEntry {
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        connection: connection.to_string(),
+        sql: sql.trim().to_string(),
+    };
+    let path = history_path()?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    drop(file);
+
+    let data = fs::read_to_string(&path)?;
+    let lines: Vec<&str> = data.lines().collect();
+    if lines.len() > cap {
+        // Write aside and rename so a crash can't leave the history half-written
+        let tmp = path.with_extension("tmp");
+        let mut kept = lines[lines.len() - cap..].join("\n");
+        kept.push('\n');
+        fs::write(&tmp, kept).with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something a key can be bound to. Names are used as-is in the config's
+/// `keys` section; the same key may serve different actions in different views.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    // Everywhere
+    MoveUp,
+    MoveDown,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    MoveTop,
+    MoveBottom,
+    Select,
+    Back,
+    Help,
+    FindTable,
+    SearchValues,
+    ShrinkTree,
+    GrowTree,
+    ToggleZoom,
+    // Connection list
+    GenerateConfig,
+    AddConnection,
+    EditConnection,
+    DeleteConnection,
+    TestConnection,
+    CopyUrl,
+    CopyUrlWithPassword,
+    // Database tree
+    Expand,
+    Fold,
+    ToggleExpand,
+    ExpandAll,
+    CollapseAll,
+    Filter,
+    Refresh,
+    OpenCli,
+    // Table view
+    FocusRecords,
+    FocusSql,
+    FocusProperties,
+    SwitchPanel,
+    ScrollLeft,
+    ScrollRight,
+    JumpLeft,
+    JumpRight,
+    FirstColumn,
+    LastColumn,
+    NextPage,
+    PrevPage,
+    Search,
+    ToggleSearchCase,
+    GotoRow,
+    NextMatch,
+    PrevMatch,
+    EditWhere,
+    EditCell,
+    DeleteRow,
+    InsertRow,
+    CycleAggregate,
+    ToggleRawValues,
+    ToggleFitColumns,
+    ToggleWrap,
+    ToggleBookmark,
+    NextBookmark,
+    PrevBookmark,
+    CountRows,
+    ExportCsv,
+    ExportJson,
+    CopyMarkdown,
+    EditSql,
+    RunQuery,
+    QueryHistory,
+    SaveQuery,
+    OpenQuery,
+    Explain,
+    ExplainAnalyze,
+    ToggleIndexes,
+    ToggleForeignKeys,
+    ToggleDdl,
+    Copy,
+}
+
+/// Bindings shipped by default, matching the keys documented in the README
+const DEFAULTS: &[(Action, &[&str])] = &[
+    (Action::MoveUp, &["Up", "k"]),
+    (Action::MoveDown, &["Down", "j"]),
+    (Action::PageUp, &["PageUp"]),
+    (Action::PageDown, &["PageDown"]),
+    (Action::HalfPageUp, &["Ctrl-U"]),
+    (Action::HalfPageDown, &["Ctrl-D"]),
+    (Action::MoveTop, &["Home", "g"]),
+    (Action::MoveBottom, &["End", "G"]),
+    (Action::Select, &["Enter"]),
+    (Action::Back, &["Esc"]),
+    (Action::Help, &["?"]),
+    (Action::FindTable, &["Ctrl-T", "Ctrl-P"]),
+    (Action::SearchValues, &["Ctrl-G"]),
+    (Action::ShrinkTree, &["<"]),
+    (Action::GrowTree, &[">"]),
+    (Action::ToggleZoom, &["z"]),
+    // Only offered while there is no config file; MoveTop takes `g` after that
+    (Action::GenerateConfig, &["g"]),
+    (Action::AddConnection, &["a"]),
+    (Action::EditConnection, &["e"]),
+    (Action::DeleteConnection, &["d"]),
+    (Action::TestConnection, &["t"]),
+    (Action::CopyUrl, &["c"]),
+    (Action::CopyUrlWithPassword, &["C"]),
+    (Action::Expand, &["Right", "l"]),
+    (Action::Fold, &["Left", "h"]),
+    (Action::ToggleExpand, &["Tab"]),
+    (Action::ExpandAll, &["E"]),
+    (Action::CollapseAll, &["C"]),
+    (Action::Filter, &["/"]),
+    (Action::Refresh, &["r"]),
+    (Action::OpenCli, &["o"]),
+    (Action::FocusRecords, &["1"]),
+    (Action::FocusSql, &["2"]),
+    (Action::FocusProperties, &["3"]),
+    (Action::SwitchPanel, &["Tab"]),
+    (Action::ScrollLeft, &["Left", "h"]),
+    (Action::ScrollRight, &["Right", "l"]),
+    (Action::JumpLeft, &["["]),
+    (Action::JumpRight, &["]"]),
+    (Action::FirstColumn, &["Ctrl-A"]),
+    (Action::LastColumn, &["Ctrl-E"]),
+    (Action::NextPage, &["n"]),
+    (Action::PrevPage, &["p"]),
+    (Action::Search, &["/"]),
+    (Action::ToggleSearchCase, &["I"]),
+    (Action::GotoRow, &[":"]),
+    (Action::NextMatch, &["n"]),
+    (Action::PrevMatch, &["N"]),
+    (Action::EditWhere, &["w"]),
+    (Action::EditCell, &["e"]),
+    (Action::DeleteRow, &["d"]),
+    (Action::InsertRow, &["i"]),
+    (Action::CycleAggregate, &["a"]),
+    (Action::ToggleRawValues, &["v"]),
+    (Action::ToggleFitColumns, &["="]),
+    (Action::ToggleWrap, &["W"]),
+    (Action::ToggleBookmark, &["m"]),
+    (Action::NextBookmark, &["'"]),
+    (Action::PrevBookmark, &["\""]),
+    (Action::CountRows, &["c"]),
+    (Action::ExportCsv, &["Ctrl-S"]),
+    (Action::ExportJson, &["Ctrl-O"]),
+    (Action::CopyMarkdown, &["Y"]),
+    (Action::EditSql, &["i"]),
+    // Most terminals report Ctrl-Enter as Ctrl-J (a bare line feed)
+    (Action::RunQuery, &["Ctrl-Enter", "Ctrl-J"]),
+    (Action::QueryHistory, &["Ctrl-R"]),
+    (Action::SaveQuery, &["Ctrl-W"]),
+    (Action::OpenQuery, &["Ctrl-L"]),
+    (Action::Explain, &["Ctrl-X"]),
+    (Action::ExplainAnalyze, &["Alt-X"]),
+    (Action::ToggleIndexes, &["i"]),
+    (Action::ToggleForeignKeys, &["f"]),
+    (Action::ToggleDdl, &["s"]),
+    (Action::Copy, &["y"]),
+];
+
+/// A key plus the Ctrl/Alt modifiers, written like `j`, `G`, `PageDown`,
+/// `Ctrl-F` or `Shift-Tab`
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+/// Modifiers a chord must match exactly; Shift is carried by the key itself
+/// (`G`, `BackTab`)
+const CHORD_MODIFIERS: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::ALT);
+
+impl KeyChord {
+    fn matches(&self, key: &KeyEvent) -> bool {
+        self.code == key.code && self.modifiers == key.modifiers.intersection(CHORD_MODIFIERS)
+    }
+}
+
+impl TryFrom<String> for KeyChord {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut shift = false;
+        let mut rest = s.as_str();
+        // A lone `-` or `+` is the key itself, not a separator
+        while rest.len() > 1 {
+            let Some((prefix, tail)) = rest.split_once(['-', '+']) else { break };
+            match prefix.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" | "meta" => modifiers |= KeyModifiers::ALT,
+                "shift" => shift = true,
+                _ => break,
+            }
+            rest = tail;
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if !modifiers.is_empty() => KeyCode::Char(c.to_ascii_lowercase()),
+            (Some(c), None) if shift => KeyCode::Char(c.to_ascii_uppercase()),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "tab" if shift => KeyCode::BackTab,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                f => match f.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key {:?}", s)),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if !self.modifiers.is_empty() => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::BackTab => write!(f, "Shift-Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// One key or a list of them, as written under `keys`
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum KeyChords {
+    One(KeyChord),
+    Many(Vec<KeyChord>),
+}
+
+/// Keys for every action: the defaults, with the config's `keys` section
+/// replacing the bindings of the actions it names
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyChord>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULTS
+            .iter()
+            .map(|(action, keys)| {
+                let chords = keys
+                    .iter()
+                    .map(|k| KeyChord::try_from(k.to_string()).expect("default key binding parses"))
+                    .collect();
+                (*action, chords)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    pub fn new(overrides: &HashMap<Action, KeyChords>) -> Self {
+        let mut keymap = Self::default();
+        for (action, chords) in overrides {
+            let chords = match chords {
+                KeyChords::One(chord) => vec![*chord],
+                KeyChords::Many(chords) => chords.clone(),
+            };
+            keymap.bindings.insert(*action, chords);
+        }
+        keymap
+    }
+
+    /// Whether `key` is bound to `action`
+    pub fn is(&self, action: Action, key: &KeyEvent) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|chords| chords.iter().any(|c| c.matches(key)))
+    }
+
+    /// The keys of `action` for the help overlay, e.g. `↑ / k`
+    pub fn describe(&self, action: Action) -> String {
+        match self.bindings.get(&action).map(Vec::as_slice) {
+            None | Some([]) => "(unbound)".to_string(),
+            Some(chords) => chords.iter().map(KeyChord::to_string).collect::<Vec<_>>().join(" / "),
+        }
+    }
+
+    /// Help overlay entries for `actions`, each paired with its description
+    pub fn help(&self, actions: &[(Action, &'static str)]) -> Vec<(String, &'static str)> {
+        actions.iter().map(|(action, text)| (self.describe(*action), *text)).collect()
+    }
+}
@@ -3,9 +3,73 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
 
-static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+static LOG_FILE: OnceLock<Mutex<LogFile>> = OnceLock::new();
 static LEVEL: OnceLock<LogLevel> = OnceLock::new();
 
+/// Rotate once the log grows past this many bytes, unless
+/// `CLAZYDBM_LOG_MAX_BYTES` says otherwise
+const DEFAULT_MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept beside the log: `clazydbm.log.1` (newest) up to `.3`
+const KEEP_ROTATED: usize = 3;
+
+struct LogFile {
+    file: File,
+    path: PathBuf,
+    /// Bytes in the current file
+    size: u64,
+    max_bytes: u64,
+}
+
+impl LogFile {
+    fn open(path: &Path, max_bytes: u64) -> std::io::Result<Self> {
+        // Start fresh if an earlier run left the file over the limit
+        if std::fs::metadata(path).is_ok_and(|m| m.len() >= max_bytes) {
+            rotate_files(path)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { file, path: path.to_path_buf(), size, max_bytes })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        self.size += line.len() as u64 + 1;
+        if self.size >= self.max_bytes {
+            rotate_files(&self.path)?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.size = 0;
+        }
+        Ok(())
+    }
+}
+
+/// `path.N` for the N-th most recent rotated log
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Shift `path.1`.. up by one, dropping the oldest, and move `path` to `path.1`
+fn rotate_files(path: &Path) -> std::io::Result<()> {
+    for n in (1..KEEP_ROTATED).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, rotated_path(path, n + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_path(path, 1))
+}
+
+fn max_bytes_from_env() -> u64 {
+    std::env::var("CLAZYDBM_LOG_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_BYTES)
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum LogLevel {
     Error,
@@ -33,7 +97,7 @@ pub fn init(log_path: impl AsRef<Path>) -> std::io::Result<PathBuf> {
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let file = LogFile::open(path, max_bytes_from_env())?;
     let _ = LOG_FILE.set(Mutex::new(file));
     let _ = LEVEL.set(LogLevel::from_env());
     info(&format!("logging initialized: {}", path.display()));
@@ -66,8 +130,7 @@ fn enabled(level: LogLevel) -> bool {
 fn write_line(level: &str, msg: &str) {
     if let Some(m) = LOG_FILE.get() {
         if let Ok(mut f) = m.lock() {
-            let _ = writeln!(f, "{} [{}] {}", now_ts(), level, msg);
-            let _ = f.flush();
+            let _ = f.write_line(&format!("{} [{}] {}", now_ts(), level, msg));
         }
     }
 }
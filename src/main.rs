@@ -1,18 +1,33 @@
 mod app;
+mod clipboard;
 mod component;
 mod config;
 mod connection;
 mod db;
+mod history;
+mod keymap;
 mod logger;
+mod queries;
+mod settings;
+mod state;
+mod status;
 mod terminal;
+mod theme;
 mod update;
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::{BufWriter, Write};
+use std::time::Duration;
 
 use crate::{
-    app::run_app,
-    logger::{error, init},
-    terminal::with_terminal,
+    app::{run_app, Launch},
+    component::PendingTable,
+    config::Config,
+    connection::Connection,
+    db::{export, split_table_path, DBBehavior, RecordQuery, DB},
+    logger::{error, init, warn},
+    state::State,
+    terminal::{ScreenMode, install_panic_hook, with_terminal},
 };
 
 #[derive(Parser)]
@@ -22,6 +37,67 @@ struct Cli {
     /// Path to additional config file
     #[arg(short, long)]
     config: Option<String>,
+
+    /// Draw in the main screen buffer instead of the alternate screen
+    #[arg(long)]
+    no_alt_screen: bool,
+
+    /// Wait until the named connection accepts queries before starting
+    #[arg(long, value_name = "CONNECTION")]
+    wait_for: Option<String>,
+
+    /// Give up waiting after this many seconds
+    #[arg(long, value_name = "SECS", default_value_t = 60, requires = "wait_for")]
+    wait_timeout: u64,
+
+    /// Open this connection from the config instead of the connection list
+    #[arg(long, value_name = "NAME")]
+    connection: Option<String>,
+
+    /// Then open this table: `table`, `database.table` or `database.schema.table`
+    #[arg(long, requires = "connection")]
+    table: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+/// Work done without the TUI; with none given the TUI starts
+#[derive(Subcommand)]
+enum CliCommand {
+    /// Write a table's rows to stdout
+    Export(ExportArgs),
+}
+
+#[derive(clap::Args)]
+struct ExportArgs {
+    /// Name of the connection in the config
+    #[arg(long)]
+    connection: String,
+
+    /// `table`, `database.table` or `database.schema.table`; a bare table
+    /// name needs a connection showing a single database
+    #[arg(long)]
+    table: String,
+
+    /// `json` writes `{"columns": [...], "rows": [[...]]}` with numbers and
+    /// booleans typed, and a `{"error": ...}` object if the export fails
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    format: ExportFormat,
+
+    /// Only rows matching this SQL predicate
+    #[arg(long = "where", value_name = "PREDICATE")]
+    filter: Option<String>,
+
+    /// Stop after this many rows
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -38,7 +114,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let _ = init(log_path);
     }
 
-    let result = with_terminal(run_app);
+    if let Some(name) = &cli.wait_for {
+        wait_for_connection(name, Duration::from_secs(cli.wait_timeout))?;
+    }
+
+    if let Some(CliCommand::Export(args)) = &cli.command {
+        let result = export_table(args);
+        connection::tunnel::close_all();
+        if let (Err(e), ExportFormat::Json) = (&result, args.format) {
+            error(&format!("export failed: {:#}", e));
+            println!("{}", serde_json::json!({ "error": format!("{:#}", e) }));
+            std::process::exit(1);
+        }
+        return Ok(result?);
+    }
+
+    // Checked before the terminal is taken over, so a typo is reported plainly
+    let launch = match &cli.connection {
+        Some(name) => Some(Launch {
+            connection: find_connection(&Config::new()?, name)?,
+            table: cli.table.clone().map(PendingTable::Path),
+        }),
+        None => last_session(),
+    };
+
+    let mode = ScreenMode::detect(cli.no_alt_screen);
+    install_panic_hook(mode);
+    let result = with_terminal(mode, |terminal| run_app(terminal, launch));
+    connection::tunnel::close_all();
 
     if let Err(err) = result {
         println!("{:?}", err);
@@ -47,3 +150,124 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// The configured connection called `name`
+fn find_connection(config: &Config, name: &str) -> anyhow::Result<Connection> {
+    config
+        .conn
+        .iter()
+        .find(|c| c.name.as_deref() == Some(name))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("no connection named '{}' in config", name))
+}
+
+/// The connection (and table) open when the last session ended; `None`, so
+/// the connection list shows, once that connection is no longer configured
+fn last_session() -> Option<Launch> {
+    let state = State::load();
+    let name = state.last_connection?;
+    let config = Config::new().ok()?;
+    match find_connection(&config, &name) {
+        Ok(connection) => Some(Launch { connection, table: state.last_table.map(PendingTable::Last) }),
+        Err(e) => {
+            warn(&format!("Not restoring the last session: {}", e));
+            None
+        }
+    }
+}
+
+/// Block until the configured connection `name` is reachable, reporting each
+/// retry on stderr (the TUI isn't up yet)
+fn wait_for_connection(name: &str, timeout: Duration) -> anyhow::Result<()> {
+    let config = Config::new()?;
+    let conn = &find_connection(&config, name)?;
+    eprintln!("Waiting for {} (up to {}s)...", name, timeout.as_secs());
+    DB::wait_until_ready(|| DB::fetch_databases(conn).map(drop), timeout, |attempt, err, pause| {
+        eprintln!("  attempt {} failed: {}; retrying in {:.1}s", attempt, err, pause.as_secs_f32());
+    })?;
+    eprintln!("{} is ready", name);
+    Ok(())
+}
+
+/// Write the rows of `args.table` to stdout, fetched a `fetch_limit` page at
+/// a time in primary-key order, so pages neither overlap nor skip rows. CSV
+/// is written as pages arrive; JSON once every row is in, typed by the
+/// table's column types.
+fn export_table(args: &ExportArgs) -> anyhow::Result<()> {
+    let config = Config::new()?;
+    let conn = find_connection(&config, &args.connection)?;
+    let (database, schema, table) = match split_table_path(&args.table) {
+        (Some(database), schema, table) => (database.to_string(), schema.map(str::to_string), table.to_string()),
+        (None, _, table) => match &DB::fetch_databases(&conn)?[..] {
+            [db] => (db.name.clone(), None, table.to_string()),
+            _ => anyhow::bail!("{} shows several databases; name one as <database>.{}", args.connection, table),
+        },
+    };
+
+    let properties = DB::fetch_properties(&conn, &database, schema.as_deref(), &table)?;
+    let key: Vec<String> = properties
+        .columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| DB::quote_identifier_for(&conn, &c.name))
+        .collect();
+    let order = (!key.is_empty()).then(|| key.join(", "));
+
+    let mut out = BufWriter::new(std::io::stdout().lock());
+    let mut collected: Option<db::Records> = None;
+    let mut offset = 0;
+    // Keyed tables page in a stable order and need no warning
+    let mut warned = order.is_some();
+    loop {
+        let limit = match args.limit {
+            Some(max) => conn.fetch_limit().min(max - offset),
+            None => conn.fetch_limit(),
+        };
+        if limit == 0 {
+            break;
+        }
+        if offset > 0 && !warned {
+            eprintln!(
+                "warning: {} has no primary key; rows may repeat or go missing between pages",
+                args.table
+            );
+            warned = true;
+        }
+        let query = RecordQuery { limit, offset, filter: args.filter.clone(), order: order.clone(), chunks: None };
+        let page = DB::fetch_records(&conn, &database, schema.as_deref(), &table, &query)?;
+        let (fetched, truncated) = (page.rows.len(), page.truncated);
+        match args.format {
+            ExportFormat::Csv => {
+                if offset == 0 {
+                    export::write_csv_record(&mut out, &page.columns, export::DEFAULT_CSV_DELIMITER)?;
+                }
+                for row in &page.rows {
+                    export::write_csv_record(&mut out, row, export::DEFAULT_CSV_DELIMITER)?;
+                }
+            }
+            ExportFormat::Json => match &mut collected {
+                Some(records) => records.rows.extend(page.rows),
+                None => collected = Some(page),
+            },
+        }
+        offset += fetched;
+        // A page cut short by the memory cap isn't the end of the table
+        if fetched == 0 || (fetched < limit && !truncated) {
+            break;
+        }
+    }
+    if let ExportFormat::Json = args.format {
+        // Nothing fetched (`--limit 0`) still gives the columns
+        let mut records = collected.unwrap_or_else(|| db::Records {
+            columns: properties.columns.iter().map(|c| c.name.clone()).collect(),
+            rows: Vec::new(),
+            truncated: false,
+            blobs: Default::default(),
+            kinds: Vec::new(),
+        });
+        records.set_kinds(&properties.columns);
+        export::write_json_table(&mut out, &records, export::JsonValues::Typed)?;
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+
+use crate::config::Config;
+
+/// Directory in the app config dir holding one `<name>.sql` file per query
+const QUERIES_DIRNAME: &str = "queries";
+
+/// A statement saved from the SQL tab under a name
+#[derive(Debug, Clone)]
+pub struct SavedQuery {
+    pub name: String,
+    pub sql: String,
+}
+
+fn queries_dir() -> Result<PathBuf> {
+    Ok(Config::app_config_dir()?.join(QUERIES_DIRNAME))
+}
+
+/// Names become file names, so keep them to a single path component
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        bail!("invalid query name {:?}", name);
+    }
+    Ok(())
+}
+
+/// Every saved query, by name
+pub fn load_all() -> Result<Vec<SavedQuery>> {
+    let dir = queries_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut queries = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("sql") {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let sql = fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        queries.push(SavedQuery { name: name.to_string(), sql });
+    }
+    queries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(queries)
+}
+
+/// Write `sql` to `<name>.sql`, replacing a query saved under the same name
+pub fn save(name: &str, sql: &str) -> Result<PathBuf> {
+    check_name(name)?;
+    let dir = queries_dir()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.sql", name));
+    let mut contents = sql.trim_end().to_string();
+    contents.push('\n');
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
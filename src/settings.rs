@@ -0,0 +1,84 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Behavior of Enter on a database/schema node (`tree_enter_action` in config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeEnterAction {
+    /// Expand or fold the node
+    #[default]
+    Toggle,
+    /// Open the first table below the node
+    SelectFirstTable,
+    /// Do nothing; use `l`/`h` to expand and fold
+    None,
+}
+
+/// Glyphs drawn in the tree (`tree_icons` in config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeIcons {
+    /// Triangles and emoji
+    #[default]
+    Unicode,
+    /// Plain ASCII for terminals or fonts without emoji
+    Ascii,
+}
+
+/// Width of the tree panel (`tree_width` in config): a share of the screen,
+/// written `"25%"`, or a number of columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "WidthSpec", into = "String")]
+pub enum TreeWidth {
+    Percent(u16),
+    Columns(u16),
+}
+
+impl Default for TreeWidth {
+    fn default() -> Self {
+        TreeWidth::Percent(15)
+    }
+}
+
+impl fmt::Display for TreeWidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeWidth::Percent(p) => write!(f, "{}%", p),
+            TreeWidth::Columns(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl From<TreeWidth> for String {
+    fn from(width: TreeWidth) -> Self {
+        width.to_string()
+    }
+}
+
+/// `tree_width` as written: a bare number of columns, or text
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum WidthSpec {
+    Columns(u16),
+    Text(String),
+}
+
+impl TryFrom<WidthSpec> for TreeWidth {
+    type Error = String;
+
+    fn try_from(spec: WidthSpec) -> Result<Self, Self::Error> {
+        let text = match spec {
+            WidthSpec::Columns(n) => return Ok(TreeWidth::Columns(n)),
+            WidthSpec::Text(text) => text,
+        };
+        let invalid = || format!("invalid tree width {:?}; use a percentage like \"20%\" or a column count", text);
+        match text.trim().strip_suffix('%') {
+            Some(p) => match p.trim().parse::<u16>() {
+                Ok(p) if (1..100).contains(&p) => Ok(TreeWidth::Percent(p)),
+                _ => Err(invalid()),
+            },
+            None => text.trim().parse().map(TreeWidth::Columns).map_err(|_| invalid()),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::component::TableInfo;
+use crate::config::Config;
+use crate::logger::warn;
+use crate::settings::TreeWidth;
+
+/// File in the app config dir remembering choices made in the UI
+const STATE_FILENAME: &str = "state.json";
+
+/// Serializes `update` calls from background tasks, so one can't drop
+/// another's change
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// UI choices kept between runs. Unlike the config, this is written by the
+/// app; missing fields fall back to the config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct State {
+    /// Width of the tree panel, as last resized
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_width: Option<TreeWidth>,
+    /// Name of the connection open when the last session ended; cleared on
+    /// going back to the connection list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_connection: Option<String>,
+    /// Table last opened on that connection
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "ignore_invalid")]
+    pub last_table: Option<TableInfo>,
+}
+
+/// `None` for a value that doesn't parse, such as the `database.schema.table`
+/// string older versions saved, rather than failing the whole file
+fn ignore_invalid<'de, D: Deserializer<'de>, T: serde::de::DeserializeOwned>(d: D) -> Result<Option<T>, D::Error> {
+    Ok(serde_json::from_value(serde_json::Value::deserialize(d)?).ok())
+}
+
+impl State {
+    fn path() -> Result<PathBuf> {
+        Ok(Config::app_config_dir()?.join(STATE_FILENAME))
+    }
+
+    fn read() -> Result<State> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(State::default());
+        }
+        let data = fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// The saved state; a missing or unreadable file gives the defaults
+    pub fn load() -> State {
+        Self::read().unwrap_or_else(|e| {
+            warn(&format!("State: {:#}", e));
+            State::default()
+        })
+    }
+
+    /// Change the saved state with `f` and write it back
+    pub fn update(f: impl FnOnce(&mut State)) -> Result<()> {
+        let _lock = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = Self::read().unwrap_or_default();
+        f(&mut state);
+        let path = Self::path()?;
+        // Write aside and rename so a crash can't leave the file half-written
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&state)?)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_table_keeps_each_part_of_its_name() {
+        let table = TableInfo { database: "db".to_string(), schema: None, table: "v1.2_backup".to_string() };
+        let state = State { last_table: Some(table), ..State::default() };
+        let json = serde_json::to_string(&state).unwrap();
+        let read: State = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.last_table, state.last_table);
+    }
+
+    #[test]
+    fn a_last_table_saved_as_a_path_is_dropped() {
+        let read: State =
+            serde_json::from_str(r#"{"tree_width": "20%", "last_connection": "pg", "last_table": "app.public.users"}"#)
+                .unwrap();
+        assert!(read.last_table.is_none());
+        assert_eq!(read.last_connection.as_deref(), Some("pg"));
+        assert!(read.tree_width.is_some());
+    }
+}
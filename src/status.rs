@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusLevel {
+    Info,
+    Error,
+}
+
+/// A line for the status bar at the bottom of the screen
+#[derive(Debug, Clone)]
+pub struct StatusMessage {
+    pub level: StatusLevel,
+    pub text: String,
+    /// Wall-clock time shown next to the text
+    pub time: DateTime<Local>,
+    shown: Instant,
+}
+
+impl StatusMessage {
+    pub fn info(text: impl Into<String>) -> Self {
+        Self::new(StatusLevel::Info, text.into())
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self::new(StatusLevel::Error, text.into())
+    }
+
+    fn new(level: StatusLevel, text: String) -> Self {
+        Self { level, text, time: Local::now(), shown: Instant::now() }
+    }
+
+    /// Whether the message has been up long enough to clear; errors stay a
+    /// little longer so there is time to read them
+    pub fn is_expired(&self) -> bool {
+        let ttl = match self.level {
+            StatusLevel::Info => Duration::from_secs(4),
+            StatusLevel::Error => Duration::from_secs(8),
+        };
+        self.shown.elapsed() >= ttl
+    }
+}
@@ -1,22 +1,72 @@
 use anyhow::Result;
 use crossterm::{
+    cursor::{MoveTo, Show},
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui;
-use ratatui::{Terminal as RatatuiTerminal, backend::CrosstermBackend};
+use ratatui::{Terminal as RatatuiTerminal, TerminalOptions, Viewport, backend::CrosstermBackend};
 use ratatui::prelude::Backend;
 use std::io::{self, Stdout, stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether `with_terminal` has taken over the terminal and not given it back yet
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// How the TUI occupies the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenMode {
+    /// Draw on the alternate screen buffer (default)
+    Alternate,
+    /// Draw in the main buffer below the prompt, for terminals without
+    /// alternate-screen support. Existing output is scrolled up, not overwritten.
+    Inline,
+}
+
+impl ScreenMode {
+    /// `--no-alt-screen` forces inline mode; terminals known to lack the
+    /// alternate screen (`TERM=dumb`, the Linux console) get it automatically.
+    pub fn detect(no_alt_screen: bool) -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        if no_alt_screen || term == "dumb" || term == "linux" {
+            ScreenMode::Inline
+        } else {
+            ScreenMode::Alternate
+        }
+    }
+
+    fn enter(self, w: &mut impl Write) -> io::Result<()> {
+        match self {
+            ScreenMode::Alternate => execute!(w, EnterAlternateScreen),
+            ScreenMode::Inline => Ok(()),
+        }
+    }
+
+    fn leave(self, w: &mut impl Write) -> io::Result<()> {
+        match self {
+            ScreenMode::Alternate => execute!(w, LeaveAlternateScreen),
+            ScreenMode::Inline => Ok(()),
+        }
+    }
+
+    fn viewport(self) -> io::Result<Viewport> {
+        Ok(match self {
+            ScreenMode::Alternate => Viewport::Fullscreen,
+            ScreenMode::Inline => Viewport::Inline(crossterm::terminal::size()?.1),
+        })
+    }
+}
 
 /// Custom terminal wrapper that handles suspension and restoration
 pub struct Terminal<B: Backend> {
     inner: RatatuiTerminal<B>,
+    mode: ScreenMode,
 }
 
 impl<B: Backend> Terminal<B> {
-    pub fn new(terminal: RatatuiTerminal<B>) -> Self {
-        Self { inner: terminal }
+    pub fn new(terminal: RatatuiTerminal<B>, mode: ScreenMode) -> Self {
+        Self { inner: terminal, mode }
     }
 
     /// Execute a closure with suspended terminal
@@ -37,7 +87,7 @@ impl<B: Backend> Terminal<B> {
         
         // Leave alternate screen
         let mut stdout = stdout();
-        write!(stdout, "\x1b[?1049l")?; // Exit alternate screen buffer
+        self.mode.leave(&mut stdout)?;
         stdout.flush()?;
         
         // Disable raw mode
@@ -53,7 +103,7 @@ impl<B: Backend> Terminal<B> {
         
         // Re-enter alternate screen
         let mut stdout = stdout();
-        write!(stdout, "\x1b[?1049h")?; // Enter alternate screen buffer
+        self.mode.enter(&mut stdout)?;
         stdout.flush()?;
         
         // Clear and redraw
@@ -71,6 +121,25 @@ impl<B: Backend> Terminal<B> {
         Ok(())
     }
 
+    /// Fit the viewport to the terminal's new size and forget the last frame,
+    /// so the next draw repaints every cell rather than diffing against
+    /// content the terminal may have reflowed
+    pub fn resize(&mut self) -> Result<()> {
+        self.inner.autoresize()?;
+        self.inner.clear()?;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn backend(&self) -> &B {
+        self.inner.backend()
+    }
+
+    #[cfg(test)]
+    pub fn backend_mut(&mut self) -> &mut B {
+        self.inner.backend_mut()
+    }
+
     /// Delegate to the inner terminal's clear method
     #[allow(dead_code)]
     pub fn clear(&mut self) -> Result<()> {
@@ -79,27 +148,129 @@ impl<B: Backend> Terminal<B> {
     }
 }
 
+/// Undo `with_terminal`'s setup. Only the first call after setup does
+/// anything, so the panic hook and `TerminalGuard` can both call it.
+fn restore_terminal(mode: ScreenMode) -> io::Result<()> {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    disable_raw_mode()?;
+    let mut stdout = io::stdout();
+    mode.leave(&mut stdout)?;
+    execute!(stdout, DisableMouseCapture)?;
+    if mode == ScreenMode::Inline {
+        // Leave the prompt below the last drawn frame instead of on top of it
+        let (_, rows) = crossterm::terminal::size()?;
+        execute!(stdout, MoveTo(0, rows.saturating_sub(1)), Show)?;
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+/// Runs `restore` when dropped, covering early returns and panics
+/// unwinding out of `with_terminal`
+struct TerminalGuard<F: FnMut() -> io::Result<()>> {
+    restore: Option<F>,
+}
+
+impl<F: FnMut() -> io::Result<()>> TerminalGuard<F> {
+    /// Restore now, reporting a failure; the drop then does nothing
+    fn restore(mut self) -> io::Result<()> {
+        self.restore.take().map_or(Ok(()), |mut restore| restore())
+    }
+}
+
+impl<F: FnMut() -> io::Result<()>> Drop for TerminalGuard<F> {
+    fn drop(&mut self) {
+        if let Some(mut restore) = self.restore.take() {
+            let _ = restore();
+        }
+    }
+}
+
+/// Run `f`, then `restore` once however it ends: a failed restore is
+/// reported when `f` returns, and the guard still restores if it panics
+fn run_restoring<R>(restore: impl FnMut() -> io::Result<()>, f: impl FnOnce() -> Result<R>) -> Result<R> {
+    let guard = TerminalGuard { restore: Some(restore) };
+    let result = f();
+    guard.restore()?;
+    result
+}
+
+/// Give the terminal back before a panic message is printed, so it is
+/// readable and the shell isn't left in raw mode on the alternate screen.
+/// Panics in background tasks leave the TUI running and are only reported.
+pub fn install_panic_hook(mode: ScreenMode) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            let _ = restore_terminal(mode);
+        }
+        default_hook(info);
+    }));
+}
+
 /// Terminal wrapper that handles setup and cleanup automatically
-pub fn with_terminal<F, R>(f: F) -> Result<R>
+pub fn with_terminal<F, R>(mode: ScreenMode, f: F) -> Result<R>
 where
     F: FnOnce(Terminal<CrosstermBackend<Stdout>>) -> Result<R>,
 {
     // Setup terminal
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    run_restoring(
+        || restore_terminal(mode),
+        || {
+            let mut stdout = io::stdout();
+            mode.enter(&mut stdout)?;
+            execute!(stdout, EnableMouseCapture)?;
 
-    let backend = CrosstermBackend::new(stdout);
-    let ratatui_terminal = RatatuiTerminal::new(backend)?;
-    let terminal = Terminal::new(ratatui_terminal);
+            let backend = CrosstermBackend::new(stdout);
+            let options = TerminalOptions { viewport: mode.viewport()? };
+            let ratatui_terminal = RatatuiTerminal::with_options(backend, options)?;
+            let terminal = Terminal::new(ratatui_terminal, mode);
 
-    // Run the function with the terminal
-    let result = f(terminal);
+            // Run the function with the terminal
+            f(terminal)
+        },
+    )
+}
 
-    // Cleanup terminal state
-    disable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, LeaveAlternateScreen, DisableMouseCapture)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
 
-    result
+    #[test]
+    fn restores_once_after_a_normal_return() {
+        let restores = Cell::new(0);
+        let result = run_restoring(|| Ok(restores.set(restores.get() + 1)), || Ok(42));
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(restores.get(), 1);
+    }
+
+    #[test]
+    fn restores_after_an_error_and_keeps_it() {
+        let restores = Cell::new(0);
+        let result: Result<()> = run_restoring(|| Ok(restores.set(restores.get() + 1)), || anyhow::bail!("boom"));
+        assert_eq!(result.unwrap_err().to_string(), "boom");
+        assert_eq!(restores.get(), 1);
+    }
+
+    #[test]
+    fn restores_when_the_closure_panics() {
+        let restores = Cell::new(0);
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            run_restoring(|| Ok(restores.set(restores.get() + 1)), || -> Result<()> { panic!("boom") })
+        }));
+        assert!(outcome.is_err());
+        assert_eq!(restores.get(), 1);
+    }
+
+    #[test]
+    fn a_failed_restore_is_reported() {
+        let result = run_restoring(|| Err(io::Error::other("tty gone")), || Ok(()));
+        assert_eq!(result.unwrap_err().to_string(), "tty gone");
+    }
 }
@@ -0,0 +1,39 @@
+use ratatui::style::{Color, Style};
+use serde::Deserialize;
+
+/// Colors of the UI, from the optional `theme` section of the config. Any
+/// color can be a name (`yellow`, `light-blue`), a 256-color index or `#rrggbb`;
+/// unset ones keep the default look.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    /// Border of the focused panel and of popups
+    pub focused_border: Color,
+    /// Border (and tab labels) of the other panels
+    pub unfocused_border: Color,
+    /// Selected list entry and tab
+    pub highlight: Color,
+    /// Column names above grids
+    pub header: Color,
+    /// Placeholder shown for NULL values
+    pub null_cell: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            focused_border: Color::Yellow,
+            unfocused_border: Color::White,
+            highlight: Color::Cyan,
+            header: Color::Reset,
+            null_cell: Color::DarkGray,
+        }
+    }
+}
+
+impl Theme {
+    /// Border style of a panel
+    pub fn border(&self, focused: bool) -> Style {
+        Style::default().fg(if focused { self.focused_border } else { self.unfocused_border })
+    }
+}
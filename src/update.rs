@@ -1,7 +1,7 @@
 use std::sync::mpsc::Sender;
 
 use crate::app::AppMsg;
-use crate::component::{ConnectionMsg, DashboardMsg, DBListMsg, RootMsg, TableMsg};
+use crate::component::{ConfirmMsg, ConnectionMsg, DashboardMsg, DBListMsg, FinderMsg, HistoryMsg, QueriesMsg, RootMsg, TableMsg, ValueSearchMsg};
 
 #[allow(dead_code)]
 pub enum Command {
@@ -85,6 +85,31 @@ impl From<TableMsg> for Update<TableMsg> {
         Update::msg(msg)
     }
 }
+impl From<ConfirmMsg> for Update<ConfirmMsg> {
+    fn from(msg: ConfirmMsg) -> Self {
+        Update::msg(msg)
+    }
+}
+impl From<FinderMsg> for Update<FinderMsg> {
+    fn from(msg: FinderMsg) -> Self {
+        Update::msg(msg)
+    }
+}
+impl From<HistoryMsg> for Update<HistoryMsg> {
+    fn from(msg: HistoryMsg) -> Self {
+        Update::msg(msg)
+    }
+}
+impl From<QueriesMsg> for Update<QueriesMsg> {
+    fn from(msg: QueriesMsg) -> Self {
+        Update::msg(msg)
+    }
+}
+impl From<ValueSearchMsg> for Update<ValueSearchMsg> {
+    fn from(msg: ValueSearchMsg) -> Self {
+        Update::msg(msg)
+    }
+}
 impl From<DashboardMsg> for Update<DashboardMsg> {
     fn from(msg: DashboardMsg) -> Self {
         Update::msg(msg)